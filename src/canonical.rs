@@ -0,0 +1,379 @@
+//! Canonical blank node labels and content hashes for stores.
+//!
+//! This follows the RDF Dataset Canonicalization algorithm (RDFC-1.0, formerly known as
+//! URDNA2015, see http://www.w3.org/TR/rdf-canon/) except that SHA-1 (from std::sha1) is
+//! used instead of SHA-256 and, because stores have no named graphs, every triple is in
+//! the default graph.
+use std::sha1::{Sha1};
+
+// Issues new identifiers for blank nodes in the order they are first asked for.
+priv struct IdIssuer
+{
+	prefix: ~str,
+	ids: HashMap<@~str, @~str>,
+	order: DVec<~str>,
+}
+
+// Blank_triples maps each blank node to the indices of the triples which use it.
+priv type CanonState = {triples: ~[Triple], blanks: ~[~str], blank_triples: HashMap<@~str, @DVec<uint>>, first_degree: HashMap<@~str, @~str>, canonical: @IdIssuer};
+
+/// Returns a map from the store's blank node labels to canonical labels (_:c14n0, _:c14n1, etc).
+///
+/// Stores which are isomorphic will have the same triples after relabeling.
+pub fn canonical_labels(store: &Store) -> HashMap<@~str, @~str>
+{
+	let state = make_canon_state(store);
+	
+	// Group the blank nodes by their first degree hashes.
+	let hashes = HashMap();
+	for state.blanks.each
+	|blank|
+	{
+		let hash = state.first_degree.get(@copy *blank);
+		match hashes.find(hash)
+		{
+			option::Some(list) => list.push(copy *blank),
+			option::None => hashes.insert(hash, @dvec::from_vec(~[copy *blank])),
+		}
+	}
+	
+	// Blank nodes with unique hashes are issued ids in hash order.
+	let mut shared = ~[];
+	for sorted_keys(hashes).each
+	|hash|
+	{
+		let list = hashes.get(@copy *hash);
+		if list.len() == 1
+		{
+			issue_id(state.canonical, list.get_elt(0));
+		}
+		else
+		{
+			vec::push(&mut shared, list.get());
+		}
+	}
+	
+	// The remaining blank nodes are distinguished using the blank nodes they are connected to.
+	for shared.each
+	|list|
+	{
+		let mut results = ~[];
+		for list.each
+		|blank|
+		{
+			if !state.canonical.ids.contains_key(@copy *blank)
+			{
+				let issuer = IdIssuer("_:b");
+				issue_id(issuer, *blank);
+				vec::push(&mut results, hash_n_degree(&state, *blank, issuer));
+			}
+		}
+		
+		for std::sort::merge_sort(results, |x, y| {x.first() <= y.first()}).each
+		|result|
+		{
+			let issuer = result.second();
+			for issuer.order.each |blank|
+			{
+				issue_id(state.canonical, *blank);
+			}
+		}
+	}
+	
+	state.canonical.ids
+}
+
+/// Returns the store's triples as N-Triples using canonical blank node labels.
+///
+/// The lines are sorted and duplicate triples are removed so two stores with the same
+/// triples will have the same output regardless of how their blank nodes are labeled.
+/// Language tags are converted to lower case.
+pub fn store_to_canonical_ntriples(store: &Store) -> ~str
+{
+	let labels = canonical_labels(store);
+	
+	let mut lines = ~[];
+	for store.each
+	|triple|
+	{
+		vec::push(&mut lines, canonical_line(triple, |name| {copy *labels.get(@name.to_owned())}));
+	};
+	let lines = std::sort::merge_sort(lines, |x, y| {*x <= *y});
+	
+	let mut result = ~"";
+	for lines.eachi
+	|i, line|
+	{
+		if i == 0 || *line != lines[i - 1]
+		{
+			result += *line;
+		}
+	}
+	result
+}
+
+/// Returns a hex encoded SHA-1 hash of store_to_canonical_ntriples.
+///
+/// This can be used to check whether two stores have the same content (or to sign a store).
+pub fn store_hash(store: &Store) -> ~str
+{
+	sha1_hex(store_to_canonical_ntriples(store))
+}
+
+// ---- Private Functions -----------------------------------------------------
+priv fn IdIssuer(prefix: &str) -> @IdIssuer
+{
+	@IdIssuer {prefix: prefix.to_owned(), ids: HashMap(), order: DVec()}
+}
+
+priv fn issue_id(issuer: @IdIssuer, name: &str) -> ~str
+{
+	match issuer.ids.find(@name.to_owned())
+	{
+		option::Some(id) =>
+		{
+			copy *id
+		}
+		option::None =>
+		{
+			let id = fmt!("%s%?", issuer.prefix, issuer.order.len());
+			issuer.ids.insert(@name.to_owned(), @copy id);
+			issuer.order.push(name.to_owned());
+			id
+		}
+	}
+}
+
+priv fn copy_issuer(issuer: @IdIssuer) -> @IdIssuer
+{
+	let result = IdIssuer(issuer.prefix);
+	for issuer.order.each |name|
+	{
+		issue_id(result, *name);
+	}
+	result
+}
+
+priv fn make_canon_state(store: &Store) -> CanonState
+{
+	let mut triples = ~[];
+	for store.each
+	|triple|
+	{
+		vec::push(&mut triples, copy *triple);
+	};
+	
+	let blank_triples = HashMap();
+	for triples.eachi
+	|i, triple|
+	{
+		let mut names = ~[];
+		if is_blank(triple.subject)
+		{
+			vec::push(&mut names, copy triple.subject);
+		}
+		match *triple.object
+		{
+			BlankValue(ref name) => vec::push(&mut names, copy *name),
+			_ => (),
+		}
+		
+		for names.each
+		|name|
+		{
+			match blank_triples.find(@copy *name)
+			{
+				option::Some(list) =>
+				{
+					// Triples like "_:a p _:a" are only added once.
+					if list.get_elt(list.len() - 1) != i
+					{
+						list.push(i);
+					}
+				}
+				option::None =>
+				{
+					blank_triples.insert(@copy *name, @dvec::from_vec(~[i]));
+				}
+			}
+		}
+	}
+	
+	let state = {triples: triples, blanks: sorted_keys(blank_triples), blank_triples: blank_triples, first_degree: HashMap(), canonical: IdIssuer("_:c14n")};
+	for state.blanks.each
+	|blank|
+	{
+		state.first_degree.insert(@copy *blank, @hash_first_degree(&state, *blank));
+	}
+	state
+}
+
+// Hashes the triples which use the blank node with the blank node relabeled to _:a and
+// all other blank nodes relabeled to _:z.
+priv fn hash_first_degree(state: &CanonState, blank: &str) -> ~str
+{
+	let lines = do state.blank_triples.get(@blank.to_owned()).get().map
+	|i|
+	{
+		canonical_line(&state.triples[*i], |name| {if str::eq_slice(name, blank) {~"_:a"} else {~"_:z"}})
+	};
+	sha1_hex(str::concat(std::sort::merge_sort(lines, |x, y| {*x <= *y})))
+}
+
+priv fn hash_related_blank(state: &CanonState, related: &str, triple: &Triple, issuer: @IdIssuer, position: &str) -> ~str
+{
+	let key = @related.to_owned();
+	let id = match state.canonical.ids.find(key)
+	{
+		option::Some(id) => copy *id,
+		option::None =>
+		{
+			match issuer.ids.find(key)
+			{
+				option::Some(id) => copy *id,
+				option::None => copy *state.first_degree.get(key),
+			}
+		}
+	};
+	sha1_hex(fmt!("%s<%s>%s", position, triple.predicate, id))
+}
+
+// Returns a hash for the blank node computed from the blank nodes it is connected to
+// (recursively) along with the issuer used to label them.
+priv fn hash_n_degree(state: &CanonState, blank: &str, issuer: @IdIssuer) -> (~str, @IdIssuer)
+{
+	let mut issuer = issuer;
+	
+	// Group the related blank nodes by their hashes.
+	let related = HashMap();
+	for state.blank_triples.get(@blank.to_owned()).each
+	|i|
+	{
+		let triple = &state.triples[*i];
+		let mut names = ~[];
+		if is_blank(triple.subject) && !str::eq_slice(triple.subject, blank)
+		{
+			vec::push(&mut names, (copy triple.subject, "s"));
+		}
+		match *triple.object
+		{
+			BlankValue(ref name) if !str::eq_slice(*name, blank) => vec::push(&mut names, (copy *name, "o")),
+			_ => (),
+		}
+		
+		for names.each
+		|pair|
+		{
+			let (name, position) = copy *pair;
+			let hash = @hash_related_blank(state, name, triple, issuer, position);
+			match related.find(hash)
+			{
+				option::Some(list) => list.push(name),
+				option::None => related.insert(hash, @dvec::from_vec(~[name])),
+			}
+		}
+	}
+	
+	let mut data = ~"";
+	for sorted_keys(related).each
+	|hash|
+	{
+		data += *hash;
+		
+		// Find the permutation of the related blank nodes with the smallest path.
+		let mut chosen_path = ~"";
+		let mut chosen_issuer = issuer;
+		for vec::each_permutation(related.get(@copy *hash).get())
+		|permutation|
+		{
+			let mut issuer_copy = copy_issuer(issuer);
+			let mut path = ~"";
+			let mut recursion = ~[];
+			let mut skip = false;
+			for permutation.each
+			|name|
+			{
+				match state.canonical.ids.find(@copy *name)
+				{
+					option::Some(id) =>
+					{
+						path += *id;
+					}
+					option::None =>
+					{
+						if !issuer_copy.ids.contains_key(@copy *name)
+						{
+							vec::push(&mut recursion, copy *name);
+						}
+						path += issue_id(issuer_copy, *name);
+					}
+				}
+				
+				if chosen_path.is_not_empty() && path.len() >= chosen_path.len() && path > chosen_path
+				{
+					skip = true;
+					break;
+				}
+			}
+			
+			if !skip
+			{
+				for recursion.each
+				|name|
+				{
+					let (result_hash, result_issuer) = hash_n_degree(state, *name, issuer_copy);
+					path += issue_id(issuer_copy, *name);
+					path += fmt!("<%s>", result_hash);
+					issuer_copy = result_issuer;
+					
+					if chosen_path.is_not_empty() && path.len() >= chosen_path.len() && path > chosen_path
+					{
+						skip = true;
+						break;
+					}
+				}
+			}
+			
+			if !skip && (chosen_path.is_empty() || path < chosen_path)
+			{
+				chosen_path = path;
+				chosen_issuer = issuer_copy;
+			}
+		}
+		
+		data += chosen_path;
+		issuer = chosen_issuer;
+	}
+	
+	(sha1_hex(data), issuer)
+}
+
+// Returns the N-Triples line for the triple using relabel to rename blank nodes.
+priv fn canonical_line(triple: &Triple, relabel: fn (&str) -> ~str) -> ~str
+{
+	let subject = if is_blank(triple.subject) {relabel(triple.subject)} else {copy triple.subject};
+	let object = match *triple.object
+	{
+		BlankValue(ref name) => @BlankValue(relabel(*name)),
+		StringValue(ref value, ref lang) => @StringValue(copy *value, str::to_lower(*lang)),
+		_ => triple.object,
+	};
+	triple_to_ntriples(&{subject: subject, predicate: copy triple.predicate, object: object}) + "\n"
+}
+
+priv fn sorted_keys<T: Copy>(map: HashMap<@~str, T>) -> ~[~str]
+{
+	let mut keys = ~[];
+	for map.each_key |key|
+	{
+		vec::push(&mut keys, copy *key);
+	};
+	std::sort::merge_sort(keys, |x, y| {*x <= *y})
+}
+
+priv fn sha1_hex(text: &str) -> ~str
+{
+	let hasher = std::sha1::sha1();
+	hasher.input_str(text);
+	hasher.result_str()
+}
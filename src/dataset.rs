@@ -0,0 +1,230 @@
+//! Collections of graphs used to keep triples from different sources separate.
+
+/// A default graph plus zero or more named graphs.
+///
+/// Named graphs are keyed by an IRI (or blank node name) and are created as triples are
+/// added to them. DatasetTrait methods use an empty graph name for the default graph.
+///
+/// Blank node labels are scoped to the whole dataset so each graph's next_blank is set to
+/// the largest next_blank of any graph when the graph is returned by add_graph (or changed
+/// using DatasetTrait). Use add_graph rather than get_graph when creating blank nodes.
+pub struct Dataset
+{
+	pub default_graph: @Store,
+	pub named_graphs: HashMap<@~str, @Store>,
+	
+	drop {}
+}
+
+/// Initializes a dataset with an empty default graph.
+///
+/// The namespaces and extensions are shared by all of the graphs in the dataset.
+pub fn Dataset(namespaces: &[Namespace], extensions: &HashMap<@~str, ExtensionFn>) -> Dataset
+{
+	Dataset {
+		default_graph: @Store(namespaces, extensions),
+		named_graphs: HashMap(),
+	}
+}
+
+/// Adds a namespace to every graph in the dataset replacing any existing namespace with the same prefix.
+pub fn add_dataset_namespace(dataset: &Dataset, namespace: Namespace)
+{
+	add_namespace(dataset.default_graph, copy namespace);
+	for dataset.named_graphs.each_value |graph|
+	{
+		add_namespace(graph, copy namespace);
+	};
+}
+
+pub trait DatasetTrait
+{
+	/// Efficient addition of triples to a graph. Returns the number of triples added.
+	fn add(graph: &str, subject: &str, entries: &[(~str, @Object)]) -> uint;
+	
+	/// Relatively inefficient addition of triples to a graph.
+	///
+	/// Qualified names may use the namespaces associated with the dataset and the supplied namespaces.
+	/// Returns false if the triple was not added (see StoreTrait::add_triple).
+	fn add_triple(graph: &str, namespaces: &[Namespace], triple: Triple) -> bool;
+	
+	/// Returns the graph with the name, adding an empty graph if there is no such graph.
+	fn add_graph(graph: &str) -> @Store;
+	
+	/// Removes all triples and named graphs from the dataset.
+	fn clear();
+	
+	/// Returns the first matching object in the graph, or option::none.
+	fn find_object(graph: &str, subject: &str, predicate: &str) -> option::Option<@Object>;
+	
+	/// Returns all matching objects in the graph.
+	fn find_objects(graph: &str, subject: &str, predicate: &str) -> ~[@Object];
+	
+	/// Returns the graph with the name, or option::none if there is no such graph.
+	fn get_graph(graph: &str) -> option::Option<@Store>;
+	
+	/// Returns the sorted names of the named graphs.
+	fn get_graph_names() -> ~[~str];
+	
+	/// Removes a named graph and all of its triples. Returns false if there was no such graph.
+	fn remove_graph(graph: &str) -> bool;
+	
+	/// See StoreTrait::remove_matching.
+	fn remove_matching(graph: &str, subject: option::Option<~str>, predicate: option::Option<~str>, object: option::Option<@Object>) -> uint;
+	
+	/// See StoreTrait::remove_subject.
+	fn remove_subject(graph: &str, subject: &str) -> uint;
+	
+	/// See StoreTrait::remove_triple.
+	fn remove_triple(graph: &str, namespaces: &[Namespace], triple: Triple) -> uint;
+	
+	/// Replaces the object of an existing triple or adds a new triple.
+	fn replace_triple(graph: &str, namespaces: &[Namespace], triple: Triple);
+}
+
+pub impl &Dataset : DatasetTrait
+{
+	fn add(graph: &str, subject: &str, entries: &[(~str, @Object)]) -> uint
+	{
+		get_or_add_graph(self, graph).add(subject, entries)
+	}
+	
+	fn add_triple(graph: &str, namespaces: &[Namespace], triple: Triple) -> bool
+	{
+		get_or_add_graph(self, graph).add_triple(namespaces, triple)
+	}
+	
+	fn add_graph(graph: &str) -> @Store
+	{
+		get_or_add_graph(self, graph)
+	}
+	
+	fn clear()
+	{
+		self.default_graph.clear();
+		
+		let mut names = ~[];
+		for self.named_graphs.each_key |name|
+		{
+			vec::push(&mut names, name);
+		};
+		for names.each |name|
+		{
+			self.named_graphs.remove(*name);
+		}
+	}
+	
+	fn find_object(graph: &str, subject: &str, predicate: &str) -> option::Option<@Object>
+	{
+		do self.get_graph(graph).chain |store| {store.find_object(subject, predicate)}
+	}
+	
+	fn find_objects(graph: &str, subject: &str, predicate: &str) -> ~[@Object]
+	{
+		match self.get_graph(graph)
+		{
+			option::Some(store) => store.find_objects(subject, predicate),
+			option::None => ~[],
+		}
+	}
+	
+	fn get_graph(graph: &str) -> option::Option<@Store>
+	{
+		if graph.is_empty()
+		{
+			option::Some(self.default_graph)
+		}
+		else
+		{
+			self.named_graphs.find(@expand_uri_or_blank(self.default_graph.namespaces, graph))
+		}
+	}
+	
+	fn get_graph_names() -> ~[~str]
+	{
+		let mut names = ~[];
+		for self.named_graphs.each_key |name|
+		{
+			vec::push(&mut names, copy *name);
+		};
+		std::sort::merge_sort(names, |x, y| {*x <= *y})
+	}
+	
+	fn remove_graph(graph: &str) -> bool
+	{
+		self.named_graphs.remove(@expand_uri_or_blank(self.default_graph.namespaces, graph))
+	}
+	
+	fn remove_matching(graph: &str, subject: option::Option<~str>, predicate: option::Option<~str>, object: option::Option<@Object>) -> uint
+	{
+		match self.get_graph(graph)
+		{
+			option::Some(store) => store.remove_matching(subject, predicate, object),
+			option::None => 0,
+		}
+	}
+	
+	fn remove_subject(graph: &str, subject: &str) -> uint
+	{
+		match self.get_graph(graph)
+		{
+			option::Some(store) => store.remove_subject(subject),
+			option::None => 0,
+		}
+	}
+	
+	fn remove_triple(graph: &str, namespaces: &[Namespace], triple: Triple) -> uint
+	{
+		match self.get_graph(graph)
+		{
+			option::Some(store) => store.remove_triple(namespaces, triple),
+			option::None => 0,
+		}
+	}
+	
+	fn replace_triple(graph: &str, namespaces: &[Namespace], triple: Triple)
+	{
+		get_or_add_graph(self, graph).replace_triple(namespaces, triple);
+	}
+}
+
+// ---- Private Functions -----------------------------------------------------
+
+// Named graphs use the same namespaces, extensions, and set_semantics as the default graph.
+priv fn get_or_add_graph(dataset: &Dataset, graph: &str) -> @Store
+{
+	if graph.is_empty()
+	{
+		return sync_next_blank(dataset, dataset.default_graph);
+	}
+	
+	let name = expand_uri_or_blank(dataset.default_graph.namespaces, graph);
+	match dataset.named_graphs.find(@copy name)
+	{
+		option::Some(store) =>
+		{
+			sync_next_blank(dataset, store)
+		}
+		option::None =>
+		{
+			let store = @Store(~[], &dataset.default_graph.extensions);
+			store.namespaces = copy dataset.default_graph.namespaces;
+			store.set_semantics = dataset.default_graph.set_semantics;
+			dataset.named_graphs.insert(@name, store);
+			sync_next_blank(dataset, store)
+		}
+	}
+}
+
+// Blank node labels are scoped to the dataset so graphs have to continue from the
+// largest next_blank used by any of the graphs.
+priv fn sync_next_blank(dataset: &Dataset, store: @Store) -> @Store
+{
+	let mut next_blank = dataset.default_graph.next_blank;
+	for dataset.named_graphs.each_value |graph|
+	{
+		next_blank = int::max(next_blank, graph.next_blank);
+	};
+	store.next_blank = next_blank;
+	store
+}
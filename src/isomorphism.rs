@@ -0,0 +1,355 @@
+//! Comparison of stores and solutions which ignores blank node labels.
+//!
+//! Blank nodes are first partitioned into equivalence classes ("colors") using the
+//! predicates and terms they are connected to. Classes with more than one blank node
+//! are split by trying each candidate pairing and refining again. For most graphs
+//! the refinement alone is enough to determine the mapping.
+
+// Terms are blank node labels (which start with "_:") or strings which uniquely identify
+// an IRI or literal (see term_key).
+priv type TermTriple = (~str, ~str, ~str);
+
+// Edges maps each blank node label to the indices of the triples which use it.
+priv type TermGraph = {triples: ~[TermTriple], blanks: ~[~str], edges: HashMap<@~str, @DVec<uint>>};
+
+/// Returns true if the stores have the same triples after renaming blank nodes.
+///
+/// Literals are compared using RDF term equality (see same_term). Note that if the
+/// stores have duplicate triples the number of duplicates must also match.
+pub fn stores_are_isomorphic(lhs: &Store, rhs: &Store) -> bool
+{
+	graphs_are_isomorphic(&store_to_graph(lhs), &store_to_graph(rhs))
+}
+
+/// Returns true if the solutions have the same selected bindings and rows ignoring the
+/// order of the bindings, the order of the rows, and blank node labels.
+pub fn solutions_are_isomorphic(lhs: &Solution, rhs: &Solution) -> bool
+{
+	let names = std::sort::merge_sort(vec::slice(lhs.bindings, 0, lhs.num_selected), |x, y| {*x <= *y});
+	if names != std::sort::merge_sort(vec::slice(rhs.bindings, 0, rhs.num_selected), |x, y| {*x <= *y})
+	{
+		return false;
+	}
+	
+	graphs_are_isomorphic(&solution_to_graph(lhs, names), &solution_to_graph(rhs, names))
+}
+
+// ---- Private Functions -----------------------------------------------------
+priv pure fn is_blank(term: &str) -> bool
+{
+	str::starts_with(term, "_:")
+}
+
+priv fn term_key(object: &Object) -> ~str
+{
+	match *object
+	{
+		BlankValue(ref name) =>
+		{
+			copy *name
+		}
+		IriValue(ref iri) =>
+		{
+			fmt!("<%s>", *iri)
+		}
+		UnboundValue =>
+		{
+			~"unbound"
+		}
+		ErrorValue(ref err) =>
+		{
+			fmt!("error %?", *err)
+		}
+		_ =>
+		{
+			// Language tags are case-insensitive.
+			let (value, kind, lang) = object_to_literal(object);
+			fmt!("%?^^<%s>@%s", value, kind, str::to_lower(lang))
+		}
+	}
+}
+
+priv fn store_to_graph(store: &Store) -> TermGraph
+{
+	let mut triples = ~[];
+	for store.each
+	|triple|
+	{
+		let subject = if is_blank(triple.subject) {copy triple.subject} else {fmt!("<%s>", triple.subject)};
+		vec::push(&mut triples, (subject, copy triple.predicate, term_key(triple.object)));
+	};
+	make_graph(triples)
+}
+
+// Each row becomes a blank node with an edge to each of its bound values. Row labels
+// contain a space so they cannot collide with blank nodes within the solution.
+priv fn solution_to_graph(solution: &Solution, names: &[~str]) -> TermGraph
+{
+	let indexes = do names.map |name| {solution.bindings.position_elem(name).get()};
+	
+	let mut triples = ~[];
+	for solution.rows.eachi
+	|i, row|
+	{
+		let label = fmt!("_: row %?", i);
+		vec::push(&mut triples, (copy label, ~"", ~"row"));
+		for names.eachi
+		|j, name|
+		{
+			let value = row[indexes[j]];
+			if !value.is_unbound()
+			{
+				vec::push(&mut triples, (copy label, copy *name, term_key(value)));
+			}
+		}
+	}
+	make_graph(triples)
+}
+
+priv fn make_graph(triples: ~[TermTriple]) -> TermGraph
+{
+	let mut blanks = ~[];
+	let edges = HashMap();
+	for triples.eachi
+	|i, triple|
+	{
+		let (subject, _, object) = copy *triple;
+		for [subject, object].each
+		|term|
+		{
+			if is_blank(*term)
+			{
+				match edges.find(@copy *term)
+				{
+					option::Some(list) =>
+					{
+						// Triples like "_:a p _:a" are only added once.
+						if list.get_elt(list.len() - 1) != i
+						{
+							list.push(i);
+						}
+					}
+					option::None =>
+					{
+						vec::push(&mut blanks, copy *term);
+						edges.insert(@copy *term, @dvec::from_vec(~[i]));
+					}
+				}
+			}
+		}
+	}
+	{triples: triples, blanks: blanks, edges: edges}
+}
+
+priv fn graphs_are_isomorphic(lhs: &TermGraph, rhs: &TermGraph) -> bool
+{
+	if lhs.triples.len() != rhs.triples.len() || lhs.blanks.len() != rhs.blanks.len()
+	{
+		return false;
+	}
+	
+	let lcolors = uniform_colors(lhs);
+	let rcolors = uniform_colors(rhs);
+	refine_colors(lhs, lcolors, rhs, rcolors) && search(lhs, lcolors, rhs, rcolors)
+}
+
+priv fn uniform_colors(graph: &TermGraph) -> HashMap<@~str, uint>
+{
+	let colors = HashMap();
+	for graph.blanks.each |blank|
+	{
+		colors.insert(@copy *blank, 0);
+	}
+	colors
+}
+
+// Repeatedly splits the colors of the blank nodes in both graphs using the colors of their
+// neighbors until no more colors are split. Returns false if the graphs cannot be isomorphic.
+priv fn refine_colors(lhs: &TermGraph, lcolors: HashMap<@~str, uint>, rhs: &TermGraph, rcolors: HashMap<@~str, uint>) -> bool
+{
+	let mut count = num_colors(lcolors);
+	loop
+	{
+		// Signatures are shared so that the new colors are comparable across the graphs.
+		let signatures = HashMap();
+		let lnew = recolor(lhs, lcolors, signatures);
+		let rnew = recolor(rhs, rcolors, signatures);
+		for lnew.each |pair| {lcolors.insert(pair.first(), pair.second());}
+		for rnew.each |pair| {rcolors.insert(pair.first(), pair.second());}
+		if !same_histograms(lcolors, rcolors)
+		{
+			return false;
+		}
+		
+		if signatures.size() == count
+		{
+			return true;
+		}
+		count = signatures.size();
+	}
+}
+
+priv fn recolor(graph: &TermGraph, colors: HashMap<@~str, uint>, signatures: HashMap<@~str, uint>) -> ~[(@~str, uint)]
+{
+	do graph.blanks.map
+	|blank|
+	{
+		let edges = do graph.edges.get(@copy *blank).get().map
+		|i|
+		{
+			let (subject, predicate, object) = copy graph.triples[*i];
+			fmt!("%?%?%?", neighbor_color(subject, *blank, colors), predicate, neighbor_color(object, *blank, colors))
+		};
+		let edges = std::sort::merge_sort(edges, |x, y| {*x <= *y});
+		let signature = fmt!("%? %s", colors.get(@copy *blank), str::concat(edges));
+		
+		let color = match signatures.find(@copy signature)
+		{
+			option::Some(color) =>
+			{
+				color
+			}
+			option::None =>
+			{
+				let color = signatures.size();
+				signatures.insert(@signature, color);
+				color
+			}
+		};
+		(@copy *blank, color)
+	}
+}
+
+priv fn neighbor_color(term: &str, blank: &str, colors: HashMap<@~str, uint>) -> ~str
+{
+	if str::eq_slice(term, blank)
+	{
+		~"self"
+	}
+	else if is_blank(term)
+	{
+		fmt!("#%?", colors.get(@term.to_owned()))
+	}
+	else
+	{
+		term.to_owned()
+	}
+}
+
+priv fn num_colors(colors: HashMap<@~str, uint>) -> uint
+{
+	let seen = HashMap();
+	for colors.each_value |color|
+	{
+		seen.insert(color, ());
+	};
+	seen.size()
+}
+
+priv fn same_histograms(lhs: HashMap<@~str, uint>, rhs: HashMap<@~str, uint>) -> bool
+{
+	let counts: HashMap<uint, int> = HashMap();
+	for lhs.each_value |color|
+	{
+		counts.insert(color, counts.find(color).get_default(0) + 1);
+	};
+	for rhs.each_value |color|
+	{
+		counts.insert(color, counts.find(color).get_default(0) - 1);
+	};
+	
+	let mut same = true;
+	for counts.each_value |count|
+	{
+		if count != 0
+		{
+			same = false;
+		}
+	};
+	same
+}
+
+// If there is a color shared by multiple blank nodes then try mapping one of them to each
+// of the candidates in rhs (giving both a new color and refining).
+priv fn search(lhs: &TermGraph, lcolors: HashMap<@~str, uint>, rhs: &TermGraph, rcolors: HashMap<@~str, uint>) -> bool
+{
+	match find_shared_color(lhs, lcolors)
+	{
+		option::Some(color) =>
+		{
+			let blank = @copy *vec::find(lhs.blanks, |b| {lcolors.get(@copy *b) == color}).get();
+			let fresh = vec::foldl(0u, lhs.blanks, |m, b| {uint::max(m, lcolors.get(@copy *b))}) + 1;
+			for rhs.blanks.each
+			|candidate|
+			{
+				if rcolors.get(@copy *candidate) == color
+				{
+					let lcopy = copy_colors(lcolors);
+					let rcopy = copy_colors(rcolors);
+					lcopy.insert(blank, fresh);
+					rcopy.insert(@copy *candidate, fresh);
+					if refine_colors(lhs, lcopy, rhs, rcopy) && search(lhs, lcopy, rhs, rcopy)
+					{
+						return true;
+					}
+				}
+			}
+			false
+		}
+		option::None =>
+		{
+			same_triples(lhs, lcolors, rhs, rcolors)
+		}
+	}
+}
+
+// Returns the color of the smallest class with more than one blank node.
+priv fn find_shared_color(graph: &TermGraph, colors: HashMap<@~str, uint>) -> option::Option<uint>
+{
+	let counts: HashMap<uint, uint> = HashMap();
+	for graph.blanks.each |blank|
+	{
+		let color = colors.get(@copy *blank);
+		counts.insert(color, counts.find(color).get_default(0) + 1);
+	}
+	
+	let mut result = option::None;
+	let mut smallest = uint::max_value;
+	for graph.blanks.each |blank|
+	{
+		let color = colors.get(@copy *blank);
+		let count = counts.get(color);
+		if count > 1 && count < smallest
+		{
+			result = option::Some(color);
+			smallest = count;
+		}
+	}
+	result
+}
+
+priv fn copy_colors(colors: HashMap<@~str, uint>) -> HashMap<@~str, uint>
+{
+	let result = HashMap();
+	for colors.each |blank, color|
+	{
+		result.insert(blank, color);
+	};
+	result
+}
+
+// Every blank node has a unique color so we can map the lhs blank nodes to the rhs blank
+// nodes and compare the triples.
+priv fn same_triples(lhs: &TermGraph, lcolors: HashMap<@~str, uint>, rhs: &TermGraph, rcolors: HashMap<@~str, uint>) -> bool
+{
+	let names = HashMap();
+	for rcolors.each |blank, color|
+	{
+		names.insert(color, blank);
+	};
+	
+	let rename = |term: &~str| {if is_blank(*term) {copy *names.get(lcolors.get(@copy *term))} else {copy *term}};
+	let ltriples = do lhs.triples.map |triple| {let (s, p, o) = copy *triple; fmt!("%? %? %?", rename(&s), p, rename(&o))};
+	let rtriples = do rhs.triples.map |triple| {let (s, p, o) = copy *triple; fmt!("%? %? %?", s, p, o)};
+	std::sort::merge_sort(ltriples, |x, y| {*x <= *y}) == std::sort::merge_sort(rtriples, |x, y| {*x <= *y})
+}
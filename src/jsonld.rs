@@ -0,0 +1,784 @@
+//! Loads and saves stores using JSON-LD (http://www.w3.org/TR/json-ld/).
+use io::WriterUtil;
+use std::json;
+
+// The parts of a term definition which affect expansion.
+struct TermDefinition
+{
+	term: ~str,
+	iri: ~str,									// may itself be a term or compact IRI
+	kind: ~str,									// @type: "", "@id", "@vocab", or a datatype IRI
+	container: ~str,							// "" or "@list"
+	language: option::Option<~str>,
+}
+
+struct JsonContext
+{
+	terms: ~[TermDefinition],
+	vocab: ~str,
+	base: ~str,
+	language: ~str,
+}
+
+struct JsonLoader
+{
+	blanks: HashMap<@~str, @~str>,		// maps document blank node identifiers to store blank names
+}
+
+/// Expands a JSON-LD document and adds the resulting triples to the store.
+///
+/// Contexts may define terms, prefixes, @vocab, @base, and a default @language but remote
+/// contexts are not supported. Nodes within @graph are added to the store (named graphs
+/// are merged into the store). Blank node identifiers are renamed using get_blank_name.
+/// Returns an error message if the document could not be parsed (in which case triples
+/// before the error may have been added).
+pub fn load_jsonld(store: &Store, text: &str) -> result::Result<(), ~str>
+{
+	match json::from_str(text)
+	{
+		result::Ok(ref document) =>
+		{
+			let loader = JsonLoader {blanks: HashMap()};
+			let context = JsonContext {terms: ~[], vocab: ~"", base: ~"", language: ~""};
+			match *document
+			{
+				json::List(ref items) =>
+				{
+					add_nodes(store, &loader, &context, *items)
+				}
+				json::Object(ref map) =>
+				{
+					if map.contains_key(&~"@graph") && map.len() == (if map.contains_key(&~"@context") {2} else {1})
+					{
+						do get_context(&context, *map).chain |context|
+						{
+							match map.get(&~"@graph")
+							{
+								json::List(ref items) => add_nodes(store, &loader, &context, *items),
+								_ => result::Err(~"@graph must be an array."),
+							}
+						}
+					}
+					else
+					{
+						do add_node(store, &loader, &context, *map).chain |_subject| {result::Ok(())}
+					}
+				}
+				_ =>
+				{
+					result::Err(~"JSON-LD documents must be an object or an array.")
+				}
+			}
+		}
+		result::Err(ref err) =>
+		{
+			result::Err(fmt!("%s on line %? col %?", *err.msg, err.line, err.col))
+		}
+	}
+}
+
+/// Like load_jsonld except that the text is read from a reader.
+pub fn load_jsonld_reader(store: &Store, reader: io::Reader) -> result::Result<(), ~str>
+{
+	let bytes = reader.read_whole_stream();
+	if str::is_utf8(bytes)
+	{
+		load_jsonld(store, str::from_bytes(bytes))
+	}
+	else
+	{
+		result::Err(~"JSON-LD documents must be UTF-8.")
+	}
+}
+
+/// Writes the store as a compacted JSON-LD document.
+///
+/// The @context is built from the store's namespaces and IRIs are compacted using them.
+/// Subjects and predicates are sorted so that the output is stable. Lists added with
+/// add_list are written using @list.
+pub fn write_jsonld(store: &Store, writer: io::Writer)
+{
+	let namespaces = do std::sort::merge_sort(store.namespaces) |x, y| {x.prefix <= y.prefix};
+	let namespaces = do vec::filter(namespaces) |n| {n.prefix != ~"_"};
+	
+	writer.write_line("{");
+	writer.write_line("\t\"@context\": {");
+	for namespaces.eachi |i, namespace|
+	{
+		let separator = if i + 1 < namespaces.len() {","} else {""};
+		writer.write_line(fmt!("\t\t%s: %s%s", json_string(namespace.prefix), json_string(namespace.path), separator));
+	}
+	writer.write_line("\t},");
+	writer.write_str("\t\"@graph\": [");
+	
+	let lists = find_lists(store);
+	let subjects = do sorted_subjects(store).filter |subject| {!lists.skipped.contains_key(@copy *subject)};
+	for subjects.eachi |i, subject|
+	{
+		let entries = sorted_entries(store, find_entries(store, *subject).get());
+		writer.write_str(if i == 0 {"\n\t\t{\n"} else {",\n\t\t{\n"});
+		writer.write_str(fmt!("\t\t\t\"@id\": %s", json_string(subject_to_jsonld(namespaces, *subject))));
+		
+		let types = do entries.filter |e| {e.predicate == ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#type" && is_iri(e.object)};
+		if types.is_not_empty()
+		{
+			let types = do types.map |e| {json_string(contract_uri(namespaces, e.object.as_iri()))};
+			writer.write_str(fmt!(",\n\t\t\t\"@type\": %s", values_to_jsonld(types)));
+		}
+		
+		let entries = do entries.filter |e| {e.predicate != ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#type" || !is_iri(e.object)};
+		let mut j = 0;
+		while j < entries.len()
+		{
+			let predicate = copy entries[j].predicate;
+			let mut values = ~[];
+			while j < entries.len() && entries[j].predicate == predicate
+			{
+				values.push(object_to_jsonld(namespaces, &lists, entries[j].object));
+				j += 1;
+			}
+			writer.write_str(fmt!(",\n\t\t\t%s: %s", json_string(contract_uri(namespaces, predicate)), values_to_jsonld(values)));
+		}
+		writer.write_str("\n\t\t}");
+	}
+	
+	writer.write_line(if subjects.is_empty() {"]"} else {"\n\t]"});
+	writer.write_line("}");
+}
+
+/// Returns the store formatted as a compacted JSON-LD document.
+pub fn store_to_jsonld(store: &Store) -> ~str
+{
+	do io::with_str_writer |writer| {write_jsonld(store, writer)}
+}
+
+/// Returns the text quoted and escaped as a JSON string.
+pub fn json_string(text: &str) -> ~str
+{
+	json::to_str(&json::String(text.to_owned()))
+}
+
+// ---- Private Functions -----------------------------------------------------
+
+priv fn add_nodes(store: &Store, loader: &JsonLoader, context: &JsonContext, items: &[json::Json]) -> result::Result<(), ~str>
+{
+	for items.each |item|
+	{
+		let result = match *item
+		{
+			json::Object(ref map) => add_node(store, loader, context, *map).chain(|_subject| {result::Ok(())}),
+			_ => result::Err(~"Expected a node object."),
+		};
+		if result.is_err()
+		{
+			return result;
+		}
+	}
+	result::Ok(())
+}
+
+// Returns the subject of the node (an IriValue or BlankValue).
+priv fn add_node(store: &Store, loader: &JsonLoader, context: &JsonContext, map: &json::Object) -> result::Result<@Object, ~str>
+{
+	let context = match get_context(context, map)
+	{
+		result::Ok(context) => context,
+		result::Err(copy err) => return result::Err(err),
+	};
+	
+	let subject = match map.find(&~"@id")
+	{
+		option::Some(json::String(ref id)) => iri_to_object(store, loader, expand_iri(&context, *id, false)),
+		option::Some(_) => return result::Err(~"@id must be a string."),
+		option::None => @BlankValue(get_blank_name(store, "node")),
+	};
+	let name = match *subject
+	{
+		IriValue(ref name) | BlankValue(ref name) => copy *name,
+		_ => fail ~"subject should be an IRI or blank node",
+	};
+	
+	for sorted_keys(map).each |key|
+	{
+		let value = map.get(key);
+		let result = if *key == ~"@type"
+		{
+			add_types(store, &context, name, &value)
+		}
+		else if *key == ~"@graph"
+		{
+			match value
+			{
+				json::List(ref items) => add_nodes(store, loader, &context, *items),
+				json::Object(ref nested) => add_node(store, loader, &context, *nested).chain(|_subject| {result::Ok(())}),
+				_ => result::Err(~"@graph must be an array or an object."),
+			}
+		}
+		else if key.starts_with("@")
+		{
+			result::Ok(())				// @context, @id, @index, etc
+		}
+		else
+		{
+			let predicate = expand_iri(&context, *key, true);
+			if str::contains_char(predicate, ':') && !predicate.starts_with("_:")
+			{
+				let term = find_term(&context, *key);
+				do get_values(store, loader, &context, &term, &value).chain |objects|
+				{
+					for objects.each |object|
+					{
+						store.add_triple(~[], {subject: copy name, predicate: copy predicate, object: *object});
+					}
+					result::Ok(())
+				}
+			}
+			else
+			{
+				result::Ok(())			// keys which do not expand to absolute IRIs are dropped
+			}
+		};
+		if result.is_err()
+		{
+			return result::Err(result.get_err());
+		}
+	}
+	
+	result::Ok(subject)
+}
+
+priv fn add_types(store: &Store, context: &JsonContext, subject: &str, value: &json::Json) -> result::Result<(), ~str>
+{
+	let types = match *value
+	{
+		json::String(ref kind) => ~[copy *kind],
+		json::List(ref items) =>
+		{
+			let mut types = ~[];
+			for items.each |item|
+			{
+				match *item
+				{
+					json::String(ref kind) => vec::push(&mut types, copy *kind),
+					_ => return result::Err(~"@type values must be strings."),
+				}
+			}
+			types
+		}
+		_ => return result::Err(~"@type must be a string or an array of strings."),
+	};
+	
+	for types.each |kind|
+	{
+		let object = @IriValue(expand_iri(context, *kind, true));
+		store.add_triple(~[], {subject: subject.to_owned(), predicate: ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#type", object: object});
+	}
+	result::Ok(())
+}
+
+priv fn get_values(store: &Store, loader: &JsonLoader, context: &JsonContext, term: &option::Option<TermDefinition>, value: &json::Json) -> result::Result<~[@Object], ~str>
+{
+	match *value
+	{
+		json::Null =>
+		{
+			result::Ok(~[])
+		}
+		json::List(ref items) if term.is_some() && term.get().container == ~"@list" =>
+		{
+			do add_list(store, loader, context, term, *items).chain |object| {result::Ok(~[object])}
+		}
+		json::List(ref items) =>
+		{
+			let mut objects = ~[];
+			for items.each |item|
+			{
+				match get_values(store, loader, context, term, item)
+				{
+					result::Ok(values) => vec::push_all(&mut objects, values),
+					result::Err(copy err) => return result::Err(err),
+				}
+			}
+			result::Ok(objects)
+		}
+		json::Object(ref map) if map.contains_key(&~"@set") =>
+		{
+			get_values(store, loader, context, term, &map.get(&~"@set"))
+		}
+		_ =>
+		{
+			do get_value(store, loader, context, term, value).chain |object| {result::Ok(~[object])}
+		}
+	}
+}
+
+priv fn get_value(store: &Store, loader: &JsonLoader, context: &JsonContext, term: &option::Option<TermDefinition>, value: &json::Json) -> result::Result<@Object, ~str>
+{
+	let kind = match *term {option::Some(ref t) => copy t.kind, option::None => ~""};
+	match *value
+	{
+		json::String(ref text) =>
+		{
+			if kind == ~"@id"
+			{
+				result::Ok(iri_to_object(store, loader, expand_iri(context, *text, false)))
+			}
+			else if kind == ~"@vocab"
+			{
+				result::Ok(iri_to_object(store, loader, expand_iri(context, *text, true)))
+			}
+			else if kind.is_not_empty()
+			{
+				result::Ok(@literal_to_object(*text, kind, ""))
+			}
+			else
+			{
+				let lang = match *term
+				{
+					option::Some(TermDefinition {language: option::Some(ref lang), _}) => copy *lang,
+					_ => copy context.language,
+				};
+				result::Ok(@literal_to_object(*text, "http://www.w3.org/2001/XMLSchema#string", lang))
+			}
+		}
+		json::Number(n) =>
+		{
+			if kind.is_not_empty() && !kind.starts_with("@")
+			{
+				result::Ok(@literal_to_object(number_to_str(n), kind, ""))
+			}
+			else
+			{
+				result::Ok(number_to_object(n))
+			}
+		}
+		json::Boolean(b) =>
+		{
+			result::Ok(@BoolValue(b))
+		}
+		json::Object(ref map) if map.contains_key(&~"@value") =>
+		{
+			get_value_object(context, *map)
+		}
+		json::Object(ref map) if map.contains_key(&~"@list") =>
+		{
+			match map.get(&~"@list")
+			{
+				json::List(ref items) => add_list(store, loader, context, term, *items),
+				_ => result::Err(~"@list must be an array."),
+			}
+		}
+		json::Object(ref map) =>
+		{
+			add_node(store, loader, context, *map)
+		}
+		json::List(_) | json::Null =>
+		{
+			result::Err(~"Lists of lists are not supported.")
+		}
+	}
+}
+
+// {"@value": v, "@type": t} or {"@value": v, "@language": l}
+priv fn get_value_object(context: &JsonContext, map: &json::Object) -> result::Result<@Object, ~str>
+{
+	let kind = match map.find(&~"@type")
+	{
+		option::Some(json::String(ref kind)) => expand_iri(context, *kind, true),
+		option::Some(_) => return result::Err(~"@type must be a string."),
+		option::None => ~"",
+	};
+	let lang = match map.find(&~"@language")
+	{
+		option::Some(json::String(ref lang)) => copy *lang,
+		option::Some(_) => return result::Err(~"@language must be a string."),
+		option::None => ~"",
+	};
+	
+	match map.get(&~"@value")
+	{
+		json::String(ref text) =>
+		{
+			if kind.is_not_empty()
+			{
+				result::Ok(@literal_to_object(*text, kind, ""))
+			}
+			else
+			{
+				result::Ok(@literal_to_object(*text, "http://www.w3.org/2001/XMLSchema#string", lang))
+			}
+		}
+		json::Number(n) if kind.is_not_empty() =>
+		{
+			result::Ok(@literal_to_object(number_to_str(n), kind, ""))
+		}
+		json::Boolean(b) if kind.is_not_empty() =>
+		{
+			result::Ok(@literal_to_object(if b {"true"} else {"false"}, kind, ""))
+		}
+		json::Number(n) =>
+		{
+			result::Ok(number_to_object(n))
+		}
+		json::Boolean(b) =>
+		{
+			result::Ok(@BoolValue(b))
+		}
+		_ =>
+		{
+			result::Err(~"@value must be a string, number, or boolean.")
+		}
+	}
+}
+
+// Lists are encoded using standard first/rest chains.
+priv fn add_list(store: &Store, loader: &JsonLoader, context: &JsonContext, term: &option::Option<TermDefinition>, items: &[json::Json]) -> result::Result<@Object, ~str>
+{
+	let mut objects = ~[];
+	for items.each |item|
+	{
+		match *item
+		{
+			json::List(_) =>
+			{
+				return result::Err(~"Lists of lists are not supported.");
+			}
+			_ =>
+			{
+				match get_values(store, loader, context, term, item)
+				{
+					result::Ok(values) => vec::push_all(&mut objects, values),
+					result::Err(copy err) => return result::Err(err),
+				}
+			}
+		}
+	}
+	
+	if objects.is_empty()
+	{
+		return result::Ok(@IriValue(~"http://www.w3.org/1999/02/22-rdf-syntax-ns#nil"));
+	}
+	
+	let head = get_blank_name(store, "list");
+	let mut blank = copy head;
+	for objects.eachi |i, object|
+	{
+		store.add_triple(~[], {subject: copy blank, predicate: ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#first", object: *object});
+		if i + 1 < objects.len()
+		{
+			let next = get_blank_name(store, "list");
+			store.add_triple(~[], {subject: copy blank, predicate: ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#rest", object: @BlankValue(copy next)});
+			blank = next;
+		}
+		else
+		{
+			store.add_triple(~[], {subject: copy blank, predicate: ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#rest", object: @IriValue(~"http://www.w3.org/1999/02/22-rdf-syntax-ns#nil")});
+		}
+	}
+	result::Ok(@BlankValue(head))
+}
+
+// Returns the context in effect for a node object.
+priv fn get_context(context: &JsonContext, map: &json::Object) -> result::Result<JsonContext, ~str>
+{
+	match map.find(&~"@context")
+	{
+		option::Some(ref value) => process_context(context, value),
+		option::None => result::Ok(copy *context),
+	}
+}
+
+priv fn process_context(context: &JsonContext, value: &json::Json) -> result::Result<JsonContext, ~str>
+{
+	match *value
+	{
+		json::Null =>
+		{
+			result::Ok(JsonContext {terms: ~[], vocab: ~"", base: copy context.base, language: ~""})
+		}
+		json::String(ref url) =>
+		{
+			result::Err(fmt!("Remote contexts are not supported (%s).", *url))
+		}
+		json::List(ref items) =>
+		{
+			let mut result = copy *context;
+			for items.each |item|
+			{
+				match process_context(&result, item)
+				{
+					result::Ok(r) => result = r,
+					result::Err(copy err) => return result::Err(err),
+				}
+			}
+			result::Ok(result)
+		}
+		json::Object(ref map) =>
+		{
+			let mut result = copy *context;
+			for sorted_keys(*map).each |key|
+			{
+				match (copy *key, map.get(key))
+				{
+					(~"@vocab", json::String(ref vocab)) => result.vocab = copy *vocab,
+					(~"@vocab", json::Null) => result.vocab = ~"",
+					(~"@base", json::String(ref base)) => result.base = resolve_iri(result.base, *base),
+					(~"@language", json::String(ref lang)) => result.language = copy *lang,
+					(~"@language", json::Null) => result.language = ~"",
+					(~"@version", _) => (),
+					(copy k, _) if k.starts_with("@") => return result::Err(fmt!("Bad value for %s in @context.", k)),
+					(copy term, copy definition) =>
+					{
+						let terms = do result.terms.filter |t| {t.term != term};
+						match get_term_definition(term, &definition)
+						{
+							result::Ok(option::Some(t)) => result.terms = terms + ~[t],
+							result::Ok(option::None) => result.terms = terms,
+							result::Err(copy err) => return result::Err(err),
+						}
+					}
+				}
+			}
+			result::Ok(result)
+		}
+		_ =>
+		{
+			result::Err(~"@context must be an object, an array, a string, or null.")
+		}
+	}
+}
+
+// Returns None if the term is being removed.
+priv fn get_term_definition(term: ~str, definition: &json::Json) -> result::Result<option::Option<TermDefinition>, ~str>
+{
+	match *definition
+	{
+		json::Null =>
+		{
+			result::Ok(option::None)
+		}
+		json::String(ref iri) =>
+		{
+			result::Ok(option::Some(TermDefinition {term: term, iri: copy *iri, kind: ~"", container: ~"", language: option::None}))
+		}
+		json::Object(ref map) =>
+		{
+			let mut result = TermDefinition {term: copy term, iri: copy term, kind: ~"", container: ~"", language: option::None};
+			for sorted_keys(*map).each |key|
+			{
+				match (copy *key, map.get(key))
+				{
+					(~"@id", json::String(ref iri)) => result.iri = copy *iri,
+					(~"@type", json::String(ref kind)) => result.kind = copy *kind,
+					(~"@container", json::String(ref container)) => result.container = copy *container,
+					(~"@language", json::String(ref lang)) => result.language = option::Some(copy *lang),
+					(~"@language", json::Null) => result.language = option::Some(~""),
+					(copy k, _) => return result::Err(fmt!("Bad value for %s in the definition of %s.", k, term)),
+				}
+			}
+			result::Ok(option::Some(result))
+		}
+		_ =>
+		{
+			result::Err(fmt!("The definition of %s must be a string, an object, or null.", term))
+		}
+	}
+}
+
+priv fn find_term(context: &JsonContext, term: &str) -> option::Option<TermDefinition>
+{
+	do context.terms.find |t| {t.term == term.to_owned()}
+}
+
+// Expands terms, compact IRIs, and relative IRIs. Vocab is set for values which are
+// interpreted relative to @vocab (keys and @type values).
+priv fn expand_iri(context: &JsonContext, value: &str, vocab: bool) -> ~str
+{
+	if value.starts_with("@")
+	{
+		return value.to_owned();
+	}
+	
+	if vocab
+	{
+		match find_term(context, value)
+		{
+			option::Some(ref term) if term.iri != value.to_owned() =>
+			{
+				return expand_iri(context, term.iri, true);
+			}
+			_ =>
+			{
+			}
+		}
+	}
+	
+	match str::find_char(value, ':')
+	{
+		option::Some(i) =>
+		{
+			let prefix = str::slice(value, 0, i);
+			let suffix = str::slice(value, i + 1, str::len(value));
+			if prefix == ~"_" || suffix.starts_with("//")
+			{
+				return value.to_owned();
+			}
+			
+			match find_term(context, prefix)
+			{
+				option::Some(ref term) if term.iri != prefix => expand_iri(context, term.iri, true) + suffix,
+				_ => value.to_owned(),
+			}
+		}
+		option::None =>
+		{
+			if vocab && context.vocab.is_not_empty()
+			{
+				context.vocab + value
+			}
+			else
+			{
+				resolve_iri(context.base, value)
+			}
+		}
+	}
+}
+
+priv fn iri_to_object(store: &Store, loader: &JsonLoader, iri: ~str) -> @Object
+{
+	if iri.starts_with("_:")
+	{
+		match loader.blanks.find(@copy iri)
+		{
+			option::Some(name) =>
+			{
+				@BlankValue(copy *name)
+			}
+			option::None =>
+			{
+				let name = get_blank_name(store, str::slice(iri, 2, str::len(iri)));
+				loader.blanks.insert(@iri, @copy name);
+				@BlankValue(name)
+			}
+		}
+	}
+	else
+	{
+		@IriValue(iri)
+	}
+}
+
+// Keys are processed in sorted order so that blank node names are deterministic.
+priv fn sorted_keys(map: &json::Object) -> ~[~str]
+{
+	let mut keys = ~[];
+	for map.each_key |key|
+	{
+		vec::push(&mut keys, copy *key);
+	}
+	std::sort::merge_sort(keys, |x, y| {*x <= *y})
+}
+
+// Integral numbers map to xsd:integer and everything else to xsd:double.
+priv fn number_to_object(n: float) -> @Object
+{
+	if float::floor(n) == n && float::abs(n) < 9007199254740992.0
+	{
+		@IntValue(n as i64)
+	}
+	else
+	{
+		@FloatValue(n as f64)
+	}
+}
+
+priv fn number_to_str(n: float) -> ~str
+{
+	if float::floor(n) == n && float::abs(n) < 9007199254740992.0
+	{
+		(n as i64).to_str()
+	}
+	else
+	{
+		n.to_str()
+	}
+}
+
+priv fn subject_to_jsonld(namespaces: &[Namespace], subject: &str) -> ~str
+{
+	if subject.starts_with("_:")
+	{
+		blank_to_turtle(subject)
+	}
+	else
+	{
+		contract_uri(namespaces, subject)
+	}
+}
+
+priv fn object_to_jsonld(namespaces: &[Namespace], lists: &Lists, object: @Object) -> ~str
+{
+	match *object
+	{
+		IriValue(ref iri) =>
+		{
+			fmt!("{\"@id\": %s}", json_string(contract_uri(namespaces, *iri)))
+		}
+		BlankValue(ref name) =>
+		{
+			match lists.items.find(@copy *name)
+			{
+				option::Some(items) =>
+				{
+					let items = do items.map |item| {object_to_jsonld(namespaces, lists, *item)};
+					fmt!("{\"@list\": [%s]}", str::connect(items, ", "))
+				}
+				option::None =>
+				{
+					fmt!("{\"@id\": %s}", json_string(blank_to_turtle(*name)))
+				}
+			}
+		}
+		BoolValue(_) | IntValue(_) =>
+		{
+			object.to_str()
+		}
+		StringValue(ref value, ref lang) =>
+		{
+			if lang.is_empty()
+			{
+				json_string(*value)
+			}
+			else
+			{
+				fmt!("{\"@value\": %s, \"@language\": %s}", json_string(*value), json_string(*lang))
+			}
+		}
+		_ =>
+		{
+			let (value, kind, _lang) = object_to_literal(object);
+			fmt!("{\"@value\": %s, \"@type\": %s}", json_string(value), json_string(contract_uri(namespaces, kind)))
+		}
+	}
+}
+
+priv fn is_iri(object: @Object) -> bool
+{
+	match *object
+	{
+		IriValue(_) => true,
+		_ => false,
+	}
+}
+
+priv fn values_to_jsonld(values: &[~str]) -> ~str
+{
+	if values.len() == 1
+	{
+		copy values[0]
+	}
+	else
+	{
+		~"[" + str::connect(values, ", ") + ~"]"
+	}
+}
@@ -0,0 +1,551 @@
+//! Loads and saves stores using the N-Triples format (http://www.w3.org/TR/rdf-testcases/#ntriples)
+//! and datasets using the N-Quads format (http://sw.deri.org/2008/07/n-quads/).
+use io::{ReaderUtil, WriterUtil};
+
+// Position within a single N-Triples line.
+struct Cursor
+{
+	chars: ~[char],
+	mut index: uint,
+}
+
+/// Reads N-Triples statements from the reader and adds them to the store.
+///
+/// The reader is consumed one line at a time so documents do not need to fit into memory
+/// and each statement is added using add_triple as soon as it is parsed. Blank node labels
+/// are renamed using get_blank_name. Returns an error message with the line number if a
+/// line could not be parsed (in which case the statements before it will have been added).
+pub fn load_ntriples(store: &Store, reader: io::Reader) -> result::Result<(), ~str>
+{
+	let blanks = HashMap();
+	let mut line_num = 0;
+	while !reader.eof()
+	{
+		let line = reader.read_line();
+		line_num += 1;
+		
+		match parse_line(store, option::Some(blanks), line, false)
+		{
+			result::Ok(option::Some((triple, _graph))) =>
+			{
+				store.add_triple(~[], triple);
+			}
+			result::Ok(option::None) =>
+			{
+			}
+			result::Err(ref err) =>
+			{
+				return result::Err(fmt!("%s on line %?", *err, line_num));
+			}
+		}
+	}
+	result::Ok(())
+}
+
+/// Like load_ntriples except that the statements are read from a string.
+pub fn load_ntriples_str(store: &Store, text: &str) -> result::Result<(), ~str>
+{
+	do io::with_str_reader(text) |reader| {load_ntriples(store, reader)}
+}
+
+/// Reads N-Quads statements from the reader and adds them to the dataset.
+///
+/// Statements without a graph label are added to the default graph. Blank node labels
+/// are shared by all the graphs in the document. Otherwise this works like load_ntriples.
+pub fn load_nquads(dataset: &Dataset, reader: io::Reader) -> result::Result<(), ~str>
+{
+	let blanks = HashMap();
+	let mut line_num = 0;
+	while !reader.eof()
+	{
+		let line = reader.read_line();
+		line_num += 1;
+		
+		match parse_line(dataset.default_graph, option::Some(blanks), line, true)
+		{
+			result::Ok(option::Some((triple, graph))) =>
+			{
+				dataset.add_triple(graph, ~[], triple);
+			}
+			result::Ok(option::None) =>
+			{
+			}
+			result::Err(ref err) =>
+			{
+				return result::Err(fmt!("%s on line %?", *err, line_num));
+			}
+		}
+	}
+	result::Ok(())
+}
+
+/// Like load_nquads except that the statements are read from a string.
+pub fn load_nquads_str(dataset: &Dataset, text: &str) -> result::Result<(), ~str>
+{
+	do io::with_str_reader(text) |reader| {load_nquads(dataset, reader)}
+}
+
+/// Writes every triple in the dataset using the N-Quads format.
+///
+/// The default graph is written first followed by the named graphs in sorted order.
+pub fn write_nquads(dataset: &Dataset, writer: io::Writer)
+{
+	write_ntriples(dataset.default_graph, writer);
+	for dataset.get_graph_names().each |name|
+	{
+		let graph = dataset.get_graph(*name).get();
+		for graph.each |triple|
+		{
+			writer.write_line(quad_to_nquads(triple, *name));
+		}
+	}
+}
+
+/// Returns the dataset formatted using the N-Quads format.
+pub fn dataset_to_nquads(dataset: &Dataset) -> ~str
+{
+	do io::with_str_writer |writer| {write_nquads(dataset, writer)}
+}
+
+/// Writes every triple in the store using the N-Triples format.
+///
+/// IRIs are always written in full. Triples are written in the order returned by
+/// the store's each method so nothing needs to be buffered.
+pub fn write_ntriples(store: &Store, writer: io::Writer)
+{
+	for store.each |triple|
+	{
+		writer.write_line(triple_to_ntriples(triple));
+	}
+}
+
+/// Returns the store formatted using the N-Triples format.
+pub fn store_to_ntriples(store: &Store) -> ~str
+{
+	do io::with_str_writer |writer| {write_ntriples(store, writer)}
+}
+
+/// Returns a single N-Triples statement (without a trailing new line).
+pub fn triple_to_ntriples(triple: &Triple) -> ~str
+{
+	fmt!("%s <%s> %s .", name_to_ntriples(triple.subject), escape_iri(triple.predicate), object_to_ntriples(triple.object))
+}
+
+/// Returns a single N-Quads statement (without a trailing new line).
+///
+/// If graph is empty the statement will be in the default graph.
+pub fn quad_to_nquads(triple: &Triple, graph: &str) -> ~str
+{
+	if graph.is_empty()
+	{
+		triple_to_ntriples(triple)
+	}
+	else
+	{
+		fmt!("%s <%s> %s %s .", name_to_ntriples(triple.subject), escape_iri(triple.predicate), object_to_ntriples(triple.object), name_to_ntriples(graph))
+	}
+}
+
+/// Returns the canonical N-Triples form of an object.
+///
+/// Plain strings are written without a datatype, other literals always include one.
+pub fn object_to_ntriples(object: &Object) -> ~str
+{
+	match *object
+	{
+		IriValue(ref iri) =>
+		{
+			fmt!("<%s>", escape_iri(*iri))
+		}
+		BlankValue(ref name) =>
+		{
+			blank_to_turtle(*name)
+		}
+		StringValue(ref value, ref lang) =>
+		{
+			if lang.is_empty()
+			{
+				fmt!("\"%s\"", escape_string(*value))
+			}
+			else
+			{
+				fmt!("\"%s\"@%s", escape_string(*value), *lang)
+			}
+		}
+		_ =>
+		{
+			let (value, kind, _lang) = object_to_literal(object);
+			fmt!("\"%s\"^^<%s>", escape_string(value), escape_iri(kind))
+		}
+	}
+}
+
+// ---- Private Functions -----------------------------------------------------
+
+// Returns None for blank and comment lines. If quads is set the triple may be followed by
+// a graph label (an empty graph name is returned for the default graph). If blanks is
+// None blank node labels are decoded with decode_blank (instead of being renamed with
+// get_blank_name).
+priv fn parse_line(store: &Store, blanks: option::Option<HashMap<@~str, @~str>>, line: &str, quads: bool) -> result::Result<option::Option<(Triple, ~str)>, ~str>
+{
+	let cursor = Cursor {chars: str::chars(line), index: 0};
+	skip_ws(&cursor);
+	if at_end(&cursor) || peek(&cursor) == '#'
+	{
+		return result::Ok(option::None);
+	}
+	
+	let subject = match peek(&cursor)
+	{
+		'<' => parse_iri(&cursor),
+		'_' => parse_blank(store, blanks, &cursor),
+		_ => result::Err(~"Expected an IRI or blank node for the subject"),
+	};
+	let subject = match subject
+	{
+		result::Ok(copy s) => s,
+		result::Err(copy err) => return result::Err(err),
+	};
+	skip_ws(&cursor);
+	
+	let predicate = match parse_iri(&cursor)
+	{
+		result::Ok(copy p) => p,
+		result::Err(copy err) => return result::Err(err),
+	};
+	skip_ws(&cursor);
+	
+	let object = match peek(&cursor)
+	{
+		'<' => parse_iri(&cursor).map(|iri| {@IriValue(copy *iri)}),
+		'_' => parse_blank(store, blanks, &cursor).map(|name| {@BlankValue(copy *name)}),
+		'"' => parse_literal(&cursor),
+		_ => result::Err(~"Expected an IRI, blank node, or literal for the object"),
+	};
+	let object = match object
+	{
+		result::Ok(o) => o,
+		result::Err(copy err) => return result::Err(err),
+	};
+	skip_ws(&cursor);
+	
+	let graph = match peek(&cursor)
+	{
+		'<' if quads => parse_iri(&cursor),
+		'_' if quads => parse_blank(store, blanks, &cursor),
+		_ => result::Ok(~""),
+	};
+	let graph = match graph
+	{
+		result::Ok(copy g) => g,
+		result::Err(copy err) => return result::Err(err),
+	};
+	skip_ws(&cursor);
+	
+	if !accept(&cursor, '.')
+	{
+		return result::Err(~"Expected '.'");
+	}
+	skip_ws(&cursor);
+	if !at_end(&cursor) && peek(&cursor) != '#'
+	{
+		return result::Err(fmt!("Unexpected '%c' after '.'", peek(&cursor)));
+	}
+	
+	result::Ok(option::Some(({subject: subject, predicate: predicate, object: object}, graph)))
+}
+
+// Subjects and graph names are either IRIs or blank nodes.
+priv fn name_to_ntriples(name: &str) -> ~str
+{
+	if name.starts_with("_:") {blank_to_turtle(name)} else {fmt!("<%s>", escape_iri(name))}
+}
+
+// Like triple_to_ntriples except that blank node labels are written with encode_blank so
+// that parse_line (with blanks set to None) restores the exact labels. Used for records
+// which refer to blank nodes already in a store (e.g. logs and patches).
+priv fn triple_to_exact_ntriples(triple: &Triple) -> ~str
+{
+	let subject = if triple.subject.starts_with("_:") {encode_blank(triple.subject)} else {fmt!("<%s>", escape_iri(triple.subject))};
+	let object = match *triple.object
+	{
+		BlankValue(ref name) => encode_blank(*name),
+		_ => object_to_ntriples(triple.object),
+	};
+	fmt!("%s <%s> %s .", subject, escape_iri(triple.predicate), object)
+}
+
+// Unlike blank_to_turtle this is reversible: characters other than letters, digits, and
+// '-' are written as '_' followed by their hex code and another '_'.
+priv fn encode_blank(name: &str) -> ~str
+{
+	let mut result = ~"_:";
+	for str::each_char(str::slice(name, 2, name.len())) |ch|
+	{
+		if char::is_alphanumeric(ch) || ch == '-'
+		{
+			str::push_char(&mut result, ch);
+		}
+		else
+		{
+			str::push_str(&mut result, fmt!("_%X_", ch as uint));
+		}
+	}
+	result
+}
+
+// Returns the blank node name for a label written by encode_blank. Underscores which
+// are not part of an escape are kept as is.
+priv fn decode_blank(label: &str) -> ~str
+{
+	let chars = str::chars(label);
+	let mut result = ~"_:";
+	let mut i = 0;
+	while i < chars.len()
+	{
+		let mut end = i + 1;
+		let mut code = 0u;
+		if chars[i] == '_'
+		{
+			while end < chars.len() && end - i <= 6 && char::to_digit(chars[end], 16).is_some()
+			{
+				code = 16*code + char::to_digit(chars[end], 16).get();
+				end += 1;
+			}
+		}
+		
+		if end > i + 1 && end < chars.len() && chars[end] == '_' && code <= 0x10FFFF
+		{
+			str::push_char(&mut result, code as char);
+			i = end + 1;
+		}
+		else
+		{
+			str::push_char(&mut result, chars[i]);
+			i += 1;
+		}
+	}
+	result
+}
+
+// uriref ::= '<' absoluteURI '>'
+priv fn parse_iri(cursor: &Cursor) -> result::Result<~str, ~str>
+{
+	if !accept(cursor, '<')
+	{
+		return result::Err(~"Expected '<'");
+	}
+	
+	let mut iri = ~"";
+	loop
+	{
+		if at_end(cursor)
+		{
+			return result::Err(~"Missing '>'");
+		}
+		
+		let ch = next(cursor);
+		if ch == '>'
+		{
+			return result::Ok(iri);
+		}
+		else if ch == '\\'
+		{
+			match parse_escape(cursor)
+			{
+				result::Ok(c) => str::push_char(&mut iri, c),
+				result::Err(copy err) => return result::Err(err),
+			}
+		}
+		else
+		{
+			str::push_char(&mut iri, ch);
+		}
+	}
+}
+
+// nodeID ::= '_:' name
+priv fn parse_blank(store: &Store, blanks: option::Option<HashMap<@~str, @~str>>, cursor: &Cursor) -> result::Result<~str, ~str>
+{
+	if !accept(cursor, '_') || !accept(cursor, ':')
+	{
+		return result::Err(~"Expected '_:'");
+	}
+	
+	let mut label = ~"";
+	while !at_end(cursor) && (char::is_alphanumeric(peek(cursor)) || peek(cursor) == '_' || peek(cursor) == '-')
+	{
+		str::push_char(&mut label, next(cursor));
+	}
+	if label.is_empty()
+	{
+		return result::Err(~"Expected a blank node label");
+	}
+	
+	match blanks
+	{
+		option::Some(blanks) =>
+		{
+			match blanks.find(@copy label)
+			{
+				option::Some(name) =>
+				{
+					result::Ok(copy *name)
+				}
+				option::None =>
+				{
+					let name = get_blank_name(store, label);
+					blanks.insert(@label, @copy name);
+					result::Ok(name)
+				}
+			}
+		}
+		option::None =>
+		{
+			result::Ok(decode_blank(label))
+		}
+	}
+}
+
+// literal ::= '"' string '"' ('@' language | '^^' uriref)?
+priv fn parse_literal(cursor: &Cursor) -> result::Result<@Object, ~str>
+{
+	if !accept(cursor, '"')
+	{
+		return result::Err(~"Expected '\"'");
+	}
+	
+	let mut value = ~"";
+	loop
+	{
+		if at_end(cursor)
+		{
+			return result::Err(~"Missing closing '\"'");
+		}
+		
+		let ch = next(cursor);
+		if ch == '"'
+		{
+			break;
+		}
+		else if ch == '\\'
+		{
+			match parse_escape(cursor)
+			{
+				result::Ok(c) => str::push_char(&mut value, c),
+				result::Err(copy err) => return result::Err(err),
+			}
+		}
+		else
+		{
+			str::push_char(&mut value, ch);
+		}
+	}
+	
+	if accept(cursor, '@')
+	{
+		let mut lang = ~"";
+		while !at_end(cursor) && (char::is_alphanumeric(peek(cursor)) || peek(cursor) == '-')
+		{
+			str::push_char(&mut lang, next(cursor));
+		}
+		if lang.is_empty()
+		{
+			return result::Err(~"Expected a language tag");
+		}
+		result::Ok(@literal_to_object(value, "http://www.w3.org/2001/XMLSchema#string", lang))
+	}
+	else if accept(cursor, '^')
+	{
+		if !accept(cursor, '^')
+		{
+			return result::Err(~"Expected '^^'");
+		}
+		do parse_iri(cursor).chain |kind| {result::Ok(@literal_to_object(value, kind, ""))}
+	}
+	else
+	{
+		result::Ok(@literal_to_object(value, "http://www.w3.org/2001/XMLSchema#string", ""))
+	}
+}
+
+// Called after a backslash has been consumed.
+priv fn parse_escape(cursor: &Cursor) -> result::Result<char, ~str>
+{
+	if at_end(cursor)
+	{
+		return result::Err(~"Incomplete escape sequence");
+	}
+	
+	match next(cursor)
+	{
+		't'	=> result::Ok('\t'),
+		'b'	=> result::Ok('\x08'),
+		'n'	=> result::Ok('\n'),
+		'r'	=> result::Ok('\r'),
+		'f'	=> result::Ok('\x0c'),
+		'"'	=> result::Ok('"'),
+		'\''	=> result::Ok('\''),
+		'\\'	=> result::Ok('\\'),
+		'u'	=> parse_hex(cursor, 4),
+		'U'	=> parse_hex(cursor, 8),
+		ch	=> result::Err(fmt!("Bad escape sequence '\\%c'", ch)),
+	}
+}
+
+priv fn parse_hex(cursor: &Cursor, count: uint) -> result::Result<char, ~str>
+{
+	let mut value = 0u;
+	for count.times
+	{
+		if at_end(cursor)
+		{
+			return result::Err(~"Incomplete unicode escape");
+		}
+		match char::to_digit(next(cursor), 16)
+		{
+			option::Some(d) => value = 16*value + d,
+			option::None => return result::Err(~"Unicode escapes must use hex digits"),
+		}
+	}
+	result::Ok(value as char)
+}
+
+priv fn skip_ws(cursor: &Cursor)
+{
+	while !at_end(cursor) && (peek(cursor) == ' ' || peek(cursor) == '\t' || peek(cursor) == '\r')
+	{
+		cursor.index += 1;
+	}
+}
+
+priv fn accept(cursor: &Cursor, ch: char) -> bool
+{
+	if !at_end(cursor) && peek(cursor) == ch
+	{
+		cursor.index += 1;
+		true
+	}
+	else
+	{
+		false
+	}
+}
+
+priv pure fn at_end(cursor: &Cursor) -> bool
+{
+	cursor.index >= cursor.chars.len()
+}
+
+// Returns a NUL character at the end of the line.
+priv fn peek(cursor: &Cursor) -> char
+{
+	if at_end(cursor) {'\x00'} else {cursor.chars[cursor.index]}
+}
+
+priv fn next(cursor: &Cursor) -> char
+{
+	let ch = cursor.chars[cursor.index];
+	cursor.index += 1;
+	ch
+}
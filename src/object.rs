@@ -8,7 +8,7 @@ use std::time::{Tm};
 // 2) Boolean functions normally want effective boolean values which are false for invalid values.
 // 3) Functions like op_and do not always propagate errors.
 /// Value component of a triple.
-pub enum Object			// TODO: once we support serialization we'll need to add something like u8 type codes to int, float, and string values
+pub enum Object			// TODO: snapshots tag each variant, but int and float values still lose their original xsd type
 {								// TODO: predicate could maybe be enum with type code and uri
 	// literals
 	BoolValue(bool),
@@ -674,6 +674,86 @@ pub fn literal_to_object(value: &str, kind: &str, lang: &str) -> Object
 	}
 }
 
+/// Converts an object to a lexical value, type IRI, and language.
+///
+/// This is the inverse of literal_to_object and is typically used when serializing objects.
+/// Blank nodes are not literals so serializers have to handle them before calling this.
+pub fn object_to_literal(obj: &Object) -> (~str, ~str, ~str)
+{
+	match *obj
+	{
+		BoolValue(value) =>
+		{
+			(if value {~"true"} else {~"false"}, ~"http://www.w3.org/2001/XMLSchema#boolean", ~"")
+		}
+		IntValue(_) =>
+		{
+			(obj.to_str(), ~"http://www.w3.org/2001/XMLSchema#integer", ~"")
+		}
+		FloatValue(_) =>
+		{
+			(obj.to_str(), ~"http://www.w3.org/2001/XMLSchema#double", ~"")
+		}
+		DateTimeValue(_) =>
+		{
+			(obj.to_str(), ~"http://www.w3.org/2001/XMLSchema#dateTime", ~"")
+		}
+		StringValue(copy value, copy lang) =>
+		{
+			(value, ~"http://www.w3.org/2001/XMLSchema#string", lang)
+		}
+		TypedValue(copy value, copy kind) | InvalidValue(copy value, copy kind) =>
+		{
+			(value, kind, ~"")
+		}
+		IriValue(copy value) =>
+		{
+			(value, ~"http://www.w3.org/2001/XMLSchema#anyURI", ~"")
+		}
+		BlankValue(*) | UnboundValue | ErrorValue(*) =>
+		{
+			fail(fmt!("Can't convert %? to a literal", *obj));
+		}
+	}
+}
+
+/// Returns true if the objects are the same RDF term.
+///
+/// Literals are compared using the lexical form, datatype, and language tag returned by
+/// object_to_literal so this is looser than the Eq implementation (which requires the
+/// same variant): IntValue(35) is the same term as TypedValue(~"35", xsd:integer) and
+/// language tags are compared case-insensitively. Literals with different lexical forms
+/// are different terms even if they have the same value, e.g. "035" and "35". IRIs and
+/// blank nodes must match exactly. See section 6.5.1 of the RDF 1.1 concepts document.
+pub fn same_term(lhs: &Object, rhs: &Object) -> bool
+{
+	match (lhs, rhs)
+	{
+		(&IriValue(ref x), &IriValue(ref y)) | (&BlankValue(ref x), &BlankValue(ref y)) | (&ErrorValue(ref x), &ErrorValue(ref y)) =>
+		{
+			*x == *y
+		}
+		(&UnboundValue, &UnboundValue) =>
+		{
+			true
+		}
+		(&IriValue(*), _) | (&BlankValue(*), _) | (&ErrorValue(*), _) | (&UnboundValue, _) =>
+		{
+			false
+		}
+		(_, &IriValue(*)) | (_, &BlankValue(*)) | (_, &ErrorValue(*)) | (_, &UnboundValue) =>
+		{
+			false
+		}
+		_ =>
+		{
+			let (lvalue, lkind, llang) = object_to_literal(lhs);
+			let (rvalue, rkind, rlang) = object_to_literal(rhs);
+			lvalue == rvalue && lkind == rkind && str::to_lower(llang) == str::to_lower(rlang)
+		}
+	}
+}
+
 // Effective boolean value, see 17.2.2
 pub pure fn get_ebv(operand: &Object) -> result::Result<bool, ~str>
 {
@@ -0,0 +1,346 @@
+//! Differences between stores and a text format for them based on RDF Patch
+//! (http://afs.github.io/rdf-patch/).
+//!
+//! Each line of a patch is either "D" followed by an N-Triples statement to remove or "A"
+//! followed by an N-Triples statement to add. Blank lines and lines starting with '#' are
+//! ignored. Blank node labels are escaped so that they are read back exactly as they were
+//! written (removed triples must name the blank nodes already in the store).
+use io::{ReaderUtil, WriterUtil};
+
+/// The changes needed to turn one store into another.
+pub struct Patch
+{
+	pub removed: ~[Triple],		// these are applied before added
+	pub added: ~[Triple],
+}
+
+// Key is the canonical N-Triples form of the triples.
+priv type PatchUnit = {key: ~str, triples: ~[Triple]};
+
+/// Returns the changes needed to make old isomorphic to new.
+///
+/// Triples without blank nodes are compared individually. Triples which are connected
+/// through blank nodes are compared as a unit using their canonical form so that blank
+/// nodes which were merely relabeled are not reported as changes. If anything within a
+/// unit changes the entire unit is removed and added again. Removed triples use old's
+/// blank node labels and added blank nodes are given labels which are not used in old.
+pub fn diff_stores(old: &Store, new: &Store) -> Patch
+{
+	let old_units = patch_units(old);
+	let available = HashMap();
+	for old_units.eachi
+	|i, unit|
+	{
+		match available.find(@copy unit.key)
+		{
+			option::Some(list) => list.push(i),
+			option::None => available.insert(@copy unit.key, @dvec::from_vec(~[i])),
+		}
+	}
+	
+	let mut matched = vec::from_elem(old_units.len(), false);
+	let mut added = ~[];
+	for patch_units(new).each
+	|unit|
+	{
+		match available.find(@copy unit.key)
+		{
+			option::Some(list) if list.len() > 0 =>
+			{
+				matched[list.pop()] = true;
+			}
+			_ =>
+			{
+				vec::push_all(&mut added, unit.triples);
+			}
+		}
+	}
+	
+	let mut removed = ~[];
+	for old_units.eachi
+	|i, unit|
+	{
+		if !matched[i]
+		{
+			vec::push_all(&mut removed, unit.triples);
+		}
+	}
+	
+	Patch {removed: removed, added: relabel_added(old, new, added)}
+}
+
+/// Reads a patch from the reader.
+///
+/// Returns an error message with the line number if a line could not be parsed.
+pub fn load_patch(reader: io::Reader) -> result::Result<Patch, ~str>
+{
+	// parse_line wants a store but with blanks set to None it doesn't change it.
+	let scratch = Store(~[], &HashMap());
+	let mut removed = ~[];
+	let mut added = ~[];
+	let mut line_num = 0;
+	while !reader.eof()
+	{
+		let line = str::trim(reader.read_line());
+		line_num += 1;
+		
+		if line.is_not_empty() && !line.starts_with("#")
+		{
+			let text = str::slice(line, 1, line.len());
+			let result = match line.char_at(0)
+			{
+				'A' => parse_triple(&scratch, text).map(|triple| {vec::push(&mut added, copy *triple)}),
+				'D' => parse_triple(&scratch, text).map(|triple| {vec::push(&mut removed, copy *triple)}),
+				ch => result::Err(fmt!("Expected 'A' or 'D' but found '%c'", ch)),
+			};
+			match result
+			{
+				result::Ok(()) =>
+				{
+				}
+				result::Err(ref err) =>
+				{
+					return result::Err(fmt!("%s on line %?", *err, line_num));
+				}
+			}
+		}
+	}
+	result::Ok(Patch {removed: removed, added: added})
+}
+
+/// Like load_patch except that the patch is read from a string.
+pub fn load_patch_str(text: &str) -> result::Result<Patch, ~str>
+{
+	do io::with_str_reader(text) |reader| {load_patch(reader)}
+}
+
+/// Writes the patch with the removed triples first.
+pub fn write_patch(patch: &Patch, writer: io::Writer)
+{
+	for patch.removed.each |triple|
+	{
+		writer.write_line(~"D " + triple_to_exact_ntriples(triple));
+	}
+	for patch.added.each |triple|
+	{
+		writer.write_line(~"A " + triple_to_exact_ntriples(triple));
+	}
+}
+
+/// Returns the patch formatted using write_patch.
+pub fn patch_to_str(patch: &Patch) -> ~str
+{
+	do io::with_str_writer |writer| {write_patch(patch, writer)}
+}
+
+// ---- Private Functions -----------------------------------------------------
+// Returns a unit for each triple without a blank node and a unit for each set of triples
+// connected by blank nodes.
+priv fn patch_units(store: &Store) -> ~[PatchUnit]
+{
+	let mut triples = ~[];
+	for store.each
+	|triple|
+	{
+		vec::push(&mut triples, copy *triple);
+	};
+	
+	// Maps blank nodes to the indices of the triples which use them.
+	let uses = HashMap();
+	for triples.eachi
+	|i, triple|
+	{
+		for triple_blanks(triple).each
+		|name|
+		{
+			match uses.find(@copy *name)
+			{
+				option::Some(list) => list.push(i),
+				option::None => uses.insert(@copy *name, @dvec::from_vec(~[i])),
+			}
+		}
+	}
+	
+	let mut units = ~[];
+	let mut visited = vec::from_elem(triples.len(), false);
+	for triples.eachi
+	|i, triple|
+	{
+		if !visited[i]
+		{
+			let blanks = triple_blanks(triple);
+			if blanks.is_empty()
+			{
+				visited[i] = true;
+				vec::push(&mut units, {key: canonical_line(triple, |name| {name.to_owned()}), triples: ~[copy *triple]});
+			}
+			else
+			{
+				// Walk the triples reachable through blank nodes.
+				let component = Store(~[], &HashMap());
+				let mut pending = blanks;
+				while pending.is_not_empty()
+				{
+					let name = vec::pop(&mut pending);
+					for uses.get(@copy name).each
+					|j|
+					{
+						if !visited[*j]
+						{
+							visited[*j] = true;
+							let t = &triples[*j];
+							add_entries(&component, copy t.subject, ~[{predicate: copy t.predicate, object: t.object}]);
+							vec::push_all(&mut pending, triple_blanks(t));
+						}
+					}
+					uses.insert(@name, @DVec());
+				}
+				
+				let mut members = ~[];
+				for component.each |t| {vec::push(&mut members, copy *t);};
+				vec::push(&mut units, {key: store_to_canonical_ntriples(&component), triples: members});
+			}
+		}
+	}
+	units
+}
+
+priv fn triple_blanks(triple: &Triple) -> ~[~str]
+{
+	let mut names = ~[];
+	if triple.subject.starts_with("_:")
+	{
+		vec::push(&mut names, copy triple.subject);
+	}
+	match *triple.object
+	{
+		BlankValue(ref name) if *name != triple.subject => vec::push(&mut names, copy *name),
+		_ => (),
+	}
+	names
+}
+
+// Renames blank nodes in added which are also used in old.
+priv fn relabel_added(old: &Store, new: &Store, added: ~[Triple]) -> ~[Triple]
+{
+	let names = HashMap();
+	let mut count = 0;
+	let rename = |name: &str|
+	{
+		if name.starts_with("_:") && has_blank(old, name)
+		{
+			match names.find(@name.to_owned())
+			{
+				option::Some(new_name) =>
+				{
+					copy *new_name
+				}
+				option::None =>
+				{
+					let mut new_name = fmt!("_:added-%?", count);
+					while has_blank(old, new_name) || has_blank(new, new_name)
+					{
+						count += 1;
+						new_name = fmt!("_:added-%?", count);
+					}
+					count += 1;
+					names.insert(@name.to_owned(), @copy new_name);
+					new_name
+				}
+			}
+		}
+		else
+		{
+			name.to_owned()
+		}
+	};
+	
+	do added.map
+	|triple|
+	{
+		let object = match *triple.object
+		{
+			BlankValue(ref name) => @BlankValue(rename(*name)),
+			_ => triple.object,
+		};
+		{subject: rename(triple.subject), predicate: copy triple.predicate, object: object}
+	}
+}
+
+priv fn has_blank(store: &Store, name: &str) -> bool
+{
+	match store.terms.find_name(name)
+	{
+		option::Some(id) => store.subjects.contains_key(id) || store.objects.contains_key(id),
+		option::None => false,
+	}
+}
+
+priv fn apply_store_patch(store: &Store, patch: &Patch) -> result::Result<(), ~str>
+{
+	// Check the removals first so that the store is left unchanged if one is missing.
+	let needed = HashMap();
+	for patch.removed.each
+	|triple|
+	{
+		let key = @canonical_line(triple, |name| {name.to_owned()});
+		let count = needed.find(key).get_default(0) + 1;
+		if count > count_triple(store, triple)
+		{
+			return result::Err(fmt!("Couldn't find %s", triple.to_str()));
+		}
+		needed.insert(key, count);
+	}
+	
+	for patch.removed.each
+	|triple|
+	{
+		let (subject, entries, index) = find_triple(store, triple).get();
+		remove_entry_at(store, subject, entries, index);
+	}
+	
+	// Blank nodes which are already in the store are used as is, the others are renamed.
+	let blanks = HashMap();
+	let rename = |name: &str|
+	{
+		if name.starts_with("_:") && !blanks.contains_key(@name.to_owned()) && has_blank(store, name)
+		{
+			blanks.insert(@name.to_owned(), @name.to_owned());
+		}
+		rename_blank(store, blanks, name)
+	};
+	for patch.added.each
+	|triple|
+	{
+		let object = match *triple.object
+		{
+			BlankValue(ref name) => @BlankValue(rename(*name)),
+			_ => triple.object,
+		};
+		add_entries(store, rename(triple.subject), ~[{predicate: copy triple.predicate, object: object}]);
+	}
+	result::Ok(())
+}
+
+priv fn count_triple(store: &Store, triple: &Triple) -> uint
+{
+	match (find_entries(store, triple.subject), store.terms.find_name(triple.predicate))
+	{
+		(option::Some(entries), option::Some(predicate)) =>
+		{
+			let mut count = 0;
+			for entries.each |entry|
+			{
+				if entry.predicate == predicate && same_term(store.terms.get(entry.object), triple.object)
+				{
+					count += 1;
+				}
+			}
+			count
+		}
+		_ =>
+		{
+			0
+		}
+	}
+}
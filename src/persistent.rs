@@ -0,0 +1,663 @@
+//! Stores which are saved to disk as they are changed.
+//!
+//! Every change to the store is appended to a write-ahead log as it is made (or when the
+//! transaction it was made within is committed) and the log is
+//! periodically folded into a snapshot (see snapshot.rs). Opening the store loads the
+//! snapshot and then replays the log. The files are numbered by generation: snapshot.N
+//! holds the store as of a checkpoint and log.N the changes made since then. Checkpoints
+//! write the new snapshot before starting the new log so a crash while checkpointing
+//! leaves either the old or the new generation intact.
+//!
+//! Log records are lines starting with a one letter code:
+//! * A triple: the N-Triples triple was added (blank node labels are written so that they
+//!   are restored exactly).
+//! * D triple: the triple was removed.
+//! * R triple: the triple's object was replaced by the object from the W record on the next line.
+//! * W triple: the replacement triple.
+//! * C: the store was cleared.
+//! * B n: next_blank was set to n.
+//! * N "prefix" <path>...: the namespaces were set (prefixes are written as N-Triples
+//!   literals and paths as IRI refs so both may contain spaces).
+use io::{ReaderUtil, WriterUtil};
+
+/// Controls when the log is flushed to disk.
+pub enum SyncPolicy
+{
+	SyncAlways,				// fsync after every record (safest but slowest)
+	SyncOnCheckpoint,		// fsync when checkpointing and closing
+	SyncNever,				// leave it up to the OS
+}
+
+/// If checkpoint_interval is non-zero the log is folded into a new snapshot after that many changes.
+pub type PersistentOptions = {sync: SyncPolicy, checkpoint_interval: uint};
+
+/// A store whose changes are saved to files within a directory.
+///
+/// The store field is used to query and change the store. Close should be called when the
+/// store is no longer needed (until then the store and the PersistentStore reference each other).
+pub struct PersistentStore
+{
+	pub store: @Store,
+	pub dir: Path,
+	pub options: PersistentOptions,
+	mut generation: uint,
+	mut fd: libc::c_int,					// the log, -1 once closed
+	mut records: uint,						// number of changes logged since the last checkpoint
+	mut next_blank: int,					// value last written to the log
+	mut namespaces: ~[Namespace],			// value last written to the log
+	pending: DVec<~str>,					// records for the current transaction
+	mut pending_state: option::Option<(int, ~[Namespace])>,	// next_blank and namespaces logged before the transaction
+	mut listener: uint,
+	
+	drop {}
+}
+
+/// Opens the persistent store within dir, creating the directory if it does not exist.
+///
+/// The namespaces are only used if the store is new. Extension functions are not saved
+/// so they are always added.
+pub fn open_persistent_store(dir: &Path, namespaces: &[Namespace], extensions: &HashMap<@~str, ExtensionFn>, options: PersistentOptions) -> result::Result<@PersistentStore, ~str>
+{
+	if !os::path_is_dir(dir) && !os::make_dir(dir, 493 as libc::c_int)		// rwxr-xr-x
+	{
+		return result::Err(fmt!("Couldn't create %s.", dir.to_str()));
+	}
+	
+	let generation = find_generation(dir);
+	let snapshot = dir.push(fmt!("snapshot.%u", generation));
+	let store = if os::path_exists(&snapshot)
+	{
+		match io::file_reader(&snapshot)
+		{
+			result::Ok(reader) =>
+			{
+				match load_snapshot(reader)
+				{
+					result::Ok(move store) =>
+					{
+						for extensions.each |name, f|
+						{
+							store.extensions.insert(name, f);
+						};
+						@(move store)
+					}
+					result::Err(ref err) =>
+					{
+						return result::Err(fmt!("%s: %s", snapshot.to_str(), *err));
+					}
+				}
+			}
+			result::Err(copy err) =>
+			{
+				return result::Err(err);
+			}
+		}
+	}
+	else
+	{
+		@Store(namespaces, extensions)
+	};
+	
+	let log = dir.push(fmt!("log.%u", generation));
+	let mut records = 0;
+	let mut torn = false;
+	if os::path_exists(&log)
+	{
+		match io::file_reader(&log)
+		{
+			result::Ok(reader) =>
+			{
+				match replay_log(store, reader)
+				{
+					result::Ok((count, partial)) =>
+					{
+						records = count;
+						torn = partial;
+					}
+					result::Err(ref err) =>
+					{
+						return result::Err(fmt!("%s: %s", log.to_str(), *err));
+					}
+				}
+			}
+			result::Err(copy err) =>
+			{
+				return result::Err(err);
+			}
+		}
+	}
+	remove_old_files(dir, generation);
+	
+	let pstore = do open_file(&log, false).map
+	|fd|
+	{
+		@PersistentStore {
+			store: store,
+			dir: copy *dir,
+			options: options,
+			generation: generation,
+			fd: *fd,
+			records: records,
+			next_blank: store.next_blank,
+			namespaces: copy store.namespaces,
+			pending: DVec(),
+			pending_state: option::None,
+			listener: 0,
+		}
+	};
+	
+	do pstore.chain
+	|pstore|
+	{
+		// Appending to a partial record would glue the next record onto it so start a
+		// new generation instead.
+		let result = if torn {pstore.checkpoint()} else {result::Ok(())};
+		do result.map
+		|_|
+		{
+			pstore.listener = do store.add_listener |change| {log_change(pstore, change)};
+			pstore
+		}
+	}
+}
+
+pub impl PersistentStore
+{
+	/// Writes a snapshot of the store and starts a new empty log.
+	///
+	/// Fails if a transaction is in progress (the snapshot would include its changes).
+	fn checkpoint(&self) -> result::Result<(), ~str>
+	{
+		if self.store.transaction.is_some()
+		{
+			return result::Err(~"Can't checkpoint while a transaction is in progress.");
+		}
+		
+		let generation = self.generation + 1;
+		let temp = self.dir.push("snapshot.tmp");
+		match open_file(&temp, true)
+		{
+			result::Ok(fd) =>
+			{
+				// Snapshots do a lot of small writes so it's much faster to buffer them up.
+				let bytes = do io::with_bytes_writer |writer| {save_snapshot(self.store, writer)};
+				io::fd_writer(fd, false).write(bytes);
+				sync_fd(self, fd, true);
+				libc::close(fd);
+			}
+			result::Err(copy err) =>
+			{
+				return result::Err(err);
+			}
+		}
+		
+		let snapshot = self.dir.push(fmt!("snapshot.%u", generation));
+		if !os::rename_file(&temp, &snapshot)
+		{
+			return result::Err(fmt!("Couldn't rename %s.", temp.to_str()));
+		}
+		
+		let log = self.dir.push(fmt!("log.%u", generation));
+		match open_file(&log, true)
+		{
+			result::Ok(fd) =>
+			{
+				libc::close(self.fd);
+				self.fd = fd;
+			}
+			result::Err(copy err) =>
+			{
+				// Otherwise the next open would ignore the changes we continue to write to the old log.
+				os::remove_file(&snapshot);
+				return result::Err(err);
+			}
+		}
+		
+		self.generation = generation;
+		self.records = 0;
+		self.next_blank = self.store.next_blank;
+		self.namespaces = copy self.store.namespaces;
+		remove_old_files(&self.dir, generation);
+		result::Ok(())
+	}
+	
+	/// Flushes the log to disk (regardless of the sync policy).
+	fn sync(&self)
+	{
+		if self.fd >= 0
+		{
+			os::fsync_fd(self.fd, io::fsync::FSync);
+		}
+	}
+	
+	/// Logs next_blank and namespaces if they have changed, syncs the log (unless the
+	/// sync policy is SyncNever), and stops logging changes. Changes made within a
+	/// transaction which has not been committed are not logged.
+	fn close(&self)
+	{
+		if self.fd >= 0
+		{
+			let lines = if self.store.transaction.is_none() {state_records(self)} else {~[]};
+			if lines.is_not_empty()
+			{
+				write_records(self, lines);
+			}
+			
+			self.store.remove_listener(self.listener);
+			sync_fd(self, self.fd, true);
+			libc::close(self.fd);
+			self.fd = -1;
+		}
+	}
+}
+
+// ---- Private Functions -----------------------------------------------------
+priv fn open_file(path: &Path, truncate: bool) -> result::Result<libc::c_int, ~str>
+{
+	let flags = libc::O_WRONLY | libc::O_CREAT | if truncate {libc::O_TRUNC} else {libc::O_APPEND};
+	let fd = do str::as_c_str(path.to_str()) |buffer| {libc::open(buffer, flags, 420 as libc::c_int)};		// rw-r--r--
+	if fd >= 0
+	{
+		result::Ok(fd)
+	}
+	else
+	{
+		result::Err(fmt!("Couldn't open %s.", path.to_str()))
+	}
+}
+
+priv fn sync_fd(pstore: &PersistentStore, fd: libc::c_int, checkpointing: bool)
+{
+	match pstore.options.sync
+	{
+		SyncAlways =>
+		{
+			os::fsync_fd(fd, io::fsync::FSync);
+		}
+		SyncOnCheckpoint if checkpointing =>
+		{
+			os::fsync_fd(fd, io::fsync::FSync);
+		}
+		_ =>
+		{
+		}
+	}
+}
+
+// Returns the newest generation with a snapshot (or zero if there are no snapshots).
+priv fn find_generation(dir: &Path) -> uint
+{
+	do vec::foldl(0u, os::list_dir(dir))
+	|generation, name|
+	{
+		match parse_generation(*name, "snapshot.")
+		{
+			option::Some(n) if n > generation => n,
+			_ => generation,
+		}
+	}
+}
+
+priv fn parse_generation(name: &str, prefix: &str) -> option::Option<uint>
+{
+	if name.starts_with(prefix)
+	{
+		uint::from_str(str::slice(name, prefix.len(), name.len()))
+	}
+	else
+	{
+		option::None
+	}
+}
+
+// Removes files left over from older generations (and from checkpoints that failed).
+priv fn remove_old_files(dir: &Path, generation: uint)
+{
+	for os::list_dir(dir).each |name|
+	{
+		let prefix = if name.starts_with("snapshot.") {"snapshot."} else {"log."};
+		let old = match parse_generation(*name, prefix)
+		{
+			option::Some(n) => n != generation,
+			option::None => *name == ~"snapshot.tmp",
+		};
+		if old
+		{
+			os::remove_file(&dir.push(*name));
+		}
+	}
+}
+
+// Returns the number of records replayed and whether the last line was only partially
+// written. A last line which is unparseable or missing its new line is assumed to be
+// the result of a crash while writing and is ignored.
+priv fn replay_log(store: &Store, reader: io::Reader) -> result::Result<(uint, bool), ~str>
+{
+	// Records are always written with a trailing new line (the seek fails for empty files).
+	reader.seek(-1, io::SeekEnd);
+	let last = reader.read_byte();
+	let complete = last == -1 || last == '\n' as int;
+	reader.seek(0, io::SeekSet);
+	
+	let mut count = 0;
+	let mut line_num = 0;
+	let mut replaced = option::None;
+	while !reader.eof()
+	{
+		let line = reader.read_line();
+		line_num += 1;
+		
+		if reader.eof() && !complete
+		{
+			return result::Ok((count, true));
+		}
+		else if line.is_not_empty()
+		{
+			match replay_record(store, line, &mut replaced)
+			{
+				result::Ok(()) =>
+				{
+					count += 1;
+				}
+				result::Err(ref err) =>
+				{
+					if reader.eof()
+					{
+						return result::Ok((count, true));
+					}
+					return result::Err(fmt!("%s on line %?", *err, line_num));
+				}
+			}
+		}
+	}
+	result::Ok((count, false))
+}
+
+priv fn replay_record(store: &Store, line: &str, replaced: &mut option::Option<Triple>) -> result::Result<(), ~str>
+{
+	let text = str::trim(str::slice(line, 1, line.len()));
+	match line.char_at(0)
+	{
+		'A' =>
+		{
+			do parse_triple(store, text).map
+			|triple|
+			{
+				add_entries(store, copy triple.subject, ~[{predicate: copy triple.predicate, object: triple.object}]);
+			}
+		}
+		'D' =>
+		{
+			do parse_triple(store, text).chain
+			|triple|
+			{
+				match find_triple(store, &triple)
+				{
+					option::Some((subject, entries, index)) =>
+					{
+						remove_entry_at(store, subject, entries, index);
+						result::Ok(())
+					}
+					option::None =>
+					{
+						result::Err(fmt!("Couldn't find %s", triple.to_str()))
+					}
+				}
+			}
+		}
+		'R' =>
+		{
+			do parse_triple(store, text).map
+			|triple|
+			{
+				*replaced = option::Some(copy *triple);
+			}
+		}
+		'W' =>
+		{
+			let old = match *replaced
+			{
+				option::Some(copy old) => old,
+				option::None => return result::Err(~"Expected an R record before the W record"),
+			};
+			*replaced = option::None;
+			
+			do parse_triple(store, text).chain
+			|triple|
+			{
+				match find_triple(store, &old)
+				{
+					option::Some((subject, entries, index)) =>
+					{
+						replace_entry_at(store, subject, entries, index, triple.object);
+						result::Ok(())
+					}
+					option::None =>
+					{
+						result::Err(fmt!("Couldn't find %s", old.to_str()))
+					}
+				}
+			}
+		}
+		'C' =>
+		{
+			store.clear();
+			result::Ok(())
+		}
+		'B' =>
+		{
+			match int::from_str(text)
+			{
+				option::Some(n) =>
+				{
+					store.next_blank = n;
+					result::Ok(())
+				}
+				option::None =>
+				{
+					result::Err(~"Expected an integer")
+				}
+			}
+		}
+		'N' =>
+		{
+			do parse_namespaces(text).map
+			|namespaces|
+			{
+				store.namespaces = copy *namespaces;
+			}
+		}
+		ch =>
+		{
+			result::Err(fmt!("Bad record code '%c'", ch))
+		}
+	}
+}
+
+priv fn parse_triple(store: &Store, text: &str) -> result::Result<Triple, ~str>
+{
+	match parse_line(store, option::None, text, false)
+	{
+		result::Ok(option::Some((triple, _graph))) =>
+		{
+			result::Ok(triple)
+		}
+		result::Ok(option::None) =>
+		{
+			result::Err(~"Expected a triple")
+		}
+		result::Err(copy err) =>
+		{
+			result::Err(err)
+		}
+	}
+}
+
+// Parses the prefix and path pairs of an N record.
+priv fn parse_namespaces(text: &str) -> result::Result<~[Namespace], ~str>
+{
+	let cursor = Cursor {chars: str::chars(text), index: 0};
+	let mut namespaces = ~[];
+	loop
+	{
+		skip_ws(&cursor);
+		if at_end(&cursor)
+		{
+			return result::Ok(namespaces);
+		}
+		
+		let prefix = match parse_literal(&cursor)
+		{
+			result::Ok(@StringValue(copy prefix, _)) => prefix,
+			result::Ok(_) => return result::Err(~"Expected a plain literal for the prefix"),
+			result::Err(copy err) => return result::Err(err),
+		};
+		skip_ws(&cursor);
+		
+		match parse_iri(&cursor)
+		{
+			result::Ok(copy path) => vec::push(&mut namespaces, Namespace {prefix: prefix, path: path}),
+			result::Err(copy err) => return result::Err(err),
+		}
+	}
+}
+
+// Returns the subject id, entries, and index of the last copy of the triple (using RDF
+// term equality).
+priv fn find_triple(store: &Store, triple: &Triple) -> option::Option<(TermId, @DVec<TermEntry>, uint)>
+{
+	find_entry_index(store, triple, |id| {same_term(store.terms.get(id), triple.object)})
+}
+
+priv fn log_change(pstore: &PersistentStore, change: &Change)
+{
+	match *change
+	{
+		CommittedTransaction =>
+		{
+			let records = pstore.pending.get();
+			pstore.pending.set(~[]);
+			pstore.pending_state = option::None;
+			if records.is_not_empty()
+			{
+				write_records(pstore, records);
+				pstore.records += records.len();
+				maybe_checkpoint(pstore);
+			}
+		}
+		RolledBackTransaction =>
+		{
+			// Nothing in the transaction was logged so forget about it.
+			pstore.pending.set(~[]);
+			match copy pstore.pending_state
+			{
+				option::Some((next_blank, namespaces)) =>
+				{
+					pstore.next_blank = next_blank;
+					pstore.namespaces = namespaces;
+				}
+				option::None =>
+				{
+				}
+			}
+			pstore.pending_state = option::None;
+		}
+		_ =>
+		{
+			if pstore.store.transaction.is_some() && pstore.pending_state.is_none()
+			{
+				pstore.pending_state = option::Some((pstore.next_blank, copy pstore.namespaces));
+			}
+			
+			let mut lines = state_records(pstore);
+			match *change
+			{
+				AddedTriple(ref triple) =>
+				{
+					vec::push(&mut lines, ~"A " + triple_to_exact_ntriples(triple));
+				}
+				RemovedTriple(ref triple) =>
+				{
+					vec::push(&mut lines, ~"D " + triple_to_exact_ntriples(triple));
+				}
+				ReplacedTriple(ref triple, old) =>
+				{
+					vec::push(&mut lines, ~"R " + triple_to_exact_ntriples(&{subject: copy triple.subject, predicate: copy triple.predicate, object: old}));
+					vec::push(&mut lines, ~"W " + triple_to_exact_ntriples(triple));
+				}
+				ClearedStore =>
+				{
+					vec::push(&mut lines, ~"C");
+				}
+				CommittedTransaction | RolledBackTransaction =>
+				{
+				}
+			}
+			
+			if pstore.store.transaction.is_some()
+			{
+				pstore.pending.push(str::connect(lines, "\n"));
+			}
+			else
+			{
+				write_records(pstore, lines);
+				pstore.records += 1;
+				maybe_checkpoint(pstore);
+			}
+		}
+	}
+}
+
+// This is called from the listener but that's OK because stores call listeners once
+// the change has been completely applied and transactions are only logged after they
+// have been committed.
+priv fn maybe_checkpoint(pstore: &PersistentStore)
+{
+	if pstore.options.checkpoint_interval > 0 && pstore.records >= pstore.options.checkpoint_interval
+	{
+		match pstore.checkpoint()
+		{
+			result::Ok(()) =>
+			{
+			}
+			result::Err(ref err) =>
+			{
+				// The log is still intact so all we lose is the speedup from having a new snapshot.
+				error!("Checkpoint failed: %s", *err);
+			}
+		}
+	}
+}
+
+// Returns B and N records for next_blank and namespaces if they have changed since they
+// were last logged.
+priv fn state_records(pstore: &PersistentStore) -> ~[~str]
+{
+	let mut lines = ~[];
+	if pstore.store.next_blank != pstore.next_blank
+	{
+		vec::push(&mut lines, fmt!("B %?", pstore.store.next_blank));
+		pstore.next_blank = pstore.store.next_blank;
+	}
+	
+	let namespaces = copy pstore.store.namespaces;
+	if !same_namespaces(namespaces, pstore.namespaces)
+	{
+		let pairs = do namespaces.map |n| {fmt!("\"%s\" <%s>", escape_string(n.prefix), escape_iri(n.path))};
+		vec::push(&mut lines, ~"N " + str::connect(pairs, " "));
+		pstore.namespaces = namespaces;
+	}
+	lines
+}
+
+priv pure fn same_namespaces(lhs: &[Namespace], rhs: &[Namespace]) -> bool
+{
+	lhs.len() == rhs.len() && vec::all2(lhs, rhs, |x, y| {x.prefix == y.prefix && x.path == y.path})
+}
+
+priv fn write_records(pstore: &PersistentStore, lines: &[~str])
+{
+	// Use a single write to make it less likely that a crash will leave a partial record.
+	io::fd_writer(pstore.fd, false).write_str(str::connect(lines, "\n") + "\n");
+	sync_fd(pstore, pstore.fd, false);
+}
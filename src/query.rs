@@ -18,7 +18,8 @@ pub enum Algebra
 	Group(~[@Algebra]),
 	Optional(@Algebra),
 	Bind(expression::Expr, ~str),
-	Filter(expression::Expr)
+	Filter(expression::Expr),
+	Graph(Pattern, @Algebra)				// graph name (IRI or variable) + pattern to evaluate against the graph
 }
 
 pub struct QueryContext
@@ -31,6 +32,9 @@ pub struct QueryContext
 	pub limit: Option<uint>,
 	pub rng: rand::Rng,		// for RAND
 	pub timestamp: Tm,		// for NOW
+	pub from: ~[~str],							// FROM graphs (merged to form the default graph)
+	pub from_named: ~[~str],					// FROM NAMED graphs
+	pub named_graphs: HashMap<@~str, @Store>,	// graphs used by GRAPH patterns
 }
 
 // TODO: All of these functions except eval should be private. But then we'd have to move the unit tests
@@ -41,6 +45,9 @@ pub struct QueryContext
 /// Returns a solution or a 'runtime' error.
 pub type Selector = fn@ (s: &Store) -> result::Result<Solution, ~str>;
 
+/// Like Selector except that the query is executed against a dataset.
+pub type DatasetSelector = fn@ (d: &Dataset) -> result::Result<Solution, ~str>;
+
 // Returns the names (from the SELECT clause) followed by bound variable
 // names not in names.
 pub fn get_bindings(names: &[~str], algebra: &Algebra) -> ~[~str]
@@ -90,6 +97,11 @@ pub fn get_bindings(names: &[~str], algebra: &Algebra) -> ~[~str]
 			Filter(_) =>
 			{
 			}
+			Graph(ref name, v) =>
+			{
+				add_pattern_binding(bindings, copy *name);
+				add_algebra_bindings(bindings, v);
+			}
 		}
 	}
 	
@@ -144,6 +156,10 @@ pub fn algebra_to_str(store: &Store, algebra: &Algebra) -> ~str
 		{
 			~"filter " + expr_to_str(store, e)
 		}
+		Graph(ref n, a) =>
+		{
+			fmt!("graph %s %s", pattern_to_str(store, n), algebra_to_str(store, a))
+		}
 	}
 }
 
@@ -294,8 +310,9 @@ pub fn join_solutions(store: &Store, group1: &Solution, group2: &Solution, optio
 	return Solution {namespaces: copy store.namespaces, bindings: copy group1.bindings, num_selected: group1.num_selected, rows: result};
 }
 
-// Attempts to match a pattern to an IRI or blank subject.
-priv fn match_subject(bindings: &[~str], actual: &str, pattern: &Pattern, row: &mut SolutionRow) -> bool
+// Attempts to match a pattern to an IRI or blank subject. Expected is the id of the
+// pattern's constant (if it has one).
+priv fn match_subject(store: &Store, bindings: &[~str], actual: TermId, pattern: &Pattern, expected: option::Option<TermId>, row: &mut SolutionRow) -> bool
 {
 	match *pattern
 	{
@@ -304,25 +321,12 @@ priv fn match_subject(bindings: &[~str], actual: &str, pattern: &Pattern, row: &
 			let i = bindings.position_elem(name);
 			assert row[i.get()].is_unbound();					// subject is always the first thing matched so this should always be true
 			
-			row[i.get()] = if actual.starts_with("_:")
-				{
-					@BlankValue(actual.to_owned())
-				}
-				else
-				{
-					@IriValue(actual.to_owned())
-				};
+			row[i.get()] = store.terms.get(actual);
 			true
 		}
-		Constant(@IriValue(ref value)) =>
+		Constant(@IriValue(_)) | Constant(@BlankValue(_)) =>
 		{
-			//debug!("Actual subject %? %s %?", actual.to_str(), [~"did not match", ~"matched")[matched as uint], value];
-			actual == *value
-		}
-		Constant(@BlankValue(ref value)) =>
-		{
-			//debug!("Actual subject %? %s %?", actual.to_str(), [~"did not match", ~"matched")[matched as uint], value];
-			actual == *value
+			expected == option::Some(actual)
 		}
 		_ =>
 		{
@@ -332,7 +336,7 @@ priv fn match_subject(bindings: &[~str], actual: &str, pattern: &Pattern, row: &
 }
 
 // Attempts to match a pattern to an IRI predicate.
-priv fn match_predicate(bindings: &[~str], actual: &str, pattern: &Pattern, row: &mut SolutionRow) -> result::Result<bool, ~str>
+priv fn match_predicate(store: &Store, bindings: &[~str], actual: TermId, pattern: &Pattern, expected: option::Option<TermId>, row: &mut SolutionRow) -> result::Result<bool, ~str>
 {
 	match *pattern
 	{
@@ -343,7 +347,7 @@ priv fn match_predicate(bindings: &[~str], actual: &str, pattern: &Pattern, row:
 			{
 				@UnboundValue =>
 				{
-					row[i.get()] = @IriValue(actual.to_owned());
+					row[i.get()] = store.terms.get(actual);
 					result::Ok(true)
 				}
 				_ =>
@@ -352,10 +356,9 @@ priv fn match_predicate(bindings: &[~str], actual: &str, pattern: &Pattern, row:
 				}
 			}
 		}
-		Constant(@IriValue(ref value)) =>
+		Constant(@IriValue(_)) =>
 		{
-			//debug!("Actual predicate %? %s %?", actual.to_str(), [~"did not match", ~"matched")[matched as uint], value];
-			result::Ok(actual == *value)
+			result::Ok(expected == option::Some(actual))
 		}
 		_ =>
 		{
@@ -393,52 +396,168 @@ priv fn match_object(solution: &Solution, actual: @Object, pattern: &Pattern, ro
 	}
 }
 
-// Iterates over all the statements where the subject matches spattern and calls callback for each one.
-priv fn iterate_matches(store: &Store, bindings: &[~str], spattern: &Pattern, callback: fn (SolutionRow, &Entry) -> bool)
+// Index used to find the statements which may match a TriplePattern.
+enum Index
+{
+	SubjectIndex(TermId, @DVec<TermEntry>),
+	PredicateIndex(TermId, @DVec<PredicateEntry>),
+	ObjectIndex(TermId, @DVec<ObjectEntry>),
+	FullScan,
+}
+
+// Ids of the constants in a TriplePattern. These are None for variables and for constants
+// which are not in the store.
+priv type PatternIds = {subject: option::Option<TermId>, predicate: option::Option<TermId>, object: option::Option<TermId>};
+
+priv fn pattern_ids(store: &Store, matcher: &TriplePattern) -> PatternIds
 {
-	fn invoke(bindings: &[~str], subject: &str, pattern: &Pattern, entries: @DVec<Entry>, callback: fn (SolutionRow, &Entry) -> bool) -> bool
+	{subject: pattern_id(store, &matcher.subject), predicate: pattern_id(store, &matcher.predicate), object: pattern_id(store, &matcher.object)}
+}
+
+priv fn pattern_id(store: &Store, pattern: &Pattern) -> option::Option<TermId>
+{
+	match *pattern
 	{
-		for entries.each() |entry|
+		Constant(value) => store.terms.find(value),
+		Variable(_) => option::None,
+	}
+}
+
+// Returns the most selective index for the constant parts of the pattern.
+priv fn select_index(store: &Store, matcher: &TriplePattern, ids: &PatternIds) -> Index
+{
+	match matcher.subject
+	{
+		Constant(@IriValue(_)) | Constant(@BlankValue(_)) =>
 		{
-			let mut row = vec::from_elem(bindings.len(), @UnboundValue);
-			if match_subject(bindings, subject, pattern, &mut row)
+			// This is the common case where we are attempting to match a specific subject.
+			return SubjectIndex(ids.subject.get_default(0), get_index_items(store.subjects, ids.subject));
+		}
+		_ =>
+		{
+		}
+	}
+	
+	let predicates = match matcher.predicate
+	{
+		Constant(@IriValue(_)) => option::Some((ids.predicate.get_default(0), get_index_items(store.predicates, ids.predicate))),
+		_ => option::None,
+	};
+	let objects = match matcher.object
+	{
+		Constant(object) =>
+		{
+			match *object
 			{
-				if !callback(move row, entry)
-				{
-					return false;
-				}
+				IriValue(_) | BlankValue(_) => option::Some((ids.object.get_default(0), get_index_items(store.objects, ids.object))),
+				_ => option::None,
 			}
 		}
-		true
+		_ =>
+		{
+			option::None
+		}
+	};
+	
+	match (predicates, objects)
+	{
+		(option::Some((predicate, p)), option::Some((_, o))) if p.len() < o.len() =>
+		{
+			PredicateIndex(predicate, p)
+		}
+		(_, option::Some((object, o))) =>
+		{
+			ObjectIndex(object, o)
+		}
+		(option::Some((predicate, p)), option::None) =>
+		{
+			PredicateIndex(predicate, p)
+		}
+		(option::None, option::None) =>
+		{
+			FullScan
+		}
+	}
+}
+
+// Returns an empty list if the key is None (i.e. the term isn't in the store).
+priv fn get_index_items<T: Copy>(index: HashMap<TermId, @DVec<T>>, key: option::Option<TermId>) -> @DVec<T>
+{
+	match key
+	{
+		option::Some(id) => option::get_default(index.find(id), @DVec()),
+		option::None => @DVec(),
+	}
+}
+
+// Iterates over all the statements which may match the pattern and calls callback for each one
+// where the subject matches.
+priv fn iterate_matches(store: &Store, bindings: &[~str], matcher: &TriplePattern, ids: &PatternIds, callback: fn (SolutionRow, &TermEntry) -> bool)
+{
+	fn invoke(store: &Store, bindings: &[~str], subject: TermId, pattern: &Pattern, expected: option::Option<TermId>, entry: &TermEntry, callback: fn (SolutionRow, &TermEntry) -> bool) -> bool
+	{
+		let mut row = vec::from_elem(bindings.len(), @UnboundValue);
+		if match_subject(store, bindings, subject, pattern, expected, &mut row)
+		{
+			callback(move row, entry)
+		}
+		else
+		{
+			true
+		}
 	}
 	
-	match *spattern
+	match select_index(store, matcher, ids)
 	{
-		Constant(@IriValue(ref subject)) | Constant(@BlankValue(ref subject)) =>
+		SubjectIndex(subject, entries) =>
 		{
-			// Optimization for a common case where we are attempting to match a specific subject.
-			let candidate = store.subjects.find(@copy *subject);
-			if option::is_some(&candidate)
+			info!("--- using subject index for %s", pattern_to_str(store, &matcher.subject));
+			for entries.each() |entry|
 			{
-				info!("--- matched subject %?", subject);
-				let entries = option::get(candidate);
-				if !invoke(bindings, *subject, spattern, entries, callback)
+				if !invoke(store, bindings, subject, &matcher.subject, ids.subject, entry, callback)
 				{
 					return;
 				}
 			}
 		}
-		_ =>
+		PredicateIndex(predicate, items) =>
 		{
-			for store.subjects.each() |subject, entries|
+			info!("--- using predicate index for %s", pattern_to_str(store, &matcher.predicate));
+			for items.each() |item|
+			{
+				let entry = {predicate: predicate, object: item.object};
+				if !invoke(store, bindings, item.subject, &matcher.subject, ids.subject, &entry, callback)
+				{
+					return;
+				}
+			}
+		}
+		ObjectIndex(object, items) =>
+		{
+			info!("--- using object index for %s", pattern_to_str(store, &matcher.object));
+			for items.each() |item|
 			{
-				debug!("--- trying subject %?", subject);
-				if !invoke(bindings, *subject, spattern, entries, callback)
+				let entry = {predicate: item.predicate, object: object};
+				if !invoke(store, bindings, item.subject, &matcher.subject, ids.subject, &entry, callback)
 				{
 					return;
 				}
 			}
 		}
+		FullScan =>
+		{
+			for store.subjects.each() |subject, entries|
+			{
+				debug!("--- trying subject %s", store.terms.get_name(subject));
+				for entries.each() |entry|
+				{
+					if !invoke(store, bindings, subject, &matcher.subject, ids.subject, entry, callback)
+					{
+						return;
+					}
+				}
+			}
+		}
 	}
 }
 
@@ -447,13 +566,14 @@ pub fn eval_basic(store: &Store,  bindings: ~[~str], num_selected: uint, matcher
 {
 	let mut solution = Solution {namespaces: copy store.namespaces, bindings: copy bindings, num_selected: num_selected, rows: ~[]};
 	
-	for iterate_matches(store, bindings, &matcher.subject) |r, entry|
+	let ids = pattern_ids(store, matcher);
+	for iterate_matches(store, bindings, matcher, &ids) |r, entry|
 	{
 		let mut row = move r;		// need the move to shut the borrow checker up
-		let result = match_predicate(bindings, entry.predicate, &matcher.predicate, &mut row);
+		let result = match_predicate(store, bindings, entry.predicate, &matcher.predicate, ids.predicate, &mut row);
 		if result.is_ok() && result.get()
 		{
-			let result = match_object(&solution, entry.object, &matcher.object, &mut row);
+			let result = match_object(&solution, store.terms.get(entry.object), &matcher.object, &mut row);
 			if result.is_ok() && result.get()
 			{
 				info!("basic %s matched %s", triple_pattern_to_str(store, matcher), solution_row_to_str(store, &solution, &row));
@@ -635,6 +755,74 @@ priv fn eval_group(store: &Store, context: &QueryContext, bindings: ~[~str], num
 	return result::Ok(result);
 }
 
+// Evaluates term against the named graph (or graphs if name is a variable in which case the
+// variable is bound to the name of the graph that matched).
+priv fn eval_graph(context: &QueryContext, bindings: ~[~str], num_selected: uint, name: &Pattern, term: &Algebra) -> result::Result<Solution, ~str>
+{
+	let mut result = Solution {namespaces: copy context.namespaces, bindings: copy bindings, num_selected: num_selected, rows: ~[]};
+	match *name
+	{
+		Constant(@IriValue(ref iri)) =>
+		{
+			match context.named_graphs.find(@copy *iri)
+			{
+				option::Some(graph) =>
+				{
+					eval_algebra(graph, &QueryContext {algebra: copy *term, ..*context}, bindings, num_selected)
+				}
+				option::None =>
+				{
+					result::Ok(result)
+				}
+			}
+		}
+		Variable(ref var) =>
+		{
+			let mut names = ~[];
+			for context.named_graphs.each_key |name|
+			{
+				vec::push(&mut names, copy *name);
+			};
+			let names = std::sort::merge_sort(names, |x, y| {*x <= *y});
+			
+			let index = bindings.position_elem(var).get();
+			for names.each |name|
+			{
+				let graph = context.named_graphs.get(@copy *name);
+				let value = if name.starts_with("_:") {@BlankValue(copy *name)} else {@IriValue(copy *name)};
+				match eval_algebra(graph, &QueryContext {algebra: copy *term, ..*context}, copy bindings, num_selected)
+				{
+					result::Ok(ref solution) =>
+					{
+						for solution.rows.each |row|
+						{
+							if row[index].is_unbound()
+							{
+								let mut row = copy *row;
+								row[index] = value;
+								result.rows.push(row);
+							}
+							else if equal_objects(row[index], value)
+							{
+								result.rows.push(copy *row);
+							}
+						}
+					}
+					result::Err(copy mesg) =>
+					{
+						return result::Err(mesg);
+					}
+				}
+			}
+			result::Ok(result)
+		}
+		_ =>
+		{
+			result::Err(~"GRAPH names must be IRIs or variables.")
+		}
+	}
+}
+
 // Evaluates the terms against either the store or the current version of the solution. Terms that return new
 // solutions join their solution to the current solution. Returns either the solution or an error message.
 priv fn eval_algebra(store: &Store, context: &QueryContext, bindings: ~[~str], num_selected: uint) -> result::Result<Solution, ~str>
@@ -664,6 +852,10 @@ priv fn eval_algebra(store: &Store, context: &QueryContext, bindings: ~[~str], n
 			// pattern (filter position doesn't matter?).
 			result::Err(~"FILTER should appear last in a pattern group.")
 		}
+		Graph(ref name, term) =>
+		{
+			eval_graph(context, bindings, num_selected, name, term)
+		}
 	}
 }
 
@@ -823,3 +1015,65 @@ pub fn eval(names: &[~str], context: &QueryContext) -> Selector
 		}
 	}
 }
+
+/// Like eval except that the closure executes the query against a dataset.
+/// 
+/// If the query has FROM or FROM NAMED clauses then the default graph is the merge of
+/// the FROM graphs and GRAPH patterns use the FROM NAMED graphs. Otherwise the dataset's
+/// default and named graphs are used.
+pub fn eval_dataset(names: &[~str], context: &QueryContext) -> DatasetSelector
+{
+	let names = vec::from_slice(names);
+	let context = copy *context;
+	|dataset: &Dataset, move names|
+	{
+		let (store, named_graphs) = get_query_graphs(dataset, &context);
+		let selector = eval(names, &QueryContext {named_graphs: named_graphs, ..copy context});
+		selector(store)
+	}
+}
+
+// Returns the default graph and the named graphs to use when executing a query against a dataset.
+priv fn get_query_graphs(dataset: &Dataset, context: &QueryContext) -> (@Store, HashMap<@~str, @Store>)
+{
+	if context.from.is_empty() && context.from_named.is_empty()
+	{
+		return (dataset.default_graph, dataset.named_graphs);
+	}
+	
+	// The default graph is the RDF merge of the FROM graphs so blank nodes are renamed
+	// (by merge) and triples which appear in more than one graph are only added once.
+	let store = @Store(~[], &dataset.default_graph.extensions);
+	store.namespaces = copy dataset.default_graph.namespaces;
+	store.set_semantics = true;
+	for context.from.each |name|
+	{
+		match dataset.named_graphs.find(@copy *name)
+		{
+			option::Some(graph) =>
+			{
+				store.merge(graph);
+			}
+			option::None =>
+			{
+			}
+		}
+	}
+	
+	let named_graphs = HashMap();
+	for context.from_named.each |name|
+	{
+		match dataset.named_graphs.find(@copy *name)
+		{
+			option::Some(graph) =>
+			{
+				named_graphs.insert(@copy *name, graph);
+			}
+			option::None =>
+			{
+			}
+		}
+	}
+	
+	(store, named_graphs)
+}
@@ -0,0 +1,784 @@
+//! Loads and saves stores using RDF/XML (http://www.w3.org/TR/rdf-syntax-grammar/).
+use io::WriterUtil;
+use xml::*;
+
+struct RdfXmlLoader
+{
+	blanks: HashMap<@~str, @~str>,		// maps rdf:nodeID values to store blank names
+}
+
+// Values inherited from ancestor elements.
+struct Context
+{
+	base: ~str,
+	lang: ~str,
+}
+
+struct RdfXmlWriter
+{
+	writer: io::Writer,
+	namespaces: ~[Namespace],			// store namespaces plus generated prefixes
+	counts: HashMap<@~str, uint>,		// number of times each blank node is used as an object
+	written: HashMap<@~str, bool>,		// subjects which have been (or are being) written
+}
+
+/// Parses an RDF/XML document and adds the resulting triples to the store.
+///
+/// The root element may be rdf:RDF or a single node element. rdf:nodeID values and
+/// anonymous nodes are renamed using get_blank_name so they will not collide with blank
+/// nodes already in the store. Returns an error message if the document could not be
+/// parsed (in which case triples before the error may have been added).
+pub fn load_rdfxml(store: &Store, text: &str) -> result::Result<(), ~str>
+{
+	match parse_xml(text)
+	{
+		result::Ok(root) =>
+		{
+			let loader = RdfXmlLoader {blanks: HashMap()};
+			let context = update_context(&Context {base: ~"", lang: ~""}, root);
+			if is_rdf(&root.name, "RDF")
+			{
+				match child_elements(root)
+				{
+					result::Ok(children) =>
+					{
+						for children.each |child|
+						{
+							match add_node(store, &loader, &context, *child)
+							{
+								result::Ok(_) => (),
+								result::Err(copy err) => return result::Err(err),
+							}
+						}
+						result::Ok(())
+					}
+					result::Err(copy err) =>
+					{
+						result::Err(err)
+					}
+				}
+			}
+			else
+			{
+				do add_node(store, &loader, &context, root).chain |_object| {result::Ok(())}
+			}
+		}
+		result::Err(copy err) =>
+		{
+			result::Err(err)
+		}
+	}
+}
+
+/// Like load_rdfxml except that the text is read from a reader.
+pub fn load_rdfxml_reader(store: &Store, reader: io::Reader) -> result::Result<(), ~str>
+{
+	let bytes = reader.read_whole_stream();
+	if str::is_utf8(bytes)
+	{
+		load_rdfxml(store, str::from_bytes(bytes))
+	}
+	else
+	{
+		result::Err(~"RDF/XML documents must be UTF-8.")
+	}
+}
+
+/// Writes the store using the RDF/XML format.
+///
+/// The store's namespaces are declared on the rdf:RDF element along with generated
+/// prefixes for predicates which cannot otherwise be written as qualified names. Blank
+/// nodes used once are nested within the property element which references them and
+/// container members are written using rdf:li. Returns an error (without writing
+/// anything) if a predicate cannot be split into an XML qualified name.
+pub fn write_rdfxml(store: &Store, writer: io::Writer) -> result::Result<(), ~str>
+{
+	match get_xml_namespaces(store)
+	{
+		result::Ok(namespaces) =>
+		{
+			let state = RdfXmlWriter {writer: writer, namespaces: namespaces, counts: count_blanks(store), written: HashMap()};
+			
+			writer.write_line("<?xml version=\"1.0\" encoding=\"UTF-8\"?>");
+			writer.write_str("<rdf:RDF");
+			for state.namespaces.each |namespace|
+			{
+				writer.write_str(fmt!("\n\txmlns:%s=\"%s\"", namespace.prefix, escape_xml(namespace.path)));
+			}
+			writer.write_line(">");
+			
+			let subjects = sorted_subjects(store);
+			for subjects.each |subject|
+			{
+				if !is_nested_blank(&state, *subject)
+				{
+					write_node(store, &state, *subject, 1, false);
+				}
+			}
+			
+			// Blank nodes which are only referenced from within a cycle.
+			for subjects.each |subject|
+			{
+				if !state.written.contains_key(@copy *subject)
+				{
+					write_node(store, &state, *subject, 1, false);
+				}
+			}
+			writer.write_line("</rdf:RDF>");
+			result::Ok(())
+		}
+		result::Err(copy err) =>
+		{
+			result::Err(err)
+		}
+	}
+}
+
+/// Returns the store formatted using the RDF/XML format.
+pub fn store_to_rdfxml(store: &Store) -> result::Result<~str, ~str>
+{
+	let mut result = result::Ok(());
+	let text = do io::with_str_writer |writer| {result = write_rdfxml(store, writer)};
+	do result.chain |_r| {result::Ok(copy text)}
+}
+
+// ---- Private Functions -----------------------------------------------------
+
+// nodeElement: returns the subject (an IriValue or BlankValue).
+priv fn add_node(store: &Store, loader: &RdfXmlLoader, context: &Context, element: &XmlElement) -> result::Result<@Object, ~str>
+{
+	let context = update_context(context, element);
+	if is_syntax_term(&element.name) || is_rdf(&element.name, "li")
+	{
+		return error(element, fmt!("%s cannot be used as a node element", element.qname));
+	}
+	
+	let subject = get_subject(store, loader, &context, element);
+	let name = object_to_subject(subject);
+	if !is_rdf(&element.name, "Description")
+	{
+		add(store, name, ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#type", @IriValue(element_iri(element)));
+	}
+	add_property_attributes(store, &context, name, element);
+	
+	match child_elements(element)
+	{
+		result::Ok(children) =>
+		{
+			let mut li = 1;
+			for children.each |child|
+			{
+				match add_property(store, loader, &context, name, *child, &mut li)
+				{
+					result::Ok(()) => (),
+					result::Err(copy err) => return result::Err(err),
+				}
+			}
+			result::Ok(subject)
+		}
+		result::Err(copy err) =>
+		{
+			result::Err(err)
+		}
+	}
+}
+
+priv fn get_subject(store: &Store, loader: &RdfXmlLoader, context: &Context, element: &XmlElement) -> @Object
+{
+	match find_rdf_attr(element, "about")
+	{
+		option::Some(ref about) =>
+		{
+			@IriValue(resolve_iri(context.base, *about))
+		}
+		option::None =>
+		{
+			match find_rdf_attr(element, "ID")
+			{
+				option::Some(ref id) =>
+				{
+					@IriValue(resolve_iri(context.base, ~"#" + *id))
+				}
+				option::None =>
+				{
+					match find_rdf_attr(element, "nodeID")
+					{
+						option::Some(ref id) => @BlankValue(get_node_id(store, loader, *id)),
+						option::None => @BlankValue(get_blank_name(store, "node")),
+					}
+				}
+			}
+		}
+	}
+}
+
+// propertyElt: li is the next index to use for rdf:li elements.
+priv fn add_property(store: &Store, loader: &RdfXmlLoader, context: &Context, subject: &str, element: &XmlElement, li: &mut uint) -> result::Result<(), ~str>
+{
+	let context = update_context(context, element);
+	let predicate = if is_rdf(&element.name, "li")
+	{
+		let p = fmt!("http://www.w3.org/1999/02/22-rdf-syntax-ns#_%?", *li);
+		*li += 1;
+		p
+	}
+	else if is_syntax_term(&element.name) || is_rdf(&element.name, "Description")
+	{
+		return error(element, fmt!("%s cannot be used as a property element", element.qname));
+	}
+	else
+	{
+		element_iri(element)
+	};
+	
+	let object = match find_rdf_attr(element, "parseType")
+	{
+		option::Some(~"Resource") =>
+		{
+			let blank = get_blank_name(store, "resource");
+			do child_elements(element).chain |children|
+			{
+				let mut li = 1;
+				let mut result = result::Ok(@BlankValue(copy blank));
+				for children.each |child|
+				{
+					match add_property(store, loader, &context, blank, *child, &mut li)
+					{
+						result::Ok(()) => (),
+						result::Err(copy err) => {result = result::Err(err); break}
+					}
+				}
+				result
+			}
+		}
+		option::Some(~"Collection") =>
+		{
+			do child_elements(element).chain |children|
+			{
+				add_collection(store, loader, &context, children)
+			}
+		}
+		option::Some(_) =>
+		{
+			// Literal (and unknown parse types which the spec says are to be treated as Literal).
+			let text = str::concat(do element.children.map |child| {xml_node_to_str(child)});
+			result::Ok(@TypedValue(text, ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#XMLLiteral"))
+		}
+		option::None =>
+		{
+			do child_elements(element).chain |children|
+			{
+				if children.len() == 1
+				{
+					add_node(store, loader, &context, children[0])
+				}
+				else if children.len() > 1
+				{
+					error(element, fmt!("%s may only contain a single node element", element.qname))
+				}
+				else
+				{
+					result::Ok(get_empty_object(store, loader, &context, element))
+				}
+			}
+		}
+	};
+	
+	do object.chain |object|
+	{
+		add(store, subject, predicate, object);
+		match find_rdf_attr(element, "ID")
+		{
+			option::Some(ref id) =>
+			{
+				let statement = resolve_iri(context.base, ~"#" + *id);
+				add(store, statement, ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#type", @IriValue(~"http://www.w3.org/1999/02/22-rdf-syntax-ns#Statement"));
+				add(store, statement, ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#subject", subject_to_object(subject));
+				add(store, statement, ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#predicate", @IriValue(copy predicate));
+				add(store, statement, ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#object", object);
+			}
+			option::None =>
+			{
+			}
+		}
+		result::Ok(())
+	}
+}
+
+// Handles literal property elements and property elements without content which use
+// rdf:resource, rdf:nodeID, or property attributes.
+priv fn get_empty_object(store: &Store, loader: &RdfXmlLoader, context: &Context, element: &XmlElement) -> @Object
+{
+	let has_properties = element.attributes.any(|attr| {is_property_attribute(&attr.name)});
+	match (find_rdf_attr(element, "resource"), find_rdf_attr(element, "nodeID"))
+	{
+		(option::Some(ref resource), _) =>
+		{
+			let object = @IriValue(resolve_iri(context.base, *resource));
+			add_property_attributes(store, context, object_to_subject(object), element);
+			object
+		}
+		(option::None, option::Some(ref id)) =>
+		{
+			let object = @BlankValue(get_node_id(store, loader, *id));
+			add_property_attributes(store, context, object_to_subject(object), element);
+			object
+		}
+		(option::None, option::None) if has_properties =>
+		{
+			let object = @BlankValue(get_blank_name(store, "node"));
+			add_property_attributes(store, context, object_to_subject(object), element);
+			object
+		}
+		(option::None, option::None) =>
+		{
+			match find_rdf_attr(element, "datatype")
+			{
+				option::Some(ref kind) => @literal_to_object(xml_text(element), resolve_iri(context.base, *kind), ""),
+				option::None => @literal_to_object(xml_text(element), "http://www.w3.org/2001/XMLSchema#string", context.lang),
+			}
+		}
+	}
+}
+
+// Collections are encoded using standard first/rest chains.
+priv fn add_collection(store: &Store, loader: &RdfXmlLoader, context: &Context, elements: &[@XmlElement]) -> result::Result<@Object, ~str>
+{
+	if elements.is_empty()
+	{
+		return result::Ok(@IriValue(~"http://www.w3.org/1999/02/22-rdf-syntax-ns#nil"));
+	}
+	
+	let head = get_blank_name(store, "list");
+	let mut blank = copy head;
+	for elements.eachi |i, element|
+	{
+		match add_node(store, loader, context, *element)
+		{
+			result::Ok(object) =>
+			{
+				add(store, blank, ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#first", object);
+			}
+			result::Err(copy err) =>
+			{
+				return result::Err(err);
+			}
+		}
+		
+		if i + 1 < elements.len()
+		{
+			let next = get_blank_name(store, "list");
+			add(store, blank, ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#rest", @BlankValue(copy next));
+			blank = next;
+		}
+		else
+		{
+			add(store, blank, ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#rest", @IriValue(~"http://www.w3.org/1999/02/22-rdf-syntax-ns#nil"));
+		}
+	}
+	result::Ok(@BlankValue(head))
+}
+
+priv fn add_property_attributes(store: &Store, context: &Context, subject: &str, element: &XmlElement)
+{
+	for element.attributes.each |attr|
+	{
+		if is_property_attribute(&attr.name)
+		{
+			let predicate = attr.name.namespace + attr.name.local;
+			if is_rdf(&attr.name, "type")
+			{
+				add(store, subject, predicate, @IriValue(resolve_iri(context.base, attr.value)));
+			}
+			else
+			{
+				add(store, subject, predicate, @literal_to_object(attr.value, "http://www.w3.org/2001/XMLSchema#string", context.lang));
+			}
+		}
+	}
+}
+
+priv fn add(store: &Store, subject: &str, predicate: &str, object: @Object)
+{
+	store.add_triple(~[], {subject: subject.to_owned(), predicate: predicate.to_owned(), object: object});
+}
+
+priv fn get_node_id(store: &Store, loader: &RdfXmlLoader, id: &str) -> ~str
+{
+	match loader.blanks.find(@id.to_owned())
+	{
+		option::Some(name) =>
+		{
+			copy *name
+		}
+		option::None =>
+		{
+			let name = get_blank_name(store, id);
+			loader.blanks.insert(@id.to_owned(), @copy name);
+			name
+		}
+	}
+}
+
+priv fn update_context(context: &Context, element: &XmlElement) -> Context
+{
+	let mut base = copy context.base;
+	let mut lang = copy context.lang;
+	for element.attributes.each |attr|
+	{
+		if attr.name.namespace == ~"http://www.w3.org/XML/1998/namespace"
+		{
+			if attr.name.local == ~"base"
+			{
+				base = resolve_iri(base, attr.value);
+			}
+			else if attr.name.local == ~"lang"
+			{
+				lang = copy attr.value;
+			}
+		}
+	}
+	Context {base: base, lang: lang}
+}
+
+// Returns the child elements or an error if there is non-whitespace text.
+priv fn child_elements(element: &XmlElement) -> result::Result<~[@XmlElement], ~str>
+{
+	let mut children = ~[];
+	for element.children.each |child|
+	{
+		match *child
+		{
+			XmlElementNode(e) =>
+			{
+				vec::push(&mut children, e);
+			}
+			XmlTextNode(ref text) =>
+			{
+				if !str::is_whitespace(*text)
+				{
+					return error(element, fmt!("%s cannot contain text", element.qname));
+				}
+			}
+		}
+	}
+	result::Ok(children)
+}
+
+priv fn find_rdf_attr(element: &XmlElement, local: &str) -> option::Option<~str>
+{
+	for element.attributes.each |attr|
+	{
+		if is_rdf(&attr.name, local)
+		{
+			return option::Some(copy attr.value);
+		}
+	}
+	option::None
+}
+
+// Attributes which are neither RDF syntax terms nor reserved xml attributes.
+priv fn is_property_attribute(name: &XmlName) -> bool
+{
+	if name.namespace.is_empty() || name.namespace == ~"http://www.w3.org/XML/1998/namespace"
+	{
+		false
+	}
+	else
+	{
+		!is_syntax_term(name) && !is_rdf(name, "li") && !is_rdf(name, "Description") &&
+			!is_rdf(name, "aboutEach") && !is_rdf(name, "aboutEachPrefix") && !is_rdf(name, "bagID")
+	}
+}
+
+priv fn is_syntax_term(name: &XmlName) -> bool
+{
+	let terms = ~["RDF", "ID", "about", "parseType", "resource", "nodeID", "datatype"];
+	terms.any(|local| {is_rdf(name, *local)})
+}
+
+priv fn is_rdf(name: &XmlName, local: &str) -> bool
+{
+	name.namespace == ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#" && name.local == local.to_owned()
+}
+
+priv fn element_iri(element: &XmlElement) -> ~str
+{
+	element.name.namespace + element.name.local
+}
+
+priv fn object_to_subject(object: @Object) -> ~str
+{
+	match *object
+	{
+		IriValue(ref name) | BlankValue(ref name) => copy *name,
+		_ => fail fmt!("Expected an IRI or blank node but found %s", object.to_str()),
+	}
+}
+
+priv fn subject_to_object(subject: &str) -> @Object
+{
+	if subject.starts_with("_:")
+	{
+		@BlankValue(subject.to_owned())
+	}
+	else
+	{
+		@IriValue(subject.to_owned())
+	}
+}
+
+priv fn error<T>(element: &XmlElement, mesg: ~str) -> result::Result<T, ~str>
+{
+	result::Err(fmt!("%s on line %?", mesg, element.line))
+}
+
+priv fn get_xml_namespaces(store: &Store) -> result::Result<~[Namespace], ~str>
+{
+	let namespaces = do std::sort::merge_sort(store.namespaces) |x, y| {x.prefix <= y.prefix};
+	let mut namespaces = do vec::filter(namespaces) |n| {n.prefix != ~"_" && n.prefix.is_not_empty()};
+	if !namespaces.any(|n| {n.path == ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#"})
+	{
+		vec::push(&mut namespaces, Namespace {prefix: ~"rdf", path: ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#"});
+	}
+	
+	let mut generated = 0;
+	for store.each |triple|
+	{
+		if get_qname(namespaces, triple.predicate).is_none()
+		{
+			match split_iri(triple.predicate)
+			{
+				option::Some((path, _local)) =>
+				{
+					vec::push(&mut namespaces, Namespace {prefix: fmt!("ns%?", generated), path: path});
+					generated += 1;
+				}
+				option::None =>
+				{
+					return result::Err(fmt!("Predicate <%s> cannot be written as an XML qualified name.", triple.predicate));
+				}
+			}
+		}
+	};
+	result::Ok(namespaces)
+}
+
+priv fn count_blanks(store: &Store) -> HashMap<@~str, uint>
+{
+	let counts = HashMap();
+	for store.each |triple|
+	{
+		match *triple.object
+		{
+			BlankValue(ref name) =>
+			{
+				let count = option::get_default(counts.find(@copy *name), 0);
+				counts.insert(@copy *name, count + 1);
+			}
+			_ =>
+			{
+			}
+		}
+	};
+	counts
+}
+
+priv fn is_nested_blank(state: &RdfXmlWriter, subject: &str) -> bool
+{
+	subject.starts_with("_:") && state.counts.find(@subject.to_owned()) == option::Some(1)
+}
+
+// Containers typed as rdf:Bag, rdf:Seq, or rdf:Alt are written as typed node elements.
+priv fn write_node(store: &Store, state: &RdfXmlWriter, subject: &str, indent: uint, nested: bool)
+{
+	state.written.insert(@subject.to_owned(), true);
+	let entries = match find_entries(store, subject)
+	{
+		option::Some(entries) => sorted_entries(store, entries),
+		option::None => ~[],
+	};
+	
+	let kind = do entries.find |entry| {entry.predicate == ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#type" && is_container_type(entry.object)};
+	let element = match kind
+	{
+		option::Some(ref entry) => get_qname(state.namespaces, object_to_subject(entry.object)).get(),
+		option::None => ~"rdf:Description",
+	};
+	let entries = do entries.filter |entry| {option::Some(copy *entry) != kind};
+	
+	let id = if !subject.starts_with("_:")
+	{
+		fmt!(" rdf:about=\"%s\"", escape_xml(subject))
+	}
+	else if nested || !state.counts.contains_key(@subject.to_owned())
+	{
+		~""
+	}
+	else
+	{
+		fmt!(" rdf:nodeID=\"%s\"", node_id(subject))
+	};
+	
+	let tabs = str::from_chars(vec::from_elem(indent, '\t'));
+	if entries.is_empty()
+	{
+		state.writer.write_line(fmt!("%s<%s%s/>", tabs, element, id));
+		return;
+	}
+	
+	state.writer.write_line(fmt!("%s<%s%s>", tabs, element, id));
+	let members = do entries.filter |entry| {member_index(entry.predicate).is_some()};
+	let members = do std::sort::merge_sort(members) |x, y| {member_index(x.predicate).get() <= member_index(y.predicate).get()};
+	let use_li = do vec::alli(members) |i, entry| {member_index(entry.predicate) == option::Some(i + 1)};
+	for entries.each |entry|
+	{
+		if member_index(entry.predicate).is_none()
+		{
+			write_property(store, state, get_qname(state.namespaces, entry.predicate).get(), entry.object, indent + 1);
+		}
+	}
+	for members.each |entry|
+	{
+		let name = if use_li {~"rdf:li"} else {get_qname(state.namespaces, entry.predicate).get()};
+		write_property(store, state, name, entry.object, indent + 1);
+	}
+	state.writer.write_line(fmt!("%s</%s>", tabs, element));
+}
+
+priv fn write_property(store: &Store, state: &RdfXmlWriter, name: &str, object: @Object, indent: uint)
+{
+	let tabs = str::from_chars(vec::from_elem(indent, '\t'));
+	match *object
+	{
+		IriValue(ref iri) =>
+		{
+			state.writer.write_line(fmt!("%s<%s rdf:resource=\"%s\"/>", tabs, name, escape_xml(*iri)));
+		}
+		BlankValue(ref blank) =>
+		{
+			if is_nested_blank(state, *blank) && !state.written.contains_key(@copy *blank)
+			{
+				state.writer.write_line(fmt!("%s<%s>", tabs, name));
+				write_node(store, state, *blank, indent + 1, true);
+				state.writer.write_line(fmt!("%s</%s>", tabs, name));
+			}
+			else
+			{
+				state.writer.write_line(fmt!("%s<%s rdf:nodeID=\"%s\"/>", tabs, name, node_id(*blank)));
+			}
+		}
+		StringValue(ref value, ref lang) =>
+		{
+			if lang.is_empty()
+			{
+				state.writer.write_line(fmt!("%s<%s>%s</%s>", tabs, name, escape_xml(*value), name));
+			}
+			else
+			{
+				state.writer.write_line(fmt!("%s<%s xml:lang=\"%s\">%s</%s>", tabs, name, escape_xml(*lang), escape_xml(*value), name));
+			}
+		}
+		TypedValue(ref value, ref kind) if *kind == ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#XMLLiteral" =>
+		{
+			state.writer.write_line(fmt!("%s<%s rdf:parseType=\"Literal\">%s</%s>", tabs, name, *value, name));
+		}
+		_ =>
+		{
+			let (value, kind, _lang) = object_to_literal(object);
+			state.writer.write_line(fmt!("%s<%s rdf:datatype=\"%s\">%s</%s>", tabs, name, escape_xml(kind), escape_xml(value), name));
+		}
+	}
+}
+
+// Returns N for rdf:_N predicates.
+priv fn member_index(predicate: &str) -> option::Option<uint>
+{
+	if predicate.starts_with("http://www.w3.org/1999/02/22-rdf-syntax-ns#_")
+	{
+		uint::from_str(str::slice(predicate, 44, str::len(predicate)))
+	}
+	else
+	{
+		option::None
+	}
+}
+
+priv fn is_container_type(object: @Object) -> bool
+{
+	match *object
+	{
+		IriValue(ref iri) =>
+		{
+			*iri == ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#Bag" || *iri == ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#Seq" || *iri == ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#Alt"
+		}
+		_ =>
+		{
+			false
+		}
+	}
+}
+
+priv fn get_qname(namespaces: &[Namespace], iri: &str) -> option::Option<~str>
+{
+	for namespaces.each |namespace|
+	{
+		if iri.starts_with(namespace.path)
+		{
+			let local = str::slice(iri, str::len(namespace.path), str::len(iri));
+			if is_ncname(local)
+			{
+				return option::Some(fmt!("%s:%s", namespace.prefix, local));
+			}
+		}
+	}
+	option::None
+}
+
+// Splits an IRI into a namespace and the longest suffix which is a valid local name.
+priv fn split_iri(iri: &str) -> option::Option<(~str, ~str)>
+{
+	let chars = str::chars(iri);
+	let mut i = chars.len();
+	while i > 0 && is_ncname_char(chars[i - 1])
+	{
+		i -= 1;
+	}
+	while i < chars.len() && !(char::is_alphabetic(chars[i]) || chars[i] == '_')
+	{
+		i += 1;
+	}
+	
+	if i > 0 && i < chars.len()
+	{
+		option::Some((str::from_chars(vec::slice(chars, 0, i)), str::from_chars(vec::slice(chars, i, chars.len()))))
+	}
+	else
+	{
+		option::None
+	}
+}
+
+priv fn is_ncname(name: &str) -> bool
+{
+	if name.is_empty()
+	{
+		return false;
+	}
+	
+	let first = str::char_at(name, 0);
+	(char::is_alphabetic(first) || first == '_') && str::all(name, is_ncname_char)
+}
+
+priv pure fn is_ncname_char(ch: char) -> bool
+{
+	char::is_alphanumeric(ch) || ch == '_' || ch == '-' || ch == '.'
+}
+
+// rdf:nodeID values must be NCNames.
+priv fn node_id(name: &str) -> ~str
+{
+	let label = blank_to_turtle(name);
+	let label = str::slice(label, 2, str::len(label));
+	if is_ncname(label) {label} else {~"n" + label}
+}
@@ -0,0 +1,573 @@
+//! Serializes solutions using the SPARQL 1.1 query results formats (JSON, XML, CSV, and TSV).
+use io::WriterUtil;
+use std::json;
+use xml::*;
+
+/// Writes the selected bindings of a solution using the application/sparql-results+json format.
+///
+/// Unbound (and error) values are omitted from the rows as the format requires.
+pub fn write_solution_json(solution: &Solution, writer: io::Writer)
+{
+	let names = solution.bindings.slice(0, solution.num_selected);
+	let vars = do names.map |name| {json_string(*name)};
+	
+	writer.write_line("{");
+	writer.write_line(fmt!("\t\"head\": {\"vars\": [%s]},", str::connect(vars, ", ")));
+	writer.write_str("\t\"results\": {\"bindings\": [");
+	for solution.rows.eachi |i, row|
+	{
+		let mut fields = ~[];
+		for names.eachi |j, name|
+		{
+			match object_to_json_term(row[j])
+			{
+				option::Some(term) => vec::push(&mut fields, fmt!("%s: %s", json_string(*name), term)),
+				option::None => (),
+			}
+		}
+		writer.write_str(if i == 0 {"\n"} else {",\n"});
+		writer.write_str(fmt!("\t\t{%s}", str::connect(fields, ", ")));
+	}
+	writer.write_line(if solution.rows.is_empty() {"]}"} else {"\n\t]}"});
+	writer.write_line("}");
+}
+
+/// Returns the selected bindings of a solution using the application/sparql-results+json format.
+pub fn solution_to_json(solution: &Solution) -> ~str
+{
+	do io::with_str_writer |writer| {write_solution_json(solution, writer)}
+}
+
+/// Parses an application/sparql-results+json document.
+///
+/// Every variable in the head is selected and variables missing from a row are set to
+/// UnboundValue. Namespaces are not part of the format so the result has none.
+pub fn json_to_solution(text: &str) -> result::Result<Solution, ~str>
+{
+	match json::from_str(text)
+	{
+		result::Ok(json::Object(ref document)) =>
+		{
+			do get_json_vars(*document).chain |bindings|
+			{
+				do get_json_rows(*document, bindings).chain |rows|
+				{
+					result::Ok(Solution {namespaces: ~[], bindings: copy bindings, num_selected: bindings.len(), rows: rows})
+				}
+			}
+		}
+		result::Ok(_) =>
+		{
+			result::Err(~"SPARQL JSON results must be an object.")
+		}
+		result::Err(ref err) =>
+		{
+			result::Err(fmt!("%s on line %? col %?", *err.msg, err.line, err.col))
+		}
+	}
+}
+
+/// Writes the selected bindings of a solution using the application/sparql-results+xml format.
+pub fn write_solution_xml(solution: &Solution, writer: io::Writer)
+{
+	let names = solution.bindings.slice(0, solution.num_selected);
+	
+	writer.write_line("<?xml version=\"1.0\"?>");
+	writer.write_line("<sparql xmlns=\"http://www.w3.org/2005/sparql-results#\">");
+	writer.write_line("\t<head>");
+	for names.each |name|
+	{
+		writer.write_line(fmt!("\t\t<variable name=\"%s\"/>", escape_xml(*name)));
+	}
+	writer.write_line("\t</head>");
+	writer.write_line("\t<results>");
+	for solution.rows.each |row|
+	{
+		writer.write_line("\t\t<result>");
+		for names.eachi |i, name|
+		{
+			match object_to_xml_term(row[i])
+			{
+				option::Some(term) => writer.write_line(fmt!("\t\t\t<binding name=\"%s\">%s</binding>", escape_xml(*name), term)),
+				option::None => (),
+			}
+		}
+		writer.write_line("\t\t</result>");
+	}
+	writer.write_line("\t</results>");
+	writer.write_line("</sparql>");
+}
+
+/// Returns the selected bindings of a solution using the application/sparql-results+xml format.
+pub fn solution_to_xml(solution: &Solution) -> ~str
+{
+	do io::with_str_writer |writer| {write_solution_xml(solution, writer)}
+}
+
+/// Parses an application/sparql-results+xml document.
+///
+/// As with json_to_solution every variable is selected, missing values are set to
+/// UnboundValue, and the result has no namespaces.
+pub fn xml_to_solution(text: &str) -> result::Result<Solution, ~str>
+{
+	do parse_xml(text).chain |root|
+	{
+		if !is_results_element(root, "sparql")
+		{
+			result::Err(~"Expected a sparql element.")
+		}
+		else
+		{
+			do get_xml_vars(root).chain |bindings|
+			{
+				do get_xml_rows(root, bindings).chain |rows|
+				{
+					result::Ok(Solution {namespaces: ~[], bindings: copy bindings, num_selected: bindings.len(), rows: rows})
+				}
+			}
+		}
+	}
+}
+
+/// Writes the selected bindings of a solution using the text/csv format.
+///
+/// Values are written using their plain lexical forms (so types and language tags are
+/// lost), blank nodes are written as _:label, and unbound values are left empty.
+pub fn write_solution_csv(solution: &Solution, writer: io::Writer)
+{
+	let names = solution.bindings.slice(0, solution.num_selected);
+	writer.write_str(str::connect(names.map(|name| {escape_csv(*name)}), ",") + "\r\n");
+	for solution.rows.each |row|
+	{
+		let fields = do names.mapi |i, _name| {escape_csv(object_to_csv(row[i]))};
+		writer.write_str(str::connect(fields, ",") + "\r\n");
+	}
+}
+
+/// Returns the selected bindings of a solution using the text/csv format.
+pub fn solution_to_csv(solution: &Solution) -> ~str
+{
+	do io::with_str_writer |writer| {write_solution_csv(solution, writer)}
+}
+
+/// Writes the selected bindings of a solution using the text/tab-separated-values format.
+///
+/// Values are written using N-Triples syntax and unbound values are left empty.
+pub fn write_solution_tsv(solution: &Solution, writer: io::Writer)
+{
+	let names = solution.bindings.slice(0, solution.num_selected);
+	writer.write_line(str::connect(names.map(|name| {~"?" + *name}), "\t"));
+	for solution.rows.each |row|
+	{
+		let fields = do names.mapi |i, _name|
+		{
+			match *row[i]
+			{
+				UnboundValue | ErrorValue(*) => ~"",
+				_ => object_to_ntriples(row[i]),
+			}
+		};
+		writer.write_line(str::connect(fields, "\t"));
+	}
+}
+
+/// Returns the selected bindings of a solution using the text/tab-separated-values format.
+pub fn solution_to_tsv(solution: &Solution) -> ~str
+{
+	do io::with_str_writer |writer| {write_solution_tsv(solution, writer)}
+}
+
+// ---- Private Functions -----------------------------------------------------
+
+// Returns None for values which are not bound.
+priv fn object_to_json_term(object: @Object) -> option::Option<~str>
+{
+	match *object
+	{
+		UnboundValue | ErrorValue(*) =>
+		{
+			option::None
+		}
+		IriValue(ref iri) =>
+		{
+			option::Some(fmt!("{\"type\": \"uri\", \"value\": %s}", json_string(*iri)))
+		}
+		BlankValue(ref name) =>
+		{
+			let label = if name.starts_with("_:") {str::slice(*name, 2, str::len(*name))} else {copy *name};
+			option::Some(fmt!("{\"type\": \"bnode\", \"value\": %s}", json_string(label)))
+		}
+		StringValue(ref value, ref lang) =>
+		{
+			if lang.is_empty()
+			{
+				option::Some(fmt!("{\"type\": \"literal\", \"value\": %s}", json_string(*value)))
+			}
+			else
+			{
+				option::Some(fmt!("{\"type\": \"literal\", \"value\": %s, \"xml:lang\": %s}", json_string(*value), json_string(*lang)))
+			}
+		}
+		_ =>
+		{
+			let (value, kind, _lang) = object_to_literal(object);
+			option::Some(fmt!("{\"type\": \"literal\", \"value\": %s, \"datatype\": %s}", json_string(value), json_string(kind)))
+		}
+	}
+}
+
+priv fn get_json_vars(document: &json::Object) -> result::Result<~[~str], ~str>
+{
+	match document.find(&~"head")
+	{
+		option::Some(json::Object(ref head)) =>
+		{
+			match head.find(&~"vars")
+			{
+				option::Some(json::List(ref vars)) =>
+				{
+					let mut names = ~[];
+					for vars.each |var|
+					{
+						match *var
+						{
+							json::String(ref name) => vec::push(&mut names, copy *name),
+							_ => return result::Err(~"head.vars must contain strings."),
+						}
+					}
+					result::Ok(names)
+				}
+				_ =>
+				{
+					result::Err(~"head.vars must be an array.")
+				}
+			}
+		}
+		_ =>
+		{
+			result::Err(~"Expected a head object.")
+		}
+	}
+}
+
+priv fn get_json_rows(document: &json::Object, bindings: &[~str]) -> result::Result<~[SolutionRow], ~str>
+{
+	let items = match document.find(&~"results")
+	{
+		option::Some(json::Object(ref results)) =>
+		{
+			match results.find(&~"bindings")
+			{
+				option::Some(json::List(copy items)) => items,
+				_ => return result::Err(~"results.bindings must be an array."),
+			}
+		}
+		_ =>
+		{
+			return result::Err(~"Expected a results object.");
+		}
+	};
+	
+	let mut rows = ~[];
+	for items.each |item|
+	{
+		match *item
+		{
+			json::Object(ref map) =>
+			{
+				let mut row = vec::from_elem(bindings.len(), @UnboundValue);
+				for map.each_key |name|
+				{
+					match bindings.position_elem(name)
+					{
+						option::Some(i) =>
+						{
+							match json_term_to_object(&map.get(name))
+							{
+								result::Ok(object) => row[i] = object,
+								result::Err(copy err) => return result::Err(fmt!("%s (for %s)", err, *name)),
+							}
+						}
+						option::None =>
+						{
+							return result::Err(fmt!("%s is not one of the variables in the head.", *name));
+						}
+					}
+				}
+				vec::push(&mut rows, row);
+			}
+			_ =>
+			{
+				return result::Err(~"Each binding must be an object.");
+			}
+		}
+	}
+	result::Ok(rows)
+}
+
+priv fn json_term_to_object(term: &json::Json) -> result::Result<@Object, ~str>
+{
+	let map = match *term
+	{
+		json::Object(copy map) => map,
+		_ => return result::Err(~"Expected an RDF term object."),
+	};
+	
+	let kind = get_json_str(map, "type");
+	let value = match map.find(&~"value")
+	{
+		option::Some(json::String(copy value)) => value,
+		_ => return result::Err(~"Expected a string value."),
+	};
+	
+	match kind
+	{
+		~"uri" =>
+		{
+			result::Ok(@IriValue(value))
+		}
+		~"bnode" =>
+		{
+			result::Ok(@BlankValue(~"_:" + value))
+		}
+		~"literal" | ~"typed-literal" =>
+		{
+			let datatype = get_json_str(map, "datatype");
+			if datatype.is_not_empty()
+			{
+				result::Ok(@literal_to_object(value, datatype, ""))
+			}
+			else
+			{
+				result::Ok(@literal_to_object(value, "http://www.w3.org/2001/XMLSchema#string", get_json_str(map, "xml:lang")))
+			}
+		}
+		_ =>
+		{
+			result::Err(fmt!("Bad RDF term type '%s'", kind))
+		}
+	}
+}
+
+// Returns an empty string if the field is missing or not a string.
+priv fn get_json_str(map: &json::Object, name: &str) -> ~str
+{
+	match map.find(&name.to_owned())
+	{
+		option::Some(json::String(copy value)) => value,
+		_ => ~"",
+	}
+}
+
+// Returns None for values which are not bound.
+priv fn object_to_xml_term(object: @Object) -> option::Option<~str>
+{
+	match *object
+	{
+		UnboundValue | ErrorValue(*) =>
+		{
+			option::None
+		}
+		IriValue(ref iri) =>
+		{
+			option::Some(fmt!("<uri>%s</uri>", escape_xml(*iri)))
+		}
+		BlankValue(ref name) =>
+		{
+			let label = if name.starts_with("_:") {str::slice(*name, 2, str::len(*name))} else {copy *name};
+			option::Some(fmt!("<bnode>%s</bnode>", escape_xml(label)))
+		}
+		StringValue(ref value, ref lang) =>
+		{
+			if lang.is_empty()
+			{
+				option::Some(fmt!("<literal>%s</literal>", escape_xml(*value)))
+			}
+			else
+			{
+				option::Some(fmt!("<literal xml:lang=\"%s\">%s</literal>", escape_xml(*lang), escape_xml(*value)))
+			}
+		}
+		_ =>
+		{
+			let (value, kind, _lang) = object_to_literal(object);
+			option::Some(fmt!("<literal datatype=\"%s\">%s</literal>", escape_xml(kind), escape_xml(value)))
+		}
+	}
+}
+
+priv fn get_xml_vars(root: &XmlElement) -> result::Result<~[~str], ~str>
+{
+	match find_results_child(root, "head")
+	{
+		option::Some(head) =>
+		{
+			let mut names = ~[];
+			for results_children(head, "variable").each |variable|
+			{
+				match get_attribute(*variable, "name")
+				{
+					option::Some(name) => vec::push(&mut names, name),
+					option::None => return result::Err(~"variable elements must have a name."),
+				}
+			}
+			result::Ok(names)
+		}
+		option::None =>
+		{
+			result::Err(~"Expected a head element.")
+		}
+	}
+}
+
+priv fn get_xml_rows(root: &XmlElement, bindings: &[~str]) -> result::Result<~[SolutionRow], ~str>
+{
+	let results = match find_results_child(root, "results")
+	{
+		option::Some(results) => results,
+		option::None => return result::Err(~"Expected a results element."),
+	};
+	
+	let mut rows = ~[];
+	for results_children(results, "result").each |element|
+	{
+		let mut row = vec::from_elem(bindings.len(), @UnboundValue);
+		for results_children(*element, "binding").each |binding|
+		{
+			let name = option::get_default(get_attribute(*binding, "name"), ~"");
+			match bindings.position_elem(&name)
+			{
+				option::Some(i) =>
+				{
+					match xml_term_to_object(*binding)
+					{
+						result::Ok(object) => row[i] = object,
+						result::Err(copy err) => return result::Err(fmt!("%s (for %s)", err, name)),
+					}
+				}
+				option::None =>
+				{
+					return result::Err(fmt!("'%s' is not one of the variables in the head.", name));
+				}
+			}
+		}
+		vec::push(&mut rows, row);
+	}
+	result::Ok(rows)
+}
+
+priv fn xml_term_to_object(binding: &XmlElement) -> result::Result<@Object, ~str>
+{
+	let terms = do binding.children.filter_map |child|
+	{
+		match *child
+		{
+			XmlElementNode(e) => option::Some(e),
+			XmlTextNode(_) => option::None,
+		}
+	};
+	if terms.len() != 1
+	{
+		return result::Err(~"Expected a single uri, bnode, or literal element.");
+	}
+	
+	let term = terms[0];
+	let value = xml_text(term);
+	if is_results_element(term, "uri")
+	{
+		result::Ok(@IriValue(value))
+	}
+	else if is_results_element(term, "bnode")
+	{
+		result::Ok(@BlankValue(~"_:" + value))
+	}
+	else if is_results_element(term, "literal")
+	{
+		match get_attribute(term, "datatype")
+		{
+			option::Some(ref kind) =>
+			{
+				result::Ok(@literal_to_object(value, *kind, ""))
+			}
+			option::None =>
+			{
+				let lang = do term.attributes.find |a| {a.name.namespace == ~"http://www.w3.org/XML/1998/namespace" && a.name.local == ~"lang"};
+				let lang = match lang {option::Some(ref a) => copy a.value, option::None => ~""};
+				result::Ok(@literal_to_object(value, "http://www.w3.org/2001/XMLSchema#string", lang))
+			}
+		}
+	}
+	else
+	{
+		result::Err(fmt!("Bad RDF term element '%s'", term.qname))
+	}
+}
+
+priv fn is_results_element(element: &XmlElement, local: &str) -> bool
+{
+	element.name.namespace == ~"http://www.w3.org/2005/sparql-results#" && element.name.local == local.to_owned()
+}
+
+priv fn results_children(element: &XmlElement, local: &str) -> ~[@XmlElement]
+{
+	do element.children.filter_map |child|
+	{
+		match *child
+		{
+			XmlElementNode(e) if is_results_element(e, local) => option::Some(e),
+			_ => option::None,
+		}
+	}
+}
+
+priv fn find_results_child(element: &XmlElement, local: &str) -> option::Option<@XmlElement>
+{
+	let children = results_children(element, local);
+	if children.is_not_empty() {option::Some(children[0])} else {option::None}
+}
+
+// Returns the value of an unqualified attribute.
+priv fn get_attribute(element: &XmlElement, local: &str) -> option::Option<~str>
+{
+	for element.attributes.each |attr|
+	{
+		if attr.name.namespace.is_empty() && attr.name.local == local.to_owned()
+		{
+			return option::Some(copy attr.value);
+		}
+	}
+	option::None
+}
+
+priv fn object_to_csv(object: @Object) -> ~str
+{
+	match *object
+	{
+		UnboundValue | ErrorValue(*) =>
+		{
+			~""
+		}
+		BlankValue(ref name) =>
+		{
+			if name.starts_with("_:") {copy *name} else {~"_:" + *name}
+		}
+		_ =>
+		{
+			let (value, _kind, _lang) = object_to_literal(object);
+			value
+		}
+	}
+}
+
+// Fields with commas, quotes, or line breaks are quoted (with embedded quotes doubled).
+priv fn escape_csv(field: &str) -> ~str
+{
+	if str::any(field, |c| {c == ',' || c == '"' || c == '\r' || c == '\n'})
+	{
+		~"\"" + str::replace(field, "\"", "\"\"") + ~"\""
+	}
+	else
+	{
+		field.to_owned()
+	}
+}
@@ -0,0 +1,137 @@
+//! Sharing stores between tasks.
+//!
+//! Stores use managed boxes so they cannot be sent to other tasks. Instead the task which
+//! updates a store publishes frozen copies of it (using the snapshot format) to a
+//! SharedStore and reader tasks use a StoreReader to thaw the latest copy into a store of
+//! their own which can be queried with the usual Selector functions.
+use std::arc::{RWARC};
+
+/// An immutable copy of a store which can be sent to other tasks.
+pub struct FrozenStore
+{
+	pub version: uint,				// incremented each time a new store is published
+	pub set_semantics: bool,
+	pub bytes: ~[u8],				// written by save_snapshot
+}
+
+/// A frozen store which one task replaces and any number of tasks read.
+///
+/// Use clone to get a copy which can be sent to another task (the copies all refer to
+/// the same frozen store).
+pub struct SharedStore
+{
+	arc: RWARC<FrozenStore>,
+}
+
+/// Used by a reader task to query the latest store published to a SharedStore.
+///
+/// The published store is only thawed when it changes so most queries run against a
+/// local store.
+pub struct StoreReader
+{
+	shared: SharedStore,
+	extensions: HashMap<@~str, ExtensionFn>,
+	mut version: uint,
+	mut thawed: option::Option<@Store>,
+}
+
+/// Returns a frozen copy of the store with version 0.
+pub fn freeze_store(store: &Store) -> FrozenStore
+{
+	let bytes = do io::with_bytes_writer |writer| {save_snapshot(store, writer)};
+	FrozenStore {version: 0, set_semantics: store.set_semantics, bytes: bytes}
+}
+
+/// Creates a new store from a frozen copy.
+///
+/// Extension functions cannot be frozen so they have to be supplied here.
+pub fn thaw_store(frozen: &FrozenStore, extensions: &HashMap<@~str, ExtensionFn>) -> Store
+{
+	// The bytes were written by save_snapshot so they should always load.
+	let store = do io::with_bytes_reader(frozen.bytes) |reader| {load_snapshot(reader)}.get();
+	store.set_semantics = frozen.set_semantics;
+	for extensions.each |name, f|
+	{
+		store.extensions.insert(name, f);
+	};
+	store
+}
+
+/// Creates a SharedStore with a frozen copy of store.
+pub fn SharedStore(store: &Store) -> SharedStore
+{
+	SharedStore {arc: RWARC(freeze_store(store))}
+}
+
+pub impl SharedStore
+{
+	/// Returns a new reference to the shared store which can be sent to another task.
+	fn clone(&self) -> SharedStore
+	{
+		SharedStore {arc: self.arc.clone()}
+	}
+	
+	/// Replaces the frozen store with a copy of store.
+	///
+	/// This would normally be called by the writer task after each batch of changes (e.g.
+	/// after Store::commit). Readers continue to use the old copy until they call
+	/// StoreReader::store or StoreReader::query again.
+	fn publish(&self, store: &Store)
+	{
+		// Do the expensive part before taking the lock.
+		let mut frozen = freeze_store(store);
+		do self.arc.write
+		|current|
+		{
+			frozen.version = current.version + 1;
+			*current <-> frozen;
+		}
+	}
+	
+	/// Returns the version of the most recently published store.
+	fn version(&self) -> uint
+	{
+		do self.arc.read |frozen| {frozen.version}
+	}
+}
+
+/// Creates a reader which will use the extension functions for the stores it thaws.
+pub fn StoreReader(shared: SharedStore, extensions: &HashMap<@~str, ExtensionFn>) -> StoreReader
+{
+	StoreReader {shared: move shared, extensions: copy *extensions, version: 0, thawed: option::None}
+}
+
+pub impl StoreReader
+{
+	/// Returns a store with the contents of the most recently published store.
+	///
+	/// The returned store should be treated as read-only: changes to it are not seen
+	/// by other tasks and are discarded when a new store is published.
+	fn store(&self) -> @Store
+	{
+		do self.shared.arc.read
+		|frozen|
+		{
+			match self.thawed
+			{
+				option::Some(store) if self.version == frozen.version =>
+				{
+					store
+				}
+				_ =>
+				{
+					let store = @thaw_store(frozen, &self.extensions);
+					self.thawed = option::Some(store);
+					self.version = frozen.version;
+					store
+				}
+			}
+		}
+	}
+	
+	/// Runs the selector against the most recently published store.
+	fn query(&self, selector: Selector) -> result::Result<Solution, ~str>
+	{
+		selector(self.store())
+	}
+}
@@ -0,0 +1,412 @@
+//! Compact binary snapshots of stores (see Store::save and Store::load).
+//!
+//! The format is a magic number and version followed by a dictionary of every string
+//! in the store, next_blank, the namespace table, and then each subject with its
+//! entries. Strings are written once and referenced by their dictionary index and
+//! objects are prefixed with a one byte tag identifying their variant. All integers
+//! are big endian.
+use io::{ReaderUtil, WriterUtil};
+use std::time::{Tm};
+
+const snapshot_version: u32 = 1;
+
+// Lengths and counts come from the snapshot so they can't be trusted. Bytes are read in
+// chunks no larger than this so that a corrupt length can't make us allocate a huge buffer.
+const max_chunk_size: uint = 65536;
+
+// Object tags.
+const bool_tag: u8 = 0;
+const int_tag: u8 = 1;
+const float_tag: u8 = 2;
+const datetime_tag: u8 = 3;
+const string_tag: u8 = 4;
+const typed_tag: u8 = 5;
+const iri_tag: u8 = 6;
+const blank_tag: u8 = 7;
+const unbound_tag: u8 = 8;
+const invalid_tag: u8 = 9;
+const error_tag: u8 = 10;
+
+// Maps strings to their index in the dictionary.
+struct Dictionary
+{
+	indexes: HashMap<@~str, u32>,
+	strings: DVec<~str>,
+}
+
+// Decoding stops at the first error (subsequent reads return nothing and numbers are zero).
+struct Decoder
+{
+	reader: io::Reader,
+	mut err: ~str,
+}
+
+/// Writes the store using the snapshot format.
+///
+/// Extension functions cannot be saved so load will only add the default extensions.
+pub fn save_snapshot(store: &Store, writer: io::Writer)
+{
+	let dictionary = Dictionary {indexes: HashMap(), strings: DVec()};
+	for store.namespaces.each |namespace|
+	{
+		intern(&dictionary, namespace.prefix);
+		intern(&dictionary, namespace.path);
+	}
+	for store.each |triple|
+	{
+		intern(&dictionary, triple.subject);
+		intern(&dictionary, triple.predicate);
+		intern_object(&dictionary, triple.object);
+	}
+	
+	writer.write_str("RRDF");
+	writer.write_be_u32(snapshot_version);
+	
+	writer.write_be_u32(dictionary.strings.len() as u32);
+	for dictionary.strings.each |s|
+	{
+		let bytes = str::to_bytes(*s);
+		writer.write_be_u32(bytes.len() as u32);
+		writer.write(bytes);
+	}
+	
+	writer.write_be_i64(store.next_blank as i64);
+	
+	writer.write_be_u32(store.namespaces.len() as u32);
+	for store.namespaces.each |namespace|
+	{
+		writer.write_be_u32(intern(&dictionary, namespace.prefix));
+		writer.write_be_u32(intern(&dictionary, namespace.path));
+	}
+	
+	writer.write_be_u32(store.subjects.size() as u32);
+	for store.subjects.each |subject, entries|
+	{
+		writer.write_be_u32(intern(&dictionary, store.terms.get_name(subject)));
+		writer.write_be_u32(entries.len() as u32);
+		for entries.each |entry|
+		{
+			writer.write_be_u32(intern(&dictionary, store.terms.get_name(entry.predicate)));
+			write_object(&dictionary, writer, store.terms.get(entry.object));
+		}
+	};
+}
+
+/// Creates a new store using data written by save_snapshot.
+///
+/// Returns an error if the data is not a snapshot, has an unsupported version, or is
+/// truncated or otherwise malformed.
+pub fn load_snapshot(reader: io::Reader) -> result::Result<Store, ~str>
+{
+	let decoder = Decoder {reader: reader, err: ~""};
+	if read_bytes(&decoder, 4) != str::to_bytes("RRDF")
+	{
+		return result::Err(~"Not a store snapshot.");
+	}
+	let version = read_u32(&decoder);
+	if version != snapshot_version
+	{
+		return result::Err(fmt!("Snapshot version %? is not supported (expected %?).", version, snapshot_version));
+	}
+	
+	let mut strings = ~[];
+	for uint::range(0, read_u32(&decoder) as uint) |_i|
+	{
+		let len = read_u32(&decoder) as uint;
+		let bytes = read_bytes(&decoder, len);
+		if decoder.err.is_empty() && !str::is_utf8(bytes)
+		{
+			decoder.err = ~"Snapshot string is not UTF-8.";
+		}
+		if decoder.err.is_not_empty()
+		{
+			break;
+		}
+		vec::push(&mut strings, str::from_bytes(bytes));
+	}
+	
+	let store = Store(~[], &HashMap());
+	store.next_blank = read_i64(&decoder) as int;
+	
+	let mut namespaces = ~[];
+	for uint::range(0, read_u32(&decoder) as uint) |_i|
+	{
+		let prefix = read_string(&decoder, strings);
+		let path = read_string(&decoder, strings);
+		if decoder.err.is_not_empty()
+		{
+			break;
+		}
+		vec::push(&mut namespaces, Namespace {prefix: prefix, path: path});
+	}
+	store.namespaces = namespaces;
+	
+	for uint::range(0, read_u32(&decoder) as uint) |_i|
+	{
+		let subject = read_string(&decoder, strings);
+		let count = read_u32(&decoder) as uint;
+		if decoder.err.is_not_empty()
+		{
+			break;
+		}
+		
+		let subject_id = store.terms.intern_name(subject);
+		let entries = @DVec();
+		for uint::range(0, count) |_j|
+		{
+			let predicate = read_string(&decoder, strings);
+			let object = read_object(&decoder, strings);
+			if decoder.err.is_not_empty()
+			{
+				break;
+			}
+			let entry = {predicate: store.terms.intern_name(predicate), object: store.terms.intern(object)};
+			index_entry(&store, subject_id, &entry);
+			entries.push(entry);
+		}
+		store.subjects.insert(subject_id, entries);
+	}
+	
+	if decoder.err.is_empty()
+	{
+		result::Ok(store)
+	}
+	else
+	{
+		result::Err(copy decoder.err)
+	}
+}
+
+// ---- Private Functions -----------------------------------------------------
+
+priv fn intern(dictionary: &Dictionary, s: &str) -> u32
+{
+	match dictionary.indexes.find(@s.to_owned())
+	{
+		option::Some(index) =>
+		{
+			index
+		}
+		option::None =>
+		{
+			let index = dictionary.strings.len() as u32;
+			dictionary.strings.push(s.to_owned());
+			dictionary.indexes.insert(@s.to_owned(), index);
+			index
+		}
+	}
+}
+
+priv fn intern_object(dictionary: &Dictionary, object: &Object)
+{
+	match *object
+	{
+		DateTimeValue(ref value) =>
+		{
+			intern(dictionary, value.tm_zone);
+		}
+		StringValue(ref value, ref lang) =>
+		{
+			intern(dictionary, *value);
+			intern(dictionary, *lang);
+		}
+		TypedValue(ref value, ref kind) | InvalidValue(ref value, ref kind) =>
+		{
+			intern(dictionary, *value);
+			intern(dictionary, *kind);
+		}
+		IriValue(ref value) | BlankValue(ref value) | ErrorValue(ref value) =>
+		{
+			intern(dictionary, *value);
+		}
+		BoolValue(*) | IntValue(*) | FloatValue(*) | UnboundValue =>
+		{
+		}
+	}
+}
+
+priv fn write_object(dictionary: &Dictionary, writer: io::Writer, object: &Object)
+{
+	match *object
+	{
+		BoolValue(value) =>
+		{
+			writer.write_u8(bool_tag);
+			writer.write_u8(if value {1} else {0});
+		}
+		IntValue(value) =>
+		{
+			writer.write_u8(int_tag);
+			writer.write_be_i64(value);
+		}
+		FloatValue(value) =>
+		{
+			writer.write_u8(float_tag);
+			writer.write_be_u64(unsafe {cast::transmute(value)});
+		}
+		DateTimeValue(ref value) =>
+		{
+			writer.write_u8(datetime_tag);
+			for [value.tm_sec, value.tm_min, value.tm_hour, value.tm_mday, value.tm_mon, value.tm_year,
+				value.tm_wday, value.tm_yday, value.tm_isdst, value.tm_gmtoff, value.tm_nsec].each |field|
+			{
+				writer.write_be_i32(*field);
+			}
+			writer.write_be_u32(intern(dictionary, value.tm_zone));
+		}
+		StringValue(ref value, ref lang) =>
+		{
+			writer.write_u8(string_tag);
+			writer.write_be_u32(intern(dictionary, *value));
+			writer.write_be_u32(intern(dictionary, *lang));
+		}
+		TypedValue(ref value, ref kind) =>
+		{
+			writer.write_u8(typed_tag);
+			writer.write_be_u32(intern(dictionary, *value));
+			writer.write_be_u32(intern(dictionary, *kind));
+		}
+		IriValue(ref value) =>
+		{
+			writer.write_u8(iri_tag);
+			writer.write_be_u32(intern(dictionary, *value));
+		}
+		BlankValue(ref value) =>
+		{
+			writer.write_u8(blank_tag);
+			writer.write_be_u32(intern(dictionary, *value));
+		}
+		UnboundValue =>
+		{
+			writer.write_u8(unbound_tag);
+		}
+		InvalidValue(ref value, ref kind) =>
+		{
+			writer.write_u8(invalid_tag);
+			writer.write_be_u32(intern(dictionary, *value));
+			writer.write_be_u32(intern(dictionary, *kind));
+		}
+		ErrorValue(ref value) =>
+		{
+			writer.write_u8(error_tag);
+			writer.write_be_u32(intern(dictionary, *value));
+		}
+	}
+}
+
+priv fn read_object(decoder: &Decoder, strings: &[~str]) -> @Object
+{
+	let tag = read_u8(decoder);
+	if tag == bool_tag
+	{
+		@BoolValue(read_u8(decoder) != 0)
+	}
+	else if tag == int_tag
+	{
+		@IntValue(read_i64(decoder))
+	}
+	else if tag == float_tag
+	{
+		let bits = read_i64(decoder) as u64;
+		@FloatValue(unsafe {cast::transmute(bits)})
+	}
+	else if tag == datetime_tag
+	{
+		let fields = do vec::from_fn(11) |_i| {read_u32(decoder) as i32};
+		let zone = read_string(decoder, strings);
+		@DateTimeValue(Tm {tm_sec: fields[0], tm_min: fields[1], tm_hour: fields[2], tm_mday: fields[3], tm_mon: fields[4], tm_year: fields[5],
+			tm_wday: fields[6], tm_yday: fields[7], tm_isdst: fields[8], tm_gmtoff: fields[9], tm_zone: zone, tm_nsec: fields[10]})
+	}
+	else if tag == string_tag
+	{
+		let value = read_string(decoder, strings);
+		@StringValue(value, read_string(decoder, strings))
+	}
+	else if tag == typed_tag
+	{
+		let value = read_string(decoder, strings);
+		@TypedValue(value, read_string(decoder, strings))
+	}
+	else if tag == iri_tag
+	{
+		@IriValue(read_string(decoder, strings))
+	}
+	else if tag == blank_tag
+	{
+		@BlankValue(read_string(decoder, strings))
+	}
+	else if tag == unbound_tag
+	{
+		@UnboundValue
+	}
+	else if tag == invalid_tag
+	{
+		let value = read_string(decoder, strings);
+		@InvalidValue(value, read_string(decoder, strings))
+	}
+	else if tag == error_tag
+	{
+		@ErrorValue(read_string(decoder, strings))
+	}
+	else
+	{
+		if decoder.err.is_empty()
+		{
+			decoder.err = fmt!("Bad object tag %?.", tag);
+		}
+		@UnboundValue
+	}
+}
+
+priv fn read_string(decoder: &Decoder, strings: &[~str]) -> ~str
+{
+	let index = read_u32(decoder) as uint;
+	if index < strings.len()
+	{
+		copy strings[index]
+	}
+	else
+	{
+		if decoder.err.is_empty()
+		{
+			decoder.err = fmt!("Bad string index %?.", index);
+		}
+		~""
+	}
+}
+
+priv fn read_u8(decoder: &Decoder) -> u8
+{
+	let bytes = read_bytes(decoder, 1);
+	if bytes.is_empty() {0} else {bytes[0]}
+}
+
+priv fn read_u32(decoder: &Decoder) -> u32
+{
+	let bytes = read_bytes(decoder, 4);
+	do vec::foldl(0u32, bytes) |sum, b| {(sum << 8) | (*b as u32)}
+}
+
+priv fn read_i64(decoder: &Decoder) -> i64
+{
+	let bytes = read_bytes(decoder, 8);
+	let value = do vec::foldl(0u64, bytes) |sum, b| {(sum << 8) | (*b as u64)};
+	value as i64
+}
+
+// Returns an empty vector if the decoder has (or hits) an error.
+priv fn read_bytes(decoder: &Decoder, count: uint) -> ~[u8]
+{
+	let mut bytes = ~[];
+	while decoder.err.is_empty() && bytes.len() < count
+	{
+		let chunk = decoder.reader.read_bytes(uint::min(count - bytes.len(), max_chunk_size));
+		if chunk.is_empty()
+		{
+			decoder.err = ~"Snapshot is truncated.";
+			return ~[];
+		}
+		vec::push_all(&mut bytes, chunk);
+	}
+	bytes
+}
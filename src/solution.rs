@@ -114,3 +114,14 @@ pub impl  &Solution : ToStr
 		result
 	}
 }
+
+pub impl Solution
+{
+	/// Returns true if the solutions have the same rows ignoring row order and blank node labels.
+	/// 
+	/// See solutions_are_isomorphic for details.
+	fn is_isomorphic(&self, other: &Solution) -> bool
+	{
+		solutions_are_isomorphic(self, other)
+	}
+}
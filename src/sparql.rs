@@ -128,6 +128,10 @@ priv fn expand(namespaces: &[Namespace], algebra: &Algebra) -> Algebra
 		{
 			Filter(expand_expr(namespaces, expr))
 		}
+		Graph(ref name, term) =>
+		{
+			Graph(expand_pattern(namespaces, name), @expand(namespaces, term))
+		}
 	}
 }
 
@@ -164,7 +168,7 @@ priv pure fn is_langtag_suffix(ch: char) -> bool
 	return is_langtag_prefix(ch) || char::is_digit(ch);
 }
 
-priv fn langtag() -> Parser<@~str> 
+pub fn langtag() -> Parser<@~str>
 {
 	let at = "@".lit();												// '@'
 	let prefix = match1(is_langtag_prefix);						// [a-zA-Z]+
@@ -176,10 +180,65 @@ priv fn langtag() -> Parser<@~str>
 		|_l, p, s| {result::Ok(@(p + str::connect(*s, ~"")))}
 }
 
-// [150] ECHAR ::= '\' [tbnrf"']
+// [150] ECHAR ::= '\' [tbnrf\"']
 priv fn is_escape_char(ch: char) -> bool
 {
-	option::is_some(&str::find_char("tbnrf\"'", ch))	// input ends with EOT so we don't need a range check here
+	option::is_some(&str::find_char("tbnrf\\\"'", ch))	// input ends with EOT so we don't need a range check here
+}
+
+// UCHAR ::= '\u' HEX HEX HEX HEX | '\U' HEX HEX HEX HEX HEX HEX HEX HEX
+//
+// Returns the number of characters in the escape at index or zero if there isn't one.
+priv fn uchar_len(chars: @[char], index: uint) -> uint
+{
+	let count = if chars[index] != '\\' {0u} else if chars[index + 1] == 'u' {4u} else if chars[index + 1] == 'U' {8u} else {0u};
+	if count == 0
+	{
+		return 0;
+	}
+	
+	for uint::range(index + 2, index + 2 + count) |i|
+	{
+		if !is_hex(chars[i])			// EOT is not a hex digit so we won't run off the end
+		{
+			return 0;
+		}
+	}
+	count + 2
+}
+
+// ECHAR | UCHAR
+priv fn escape_len(chars: @[char], index: uint) -> uint
+{
+	if chars[index] == '\\' && is_escape_char(chars[index + 1])
+	{
+		2
+	}
+	else
+	{
+		uchar_len(chars, index)
+	}
+}
+
+// ([^<>"{}|^`\]-[#x00-#x20] | UCHAR)*
+priv fn iri_chars(chars: @[char], index: uint) -> uint
+{
+	let mut i = index;
+	loop
+	{
+		if chars[i] != EOT && iri_char(chars[i])
+		{
+			i += 1;
+		}
+		else if uchar_len(chars, i) > 0
+		{
+			i += uchar_len(chars, i);
+		}
+		else
+		{
+			return i - index;
+		}
+	}
 }
 
 // [^<>"{}|^`\]-[#x00-#x20]
@@ -195,7 +254,7 @@ priv fn iri_char(ch: char) -> bool
 	}
 }
 
-// [^x\\\n\r]) | ECHAR	where x is ' or "
+// [^x\\\n\r]) | ECHAR | UCHAR	where x is ' or "
 priv fn short_char(x: char, chars: @[char], index: uint) -> uint
 {
 	let mut i = index;
@@ -210,9 +269,9 @@ priv fn short_char(x: char, chars: @[char], index: uint) -> uint
 		{
 			i += 1;
 		}
-		else if ch == '\\' && is_escape_char(chars[i + 1u])
+		else if escape_len(chars, i) > 0
 		{
-			i += 2;
+			i += escape_len(chars, i);
 		}
 		else
 		{
@@ -221,7 +280,7 @@ priv fn short_char(x: char, chars: @[char], index: uint) -> uint
 	}
 }
 
-// ( "x" | "xx" )? ( [^x\] | ECHAR )	where x is ' or "
+// ( "x" | "xx" )? ( [^x\] | ECHAR | UCHAR )	where x is ' or "
 priv fn long_char(x: char, chars: @[char], index: uint) -> uint
 {
 	let mut i = index;
@@ -241,9 +300,9 @@ priv fn long_char(x: char, chars: @[char], index: uint) -> uint
 		{
 			i += 1;
 		}
-		else if ch == '\\' && is_escape_char(chars[i + 1])
+		else if escape_len(chars, i) > 0
 		{
-			i += 2;
+			i += escape_len(chars, i);
 		}
 		else
 		{
@@ -449,7 +508,7 @@ priv fn pn_chars_or_dot_or_plx(chars: @[char], index: uint) -> uint
 	}
 }
 
-priv fn ws<T: Copy Durable>(parser: Parser<T>) -> Parser<T>
+pub fn ws<T: Copy Durable>(parser: Parser<T>) -> Parser<T>
 {
 	|input: State|
 	{
@@ -497,7 +556,7 @@ priv fn ws<T: Copy Durable>(parser: Parser<T>) -> Parser<T>
 	}
 }
 
-trait MyParserTrait<T: Copy Durable>
+pub trait MyParserTrait<T: Copy Durable>
 {
 	fn ws() -> Parser<T>;
 }
@@ -698,10 +757,12 @@ priv fn built_in_call(Expression: Parser<expression::Expr>, Var: Parser<@~str>)
 	]).err(~"built-in call")
 }
 
-// http://www.w3.org/TR/sparql11-query/#grammar
-priv fn make_parser() -> Parser<Selector>
+// ---- Terminals -----------------------------------------------------------------------------
+// These are also used by the turtle parser which shares its names, IRIs, and literals with SPARQL.
+
+// [159] PN_LOCAL ::= (PN_CHARS_U | [0-9] | PLX)  ((PN_CHARS | '.' | PLX)* (PN_CHARS | PLX))? 		note that w3c had an error here (a stray > character at the end of the production)
+pub fn pn_local() -> Parser<@~str>
 {
-	// [159] PN_LOCAL ::= (PN_CHARS_U | [0-9] | PLX)  ((PN_CHARS | '.' | PLX)* (PN_CHARS | PLX))? 		note that w3c had an error here (a stray > character at the end of the production)
 	let pn_local_prefix = or_v(@~[
 		scan(pn_chars_u),
 		"0123456789".anyc().thene(|c| {ret(@str::from_char(c))}),
@@ -709,8 +770,12 @@ priv fn make_parser() -> Parser<Selector>
 	]);
 	let pn_local_suffix = do seq2(scan(pn_chars_or_dot_or_plx), scan(pn_chars).or(scan(plx)))
 		|l, r| {result::Ok(@(*l + *r))};
-	let PN_LOCAL = seq2(pn_local_prefix, optional_str(pn_local_suffix), |l, r| {result::Ok(@(*l + *r))});
-	
+	seq2(pn_local_prefix, optional_str(pn_local_suffix), |l, r| {result::Ok(@(*l + *r))})
+}
+
+// [130] PNAME_NS	::= PN_PREFIX? ':'
+pub fn pname_ns() -> Parser<@~str>
+{
 	// [158] PN_PREFIX	::= PN_CHARS_BASE ((PN_CHARS | '.')* PN_CHARS)?
 	let pname_ns_suffix = do seq2(scan(pn_chars_or_dot), scan(pn_chars))
 		|l, r| {result::Ok(@(*l + *r))};
@@ -718,12 +783,32 @@ priv fn make_parser() -> Parser<Selector>
 	let PN_PREFIX = seq2(scan(pn_chars_base), optional_str(pname_ns_suffix),
 		|l, r| {result::Ok(@(*l + *r))});
 	
-	// [130] PNAME_NS	::= PN_PREFIX? ':'
-	let PNAME_NS = seq2(optional_str(PN_PREFIX), ":".lit(), |l, r| {result::Ok(@(*l + *r))});
-	
+	seq2(optional_str(PN_PREFIX), ":".lit(), |l, r| {result::Ok(@(*l + *r))})
+}
+
+// [127] PrefixedName ::= PNAME_LN | PNAME_NS
+pub fn prefixed_name() -> Parser<@~str>
+{
 	// [131] PNAME_LN	::= PNAME_NS PN_LOCAL
-	let PNAME_LN = seq2(PNAME_NS, PN_LOCAL, |l, r| {result::Ok(@(*l + *r))});
+	let PNAME_LN = seq2(pname_ns(), pn_local(), |l, r| {result::Ok(@(*l + *r))});
 	
+	(PNAME_LN.or(pname_ns())).note(~"prefixedname").ws()
+}
+
+// [129] IRI_REF	 ::= '<' ([^<>"{}|^`\]-[#x00-#x20] | UCHAR)* '>'
+//
+// Unlike string_text the UCHAR escapes are expanded.
+pub fn iri_ref() -> Parser<@~str>
+{
+	let IRI_REF = seq3_ret1("<".lit(), optional_str(scan(iri_chars)), ">".lit().ws());
+	IRI_REF.thene(|v| {ret(@unescape_string(*v))}).note(~"IRI_REF")
+}
+
+// [125] String ::= STRING_LITERAL1 | STRING_LITERAL2 | STRING_LITERAL_LONG1 | STRING_LITERAL_LONG2
+//
+// Note that escape sequences are not expanded.
+pub fn string_text() -> Parser<@~str>
+{
 	// [149] STRING_LITERAL_LONG2 ::= '"""' (('"' | '""')? ([^"\] | ECHAR))* '"""'
 	let STRING_LITERAL_LONG2 = seq3_ret1("\"\"\"".lit(), scan({|x, y| long_char('"', x, y)}), "\"\"\"".lit().ws());
 	
@@ -736,8 +821,19 @@ priv fn make_parser() -> Parser<Selector>
 	// [146] STRING_LITERAL1 ::= "'" (([^'\\\n\r]) | ECHAR)* "'"
 	let STRING_LITERAL1 = seq3_ret1("'".lit(), scan({|x, y| short_char('\'', x, y)}), "'".lit().ws());
 	
-	// [136] INTEGER ::= [0-9]+
-	let INTEGER = match1(is_digit).thene(|v| {ret(int_literal(*v))}).ws();
+	or_v(@~[STRING_LITERAL_LONG1, STRING_LITERAL_LONG2, STRING_LITERAL1, STRING_LITERAL2]).note(~"string")
+}
+
+// [136] INTEGER ::= [0-9]+
+pub fn integer() -> Parser<Object>
+{
+	match1(is_digit).thene(|v| {ret(int_literal(*v))}).ws()
+}
+
+// [120] NumericLiteral	::= NumericLiteralUnsigned | NumericLiteralPositive | NumericLiteralNegative
+pub fn numeric_literal() -> Parser<Object>
+{
+	let INTEGER = integer();
 	
 	// [142] INTEGER_NEGATIVE ::= '-' INTEGER
 	let INTEGER_NEGATIVE = seq2_ret_str("-".lit(), match1(is_digit)).thene(|v| {ret(int_literal(*v))}).ws();
@@ -774,35 +870,47 @@ priv fn make_parser() -> Parser<Selector>
 	// [141] DOUBLE_POSITIVE ::= '+' DOUBLE
 	let DOUBLE_POSITIVE = seq2_ret1("+".lit(), double_root).thene(|v| {ret(float_literal(*v))});
 	
+	// [121] NumericLiteralUnsigned ::= INTEGER | DECIMAL | DOUBLE
+	let NumericLiteralUnsigned = or_v(@~[DOUBLE, DECIMAL, INTEGER]).note(~"number");
+	
+	// [122] NumericLiteralPositive ::= INTEGER_POSITIVE |	DECIMAL_POSITIVE | DOUBLE_POSITIVE
+	let NumericLiteralPositive = or_v(@~[DOUBLE_POSITIVE, DECIMAL_POSITIVE, INTEGER_POSITIVE]);
+	
+	// [123] NumericLiteralNegative ::= INTEGER_NEGATIVE |	DECIMAL_NEGATIVE |	DOUBLE_NEGATIVE
+	let NumericLiteralNegative = or_v(@~[DOUBLE_NEGATIVE, DECIMAL_NEGATIVE, INTEGER_NEGATIVE]);
+	
+	or_v(@~[NumericLiteralPositive, NumericLiteralNegative, NumericLiteralUnsigned])
+}
+
+// http://www.w3.org/TR/sparql11-query/#grammar
+priv fn make_parser() -> Parser<CompiledQuery>
+{
+	// [130] PNAME_NS	::= PN_PREFIX? ':'
+	let PNAME_NS = pname_ns();
+	
+	// [136] INTEGER ::= [0-9]+
+	let INTEGER = integer();
+	
 	// [135] LANGTAG ::= '@' [a-zA-Z]+ ('-' [a-zA-Z0-9]+)*
 	let LANGTAG = langtag();
 	
 	// [129] IRI_REF	 ::= '<' ([^<>"{}|^`\]-[#x00-#x20])* '>'
-	let IRI_REF = seq3_ret1("<".lit(), match0(iri_char), ">".lit().ws()).note(~"IRI_REF");
+	let IRI_REF = iri_ref();
 	
 	// [127] PrefixedName ::= PNAME_LN | PNAME_NS
-	let PrefixedName = (PNAME_LN.or(PNAME_NS)).note(~"prefixedname").ws();
+	let PrefixedName = prefixed_name();
 	
 	// [126] IRIref ::= IRI_REF | PrefixedName
 	let IRIref = IRI_REF.or(PrefixedName);
 	
 	// [125] String ::= STRING_LITERAL1 | STRING_LITERAL2 | STRING_LITERAL_LONG1 | STRING_LITERAL_LONG2
-	let String = or_v(@~[STRING_LITERAL_LONG1, STRING_LITERAL_LONG2, STRING_LITERAL1, STRING_LITERAL2]).note(~"string");
+	let String = string_text();
 	
 	// [124] BooleanLiteral	::= 'true' | 'false'
 	let BooleanLiteral = ("true".lit()).or("false".lit()).thene({|+v: @~str| ret(bool_literal(*v))}).ws().note(~"boolean");
 	
-	// [121] NumericLiteralUnsigned ::= INTEGER | DECIMAL | DOUBLE
-	let NumericLiteralUnsigned = or_v(@~[DOUBLE, DECIMAL, INTEGER]).note(~"number");
-	
-	// [122] NumericLiteralPositive ::= INTEGER_POSITIVE |	DECIMAL_POSITIVE | DOUBLE_POSITIVE
-	let NumericLiteralPositive = or_v(@~[DOUBLE_POSITIVE, DECIMAL_POSITIVE, INTEGER_POSITIVE]);
-
-	// [123] NumericLiteralNegative ::= INTEGER_NEGATIVE |	DECIMAL_NEGATIVE |	DOUBLE_NEGATIVE
-	let NumericLiteralNegative = or_v(@~[DOUBLE_NEGATIVE, DECIMAL_NEGATIVE, INTEGER_NEGATIVE]);
-	
 	// [120] NumericLiteral	::= NumericLiteralUnsigned | NumericLiteralPositive | NumericLiteralNegative
-	let NumericLiteral = or_v(@~[NumericLiteralPositive, NumericLiteralNegative, NumericLiteralUnsigned]);
+	let NumericLiteral = numeric_literal();
 	
 	// [119] RDFLiteral ::= String (LANGTAG | ('^^' IRIref))?
 	let RDFLiteral1 = String.thene({|+v: @~str| ret(string_literal(*v, ""))}); 
@@ -983,9 +1091,16 @@ priv fn make_parser() -> Parser<Selector>
 	let OptionalGraphPattern = do seq2("OPTIONAL".liti().ws(), GroupGraphPattern_ref)
 		|_o, a| {result::Ok(Optional(@a))};
 	
+	// [97] VarOrIRIref ::= Var | IRIref
+	let VarOrIRIref = (Var.thene(|v| {ret(Variable((copy *v)))})).or(IRIref.thene({|+v: @~str| ret(iri_literal(*v))}));
+	
+	// [60] GraphGraphPattern ::= 'GRAPH' VarOrIRIref GroupGraphPattern
+	let GraphGraphPattern = do seq3("GRAPH".liti().ws(), VarOrIRIref, GroupGraphPattern_ref)
+		|_g, n, a| {result::Ok(Graph(n, @a))};
+	
 	// [57] GraphPatternNotTriples ::= GroupOrUnionGraphPattern | OptionalGraphPattern | MinusGraphPattern | 
 	//                                                GraphGraphPattern | ServiceGraphPattern | Filter | Bind
-	let GraphPatternNotTriples = or_v(@~[OptionalGraphPattern, filter, bind]).note(~"GraphPatternNotTriples");
+	let GraphPatternNotTriples = or_v(@~[OptionalGraphPattern, GraphGraphPattern, filter, bind]).note(~"GraphPatternNotTriples");
 	
 	// [56] TriplesBlock ::= TriplesSameSubjectPath ('.' TriplesBlock?)?
 	let TriplesBlock = seq2(TriplesSameSubjectPath.list(".".lit().ws()), ".".lit().ws().optional(),
@@ -1074,9 +1189,19 @@ priv fn make_parser() -> Parser<Selector>
 	let SelectClause = seq3("SELECT".liti().ws(), select_mid, select_suffix,
 		|_a, b, c| {result::Ok((option::is_some(&b), copy *c))}).note(~"SelectClause");
 		
+	// [16] SourceSelector ::= IRIref
+	// [15] NamedGraphClause ::= 'NAMED' SourceSelector
+	let NamedGraphClause = do seq2_ret1("NAMED".liti().ws(), IRIref).thene |v| {ret((true, v))};
+	
+	// [14] DefaultGraphClause ::= SourceSelector
+	let DefaultGraphClause = do IRIref.thene |v| {ret((false, v))};
+	
+	// [13] DatasetClause ::= 'FROM' (DefaultGraphClause | NamedGraphClause)
+	let DatasetClause = seq2_ret1("FROM".liti().ws(), NamedGraphClause.or(DefaultGraphClause)).note(~"DatasetClause");
+	
 	// [7] SelectQuery ::= SelectClause DatasetClause* WhereClause SolutionModifier
-	let SelectQuery = do seq3(SelectClause, WhereClause, SolutionModifier)
-		|patterns, algebra, modifiers| {result::Ok((patterns, algebra, modifiers))};
+	let SelectQuery = do seq4(SelectClause, DatasetClause.r0(), WhereClause, SolutionModifier)
+		|patterns, graphs, algebra, modifiers| {result::Ok((patterns, copy *graphs, algebra, modifiers))};
 		
 	// [6] PrefixDecl ::= 'PREFIX' PNAME_NS IRI_REF
 	let PrefixDecl = do seq3("PREFIX".liti().ws(), PNAME_NS.ws(), IRI_REF)
@@ -1097,12 +1222,19 @@ priv fn make_parser() -> Parser<Selector>
 
 type SolutionModifiers = {order_by: Option<@~[expression::Expr]>, limit: Option<uint>};
 
+// Names from the SELECT clause + the context passed to eval.
+type CompiledQuery = (~[~str], QueryContext);
+
 // namespaces are from the PREFIX clauses
 // patterns are from the SELECT clause
+// graphs are from the FROM clauses (true for FROM NAMED)
 // algebra is from the WHERE clause
-priv fn build_parser(namespaces: &[Namespace], query: ((bool, ~[Pattern]), Algebra, SolutionModifiers)) -> result::Result<Selector, @~str>
+priv fn build_parser(namespaces: &[Namespace], query: ((bool, ~[Pattern]), ~[(bool, @~str)], Algebra, SolutionModifiers)) -> result::Result<CompiledQuery, @~str>
 {
-	let ((distinct, patterns), algebra, modifiers) = query;
+	let ((distinct, patterns), graphs, algebra, modifiers) = query;
+	
+	let from = do vec::filter_map(graphs) |g| {match *g {(false, iri) => option::Some(expand_uri(namespaces, *iri)), _ => option::None}};
+	let from_named = do vec::filter_map(graphs) |g| {match *g {(true, iri) => option::Some(expand_uri(namespaces, *iri)), _ => option::None}};
 	
 	let variables = do vec::filter(patterns) |p| {match *p {Variable(ref _l)  => true, _  => false}};
 	let names = do vec::map(variables) |p| {match *p {Variable(copy n)  => n, _  => fail}};
@@ -1115,13 +1247,13 @@ priv fn build_parser(namespaces: &[Namespace], query: ((bool, ~[Pattern]), Algeb
 		// eval will set namespaces and extensions
 		if vec::is_not_empty(namespaces)
 		{
-			let context = QueryContext {namespaces: ~[], extensions: HashMap(), algebra: expand(namespaces, &algebra), order_by: copy *order_by, distinct: distinct, limit: modifiers.limit, rng: rand::Rng(), timestamp: time::now()};
-			result::Ok(eval(names, &context))
+			let context = QueryContext {namespaces: ~[], extensions: HashMap(), algebra: expand(namespaces, &algebra), order_by: copy *order_by, distinct: distinct, limit: modifiers.limit, rng: rand::Rng(), timestamp: time::now(), from: from, from_named: from_named, named_graphs: HashMap()};
+			result::Ok((names, context))
 		}
 		else
 		{
-			let context = QueryContext {namespaces: ~[], extensions: HashMap(), algebra: algebra, order_by: copy *order_by, distinct: distinct, limit: modifiers.limit, rng: rand::Rng(), timestamp: time::now()};
-			result::Ok(eval(names, &context))
+			let context = QueryContext {namespaces: ~[], extensions: HashMap(), algebra: algebra, order_by: copy *order_by, distinct: distinct, limit: modifiers.limit, rng: rand::Rng(), timestamp: time::now(), from: from, from_named: from_named, named_graphs: HashMap()};
+			result::Ok((names, context))
 		}
 	}
 	else
@@ -1134,6 +1266,34 @@ priv fn build_parser(namespaces: &[Namespace], query: ((bool, ~[Pattern]), Algeb
 /// 
 /// Expr can be a subset of http://www.w3.org/TR/2001/REC-xmlschema-2-20010502/#built-in-datatypes \"SPARQL\".
 pub fn compile(expr: &str) -> result::Result<Selector, ~str>
+{
+	do parse_query(expr).chain |query|
+	{
+		let (names, context) = query;
+		if context.from.is_empty() && context.from_named.is_empty()
+		{
+			result::Ok(eval(names, &context))
+		}
+		else
+		{
+			result::Err(~"FROM and FROM NAMED can only be used with compile_dataset.")
+		}
+	}
+}
+
+/// Like compile except that the function matches triples within a dataset.
+/// 
+/// GRAPH patterns match triples within the named graphs (see eval_dataset).
+pub fn compile_dataset(expr: &str) -> result::Result<DatasetSelector, ~str>
+{
+	do parse_query(expr).chain |query|
+	{
+		let (names, context) = query;
+		result::Ok(eval_dataset(names, &context))
+	}
+}
+
+priv fn parse_query(expr: &str) -> result::Result<CompiledQuery, ~str>
 {
 	let parser = make_parser();
 	do result::chain_err(parser.parse(@~"sparql", expr)) |err|
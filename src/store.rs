@@ -23,25 +23,49 @@ pub type Triple = {subject: Subject, predicate: Predicate, object: @Object};
 #[deriving_eq]
 pub type Entry = {predicate: ~str, object: @Object};
 
+/// Encoded version of Entry (see TermDictionary).
+#[deriving_eq]
+pub type TermEntry = {predicate: TermId, object: TermId};
+
+/// Subject and object associated with a predicate.
+#[deriving_eq]
+pub type PredicateEntry = {subject: TermId, object: TermId};
+
+/// Subject and predicate associated with an IRI or blank object.
+#[deriving_eq]
+pub type ObjectEntry = {subject: TermId, predicate: TermId};
+
+/// Called after a triple is added to, replaced in, or removed from a store (or the store is cleared
+/// or a transaction is committed or rolled back).
+pub type ChangeListener = fn@ (change: &Change);
+
 /// SPARQL extension function.
 pub type ExtensionFn = pure fn@ (namespaces: &[Namespace], args: &[@Object]) -> @Object;
 
 /// Stores triples in a more or less efficient format.
 ///
-/// Note that these are not intended to be copied.
+/// Subjects, predicates, and objects are stored as ids from terms (use the each method
+/// to get the triples as strings and objects). Note that these are not intended to be copied.
 pub struct Store
 {
-	pub namespaces: ~[Namespace],
-	pub subjects: HashMap<@~str, @DVec<Entry>>,
+	pub mut namespaces: ~[Namespace],
+	pub subjects: HashMap<TermId, @DVec<TermEntry>>,
+	pub predicates: HashMap<TermId, @DVec<PredicateEntry>>,		// used to speed up queries
+	pub objects: HashMap<TermId, @DVec<ObjectEntry>>,			// only IRI and blank objects are indexed
+	pub terms: TermDictionary,
 	pub extensions: HashMap<@~str, ExtensionFn>,
 	pub mut next_blank: int,
+	pub mut set_semantics: bool,								// if true adding a triple already in the store is a no-op
+	pub mut transaction: option::Option<@Transaction>,		// set between begin and commit/rollback
+	pub listeners: DVec<(uint, ChangeListener)>,
+	pub mut next_listener: uint,
 	
 	drop {}
 }
 
 /// Initializes a store object.
 /// 
-/// xsd, rdf, rdfs, and owl namespaces are automatically added. An rrdf:pname extension is
+/// Duplicate triples are allowed unless set_semantics is set to true. xsd, rdf, rdfs, and owl namespaces are automatically added. An rrdf:pname extension is
 /// automatically added which converts an IriValue to a StringValue using namespaces (or
 /// simply stringifies it if none of the namespaces paths match).
 pub fn Store(namespaces: &[Namespace], extensions: &HashMap<@~str, ExtensionFn>) -> Store
@@ -49,8 +73,15 @@ pub fn Store(namespaces: &[Namespace], extensions: &HashMap<@~str, ExtensionFn>)
 	let store = Store {
 		namespaces: default_namespaces() + namespaces,
 		subjects: HashMap(),
+		predicates: HashMap(),
+		objects: HashMap(),
+		terms: TermDictionary(),
 		extensions: copy *extensions,
 		next_blank: 0,
+		set_semantics: false,
+		transaction: option::None,
+		listeners: DVec(),
+		next_listener: 1,
 	};
 	
 	store.extensions.insert(@~"rrdf:pname", pname_fn);
@@ -65,6 +96,14 @@ pub fn get_blank_name(store: &Store, prefix: &str) -> ~str
 	fmt!("_:%s-%?", prefix, suffix)
 }
 
+/// Adds a namespace to the store replacing any existing namespace with the same prefix.
+pub fn add_namespace(store: &Store, namespace: Namespace)
+{
+	let mut namespaces = do vec::filter(store.namespaces) |n| {n.prefix != namespace.prefix};
+	vec::push(&mut namespaces, namespace);
+	store.namespaces = namespaces;
+}
+
 /// Returns either the iri or the prefixed version of the iri.
 pub pure fn contract_uri(namespaces: &[Namespace], iri: &str) -> ~str
 {
@@ -86,12 +125,15 @@ pub trait StoreTrait
 	/// Efficient addition of triples to the store.
 	/// 
 	/// Typically create_int, create_str, etc functions are used to create objects.
-	fn add(subject: &str, entries: &[(~str, @Object)]);
+	/// Returns the number of triples which were added (if set_semantics is true this
+	/// may be less than the number of entries).
+	fn add(subject: &str, entries: &[(~str, @Object)]) -> uint;
 	
 	/// Relatively inefficient addition of triples to the store.
 	/// 
 	/// Qualified names may use the namespaces associated with the store and the supplied namespaces.
-	fn add_triple(namespaces: &[Namespace], triple: Triple);
+	/// Returns false if set_semantics is true and the triple was already in the store.
+	fn add_triple(namespaces: &[Namespace], triple: Triple) -> bool;
 	
 	/// Adds a subject statement referencing a new blank node.
 	/// 
@@ -131,6 +173,62 @@ pub trait StoreTrait
 	/// Qualified names may use the namespaces associated with the store.
 	fn find_objects(subject: &str, predicate: &str) -> ~[@Object];
 	
+	/// Returns the distinct predicates used with the subject.
+	/// 
+	/// Qualified names may use the namespaces associated with the store.
+	fn find_predicates(subject: &str) -> ~[~str];
+	
+	/// Returns the distinct subjects which have the predicate and object.
+	/// 
+	/// Qualified names may use the namespaces associated with the store.
+	fn find_subjects(predicate: &str, object: @Object) -> ~[~str];
+	
+	/// Calls blk for each triple matching the subject, predicate, and object.
+	/// 
+	/// Arguments which are option::None match everything. Objects are compared using
+	/// RDF term equality (see same_term). Qualified names may use the namespaces associated
+	/// with the store. The store should not be changed until the iteration finishes.
+	fn find_triples(subject: option::Option<&str>, predicate: option::Option<&str>, object: option::Option<@Object>, blk: fn (&Triple) -> bool);
+	
+	/// Returns the kind (e.g. rdf:Bag) and values of a container.
+	/// 
+	/// The container may be one added with add_container or a subject typed as rdf:Bag,
+	/// rdf:Seq, or rdf:Alt (as loaded from Turtle or RDF/XML). Values are ordered by their
+	/// rdf:_N predicates. Returns an error if the subject is not a container, has more
+	/// than one container, or uses an rdf:_N predicate more than once.
+	fn get_container(subject: &str) -> result::Result<(~str, ~[@Object]), ~str>;
+	
+	/// Returns the values of a list added with add_list (or loaded from a collection).
+	/// 
+	/// Returns an error if the subject does not have exactly one value for predicate or
+	/// the list is malformed, e.g. a node has more than one rdf:first or the list is cyclic.
+	fn get_list(subject: &str, predicate: &str) -> result::Result<~[@Object], ~str>;
+	
+	/// Removes all triples matching the subject, predicate, and object.
+	/// 
+	/// Arguments which are option::None match everything. Objects are compared using
+	/// RDF term equality (see same_term). Qualified names may use the namespaces associated
+	/// with the store. Returns the number of triples removed.
+	fn remove_matching(subject: option::Option<~str>, predicate: option::Option<~str>, object: option::Option<@Object>) -> uint;
+	
+	/// Removes all triples with the subject and returns the number removed.
+	/// 
+	/// Qualified names may use the namespaces associated with the store.
+	fn remove_subject(subject: &str) -> uint;
+	
+	/// Removes every copy of the triple and returns the number removed.
+	/// 
+	/// Objects are compared using RDF term equality (see same_term). Qualified names may
+	/// use the namespaces associated with the store and the supplied namespaces.
+	fn remove_triple(namespaces: &[Namespace], triple: Triple) -> uint;
+	
+	/// Replaces the values of a list or adds a new list.
+	/// 
+	/// The old list's blank nodes are removed (along with blank node values which are no
+	/// longer referenced) unless something else refers to them. Returns an error without
+	/// changing the store if the old list is malformed (see get_list).
+	fn replace_list(subject: &str, predicate: &str, values: &[@Object]) -> result::Result<(), ~str>;
+	
 	/// Replaces the object of an existing triple or adds a new triple.
 	/// 
 	/// Qualified names may use the namespaces associated with the store and the supplied namespaces.
@@ -139,47 +237,21 @@ pub trait StoreTrait
 
 pub impl  &Store : StoreTrait 
 {
-	fn add(subject: &str, entries: &[(~str, @Object)])
+	fn add(subject: &str, entries: &[(~str, @Object)]) -> uint
 	{
-		if vec::is_not_empty(entries)
-		{
-			let subject = expand_uri_or_blank(self.namespaces, subject);
-			let entries = vec::map(entries, |e| {expand_entry(self.namespaces, e)});
-			match self.subjects.find(@copy subject)
-			{
-				option::Some(list) =>
-				{
-					list.push_all(entries);
-				}
-				option::None =>
-				{
-					let list = @DVec();
-					self.subjects.insert(@subject, list);
-					list.push_all(entries);
-				}
-			}
-		}
+		let subject = expand_uri_or_blank(self.namespaces, subject);
+		let entries = vec::map(entries, |e| {expand_entry(self.namespaces, e)});
+		add_entries(self, subject, entries)
 	}
 	
-	fn add_triple(namespaces: &[Namespace], triple: Triple)
+	fn add_triple(namespaces: &[Namespace], triple: Triple) -> bool
 	{
 		let namespaces = self.namespaces + namespaces;
 		
 		let subject = expand_uri_or_blank(namespaces, triple.subject);
 		let predicate = expand_uri(namespaces, triple.predicate);
 		let entry = {predicate: predicate, object: expand_object(namespaces, triple.object)};
-		
-		match self.subjects.find(@copy subject)
-		{
-			option::Some(entries) =>
-			{
-				entries.push(entry);
-			}
-			option::None =>
-			{
-				self.subjects.insert(@subject, @dvec::from_vec(~[entry]));
-			}
-		}
+		add_entries(self, subject, ~[entry]) == 1
 	}
 	
 	fn add_aggregate(subject: &str, predicate: &str, label: &str, entries: &[(~str, @Object)]) -> ~str
@@ -240,20 +312,20 @@ pub impl  &Store : StoreTrait
 	
 	fn clear()
 	{
-		// TODO: Replace this awful code once // https://github.com/mozilla/rust/issues/2775 is fixed.
-		// (Tried making subjects mutable but that lead to illegal borrows all over the place).
-		let mut keys = ~[];
-		for self.subjects.each_key
-		|key|
+		// Listeners get a single ClearedStore, but rollback needs to know which triples were removed.
+		if self.transaction.is_some()
 		{
-			vec::push(&mut keys, key);
-		};
+			for self.each |triple|
+			{
+				record_change(self, RemovedTriple(copy *triple));
+			}
+		}
 		
-		for vec::each(keys)
-		|key|
-		{
-			self.subjects.remove(*key);
-		};
+		remove_ids(self.subjects);
+		remove_ids(self.predicates);
+		remove_ids(self.objects);
+		self.terms.clear();
+		store_changed(self, ClearedStore);
 	}
 	
 	fn find_object(subject: &str, predicate: &str) -> option::Option<@Object>
@@ -261,15 +333,15 @@ pub impl  &Store : StoreTrait
 		let subject = expand_uri_or_blank(self.namespaces, subject);
 		let predicate = expand_uri(self.namespaces, predicate);
 		
-		match self.subjects.find(@subject)
+		match (find_entries(self, subject), self.terms.find_name(predicate))
 		{
-			option::Some(entries) =>
+			(option::Some(entries), option::Some(predicate)) =>
 			{
 				match entries.position(|candidate| {candidate.predicate == predicate})
 				{
 					option::Some(index) =>
 					{
-						option::Some(copy (*entries)[index].object)
+						option::Some(self.terms.get(entries.get_elt(index).object))
 					}
 					option::None =>
 					{
@@ -277,7 +349,7 @@ pub impl  &Store : StoreTrait
 					}
 				}
 			}
-			option::None =>
+			_ =>
 			{
 				option::None
 			}
@@ -289,16 +361,16 @@ pub impl  &Store : StoreTrait
 		let subject = expand_uri_or_blank(self.namespaces, subject);
 		let predicate = expand_uri(self.namespaces, predicate);
 		
-		match self.subjects.find(@subject)
+		match (find_entries(self, subject), self.terms.find_name(predicate))
 		{
-			option::Some(entries) =>
+			(option::Some(entries), option::Some(predicate)) =>
 			{
 				do entries.get().filter_map		// TODO: pretty bad to call get, but dvec doesn't have filter_map atm
 				|entry|
 				{
 					if entry.predicate == predicate
 					{
-						option::Some(copy entry.object)
+						option::Some(self.terms.get(entry.object))
 					}
 					else
 					{
@@ -306,34 +378,285 @@ pub impl  &Store : StoreTrait
 					}
 				}
 			}
-			option::None =>
+			_ =>
 			{
 				~[]
 			}
 		}
 	}
 	
+	fn find_predicates(subject: &str) -> ~[~str]
+	{
+		let subject = option::Some(expand_uri_or_blank(self.namespaces, subject));
+		
+		let mut predicates = ~[];
+		let seen = HashMap();
+		for each_matching_entry(self, &subject, &option::None, &option::None) |_subject, entry|
+		{
+			if !seen.contains_key(entry.predicate)
+			{
+				seen.insert(entry.predicate, ());
+				vec::push(&mut predicates, self.terms.get_name(entry.predicate));
+			}
+		}
+		predicates
+	}
+	
+	fn find_subjects(predicate: &str, object: @Object) -> ~[~str]
+	{
+		let predicate = option::Some(expand_uri(self.namespaces, predicate));
+		let object = option::Some(expand_object(self.namespaces, object));
+		
+		let mut subjects = ~[];
+		let seen = HashMap();
+		for each_matching_entry(self, &option::None, &predicate, &object) |subject, _entry|
+		{
+			if !seen.contains_key(subject)
+			{
+				seen.insert(subject, ());
+				vec::push(&mut subjects, self.terms.get_name(subject));
+			}
+		}
+		subjects
+	}
+	
+	fn find_triples(subject: option::Option<&str>, predicate: option::Option<&str>, object: option::Option<@Object>, blk: fn (&Triple) -> bool)
+	{
+		let subject = subject.map(|s| {expand_uri_or_blank(self.namespaces, *s)});
+		let predicate = predicate.map(|p| {expand_uri(self.namespaces, *p)});
+		let object = object.map(|o| {expand_object(self.namespaces, *o)});
+		
+		for each_matching_entry(self, &subject, &predicate, &object) |subject, entry|
+		{
+			let triple = {subject: self.terms.get_name(subject), predicate: self.terms.get_name(entry.predicate), object: self.terms.get(entry.object)};
+			if !blk(&triple)
+			{
+				return;
+			}
+		}
+	}
+	
+	fn get_container(subject: &str) -> result::Result<(~str, ~[@Object]), ~str>
+	{
+		let subject = expand_uri_or_blank(self.namespaces, subject);
+		let kinds = ~[~"http://www.w3.org/1999/02/22-rdf-syntax-ns#Bag", ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#Seq", ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#Alt"];
+		
+		// Containers are either typed subjects or blank nodes referenced via the kind.
+		let mut containers = ~[];
+		for self.find_objects(subject, ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#type").each |kind|
+		{
+			if is_container_type(*kind)
+			{
+				vec::push(&mut containers, (object_to_subject(*kind), copy subject));
+			}
+		}
+		for kinds.each |kind|
+		{
+			for self.find_objects(subject, *kind).each |node|
+			{
+				match **node
+				{
+					IriValue(ref name) | BlankValue(ref name) => vec::push(&mut containers, (copy *kind, copy *name)),
+					_ => return result::Err(fmt!("%s container for %s is %s.", *kind, subject, node.to_str())),
+				}
+			}
+		}
+		
+		if containers.is_empty()
+		{
+			return result::Err(fmt!("%s is not a container.", subject));
+		}
+		else if containers.len() > 1
+		{
+			return result::Err(fmt!("%s has %? containers.", subject, containers.len()));
+		}
+		
+		let (kind, node) = copy containers[0];
+		let mut members = ~[];
+		for self.find_triples(option::Some(node), option::None, option::None) |triple|
+		{
+			match member_index(triple.predicate)
+			{
+				option::Some(index) =>
+				{
+					if members.any(|m: &(uint, @Object)| {m.first() == index})
+					{
+						return result::Err(fmt!("%s has more than one %s.", node, triple.predicate));
+					}
+					vec::push(&mut members, (index, triple.object));
+				}
+				option::None =>
+				{
+				}
+			}
+		}
+		
+		let members = std::sort::merge_sort(members, |x, y| {x.first() <= y.first()});
+		result::Ok((kind, do members.map |m| {m.second()}))
+	}
+	
+	fn get_list(subject: &str, predicate: &str) -> result::Result<~[@Object], ~str>
+	{
+		let heads = self.find_objects(subject, predicate);
+		if heads.len() != 1
+		{
+			return result::Err(fmt!("Expected one %s for %s but found %?.", predicate, subject, heads.len()));
+		}
+		
+		match walk_list(self, heads[0])
+		{
+			result::Ok((copy items, _)) => result::Ok(items),
+			result::Err(copy err) => result::Err(err),
+		}
+	}
+	
+	fn remove_matching(subject: option::Option<~str>, predicate: option::Option<~str>, object: option::Option<@Object>) -> uint
+	{
+		let predicate = predicate.map(|p| {expand_uri(self.namespaces, *p)});
+		let object = object.map(|o| {expand_object(self.namespaces, *o)});
+		
+		match subject
+		{
+			option::Some(ref subject) =>
+			{
+				let subject = expand_uri_or_blank(self.namespaces, *subject);
+				match self.terms.find_name(subject)
+				{
+					option::Some(id) => remove_entries(self, id, |entry| {entry_matches(entry, &predicate, &object)}),
+					option::None => 0,
+				}
+			}
+			option::None =>
+			{
+				let mut keys = ~[];
+				for self.subjects.each_key
+				|key|
+				{
+					vec::push(&mut keys, key);
+				};
+				
+				do vec::foldl(0u, keys)
+				|count, key|
+				{
+					count + remove_entries(self, *key, |entry| {entry_matches(entry, &predicate, &object)})
+				}
+			}
+		}
+	}
+	
+	fn remove_subject(subject: &str) -> uint
+	{
+		let subject = expand_uri_or_blank(self.namespaces, subject);
+		match self.terms.find_name(subject)
+		{
+			option::Some(id) => remove_entries(self, id, |_entry| {true}),
+			option::None => 0,
+		}
+	}
+	
+	fn remove_triple(namespaces: &[Namespace], triple: Triple) -> uint
+	{
+		let namespaces = self.namespaces + namespaces;
+		
+		let subject = expand_uri_or_blank(namespaces, triple.subject);
+		let predicate = expand_uri(namespaces, triple.predicate);
+		let object = expand_object(namespaces, triple.object);
+		match self.terms.find_name(subject)
+		{
+			option::Some(id) => remove_entries(self, id, |entry| {entry.predicate == predicate && same_term(entry.object, object)}),
+			option::None => 0,
+		}
+	}
+	
+	fn replace_list(subject: &str, predicate: &str, values: &[@Object]) -> result::Result<(), ~str>
+	{
+		let heads = self.find_objects(subject, predicate);
+		if heads.len() > 1
+		{
+			return result::Err(fmt!("Expected one %s for %s but found %?.", predicate, subject, heads.len()));
+		}
+		
+		if heads.len() == 1
+		{
+			let (items, nodes) = match walk_list(self, heads[0])
+			{
+				result::Ok(copy result) => result,
+				result::Err(copy err) => return result::Err(err),
+			};
+			
+			self.remove_triple(~[], {subject: subject.to_owned(), predicate: predicate.to_owned(), object: heads[0]});
+			for nodes.each |node|
+			{
+				// If the rest of the list is shared we have to leave it alone.
+				if !node.starts_with("_:") || is_object(self, *node)
+				{
+					break;
+				}
+				self.remove_subject(*node);
+			}
+			
+			self.add_list(subject, predicate, values);
+			for items.each |item|
+			{
+				remove_orphan(self, *item);
+			}
+		}
+		else
+		{
+			self.add_list(subject, predicate, values);
+		}
+		result::Ok(())
+	}
+	
 	fn replace_triple(namespaces: &[Namespace], triple: Triple)
 	{
 		let namespaces = self.namespaces + namespaces;
 		
 		let subject = expand_uri_or_blank(namespaces, triple.subject);
 		let predicate = expand_uri(namespaces, triple.predicate);
-		let entry = {predicate: copy predicate, object: expand_object(namespaces, triple.object)};
+		let object = expand_object(namespaces, triple.object);
 		
-		match self.subjects.find(@copy subject)
+		match find_entries(self, subject)
 		{
 			option::Some(entries) =>
 			{
-				match entries.position(|candidate| {candidate.predicate == predicate})
+				let subject_id = self.terms.intern_name(subject);
+				let entry = {predicate: self.terms.intern_name(predicate), object: self.terms.intern(object)};
+				match entries.position(|candidate| {candidate.predicate == entry.predicate})
 				{
-					option::Some(index) 	=> entries.set_elt(index, entry),
-					option::None 			=> entries.push(entry),
+					option::Some(index) =>
+					{
+						let old = entries.get_elt(index);
+						if self.set_semantics && has_entry(self, entries, &entry)
+						{
+							// The new triple is already present so all we need to do is
+							// remove the old triple (unless it is the new triple).
+							if !same_term(self.terms.get(old.object), self.terms.get(entry.object))
+							{
+								unindex_entry(self, subject_id, &old);
+								let mut kept = entries.get();
+								vec::remove(&mut kept, index);
+								entries.set(kept);
+								store_changed(self, RemovedTriple({subject: copy subject, predicate: predicate, object: self.terms.get(old.object)}));
+							}
+						}
+						else
+						{
+							let old_object = replace_entry_at(self, subject_id, entries, index, object);
+							store_changed(self, ReplacedTriple({subject: copy subject, predicate: predicate, object: self.terms.get(entry.object)}, old_object));
+						}
+					}
+					option::None =>
+					{
+						index_entry(self, subject_id, &entry);
+						entries.push(entry);
+						store_changed(self, AddedTriple({subject: copy subject, predicate: predicate, object: self.terms.get(entry.object)}));
+					}
 				}
 			}
 			option::None =>
 			{
-				self.subjects.insert(@subject, @dvec::from_vec(~[entry]));
+				add_entries(self, subject, ~[{predicate: predicate, object: object}]);
 			}
 		}
 	}
@@ -345,15 +668,19 @@ pub impl &Store : ToStr
 	{
 		let mut result = ~"";
 		
-		for self.subjects.each()
-		|subject, entries|
+		unsafe		// TODO: remove once bug 3372 is fixed
 		{
-			for (*entries).eachi()
-			|i, entry|
+			for self.subjects.each()
+			|subject, entries|
 			{
-				result += fmt!("%?: <%s>  <%s>  %s}\n", i, *subject, entry.predicate, entry.object.to_str());
-			}
-		};
+				for (*entries).eachi()
+				|i, entry|
+				{
+					let entry = decode_entry(self, entry);
+					result += fmt!("%?: <%s>  <%s>  %s}\n", i, self.terms.get_name(subject), entry.predicate, entry.object.to_str());
+				}
+			};
+		}
 		
 		return result;
 	}
@@ -368,9 +695,10 @@ pub impl Store : BaseIter<Triple>
 		{
 			for self.subjects.each() |subject, entries|
 			{
+				let subject = self.terms.get_name(subject);
 				for (*entries).each() |entry|
 				{
-					let triple = {subject: copy *subject, predicate: copy entry.predicate, object: copy entry.object};
+					let triple = {subject: copy subject, predicate: self.terms.get_name(entry.predicate), object: self.terms.get(entry.object)};
 					if !blk(&triple)
 					{
 						return;
@@ -389,65 +717,276 @@ pub impl Store : BaseIter<Triple>
 	}
 }
 
-pub impl  Triple : ToStr
-{
-	pure fn to_str() -> ~str
-	{
-		fmt!("{%s, %s, %s}", self.subject, self.predicate, self.object.to_str())
-	}
-}
-
-// ---- Private Functions -----------------------------------------------------
-priv fn default_namespaces() -> ~[Namespace]
-{
-	~[
-		Namespace {prefix: ~"_", path: ~"_:"},
-		Namespace {prefix: ~"xsd", path: ~"http://www.w3.org/2001/XMLSchema#"},
-		Namespace {prefix: ~"rdf", path: ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#"},
-		Namespace {prefix: ~"rdfs", path: ~"http://www.w3.org/2000/01/rdf-schema#"},
-		Namespace {prefix: ~"owl", path: ~"http://www.w3.org/2002/07/owl#"}
-	]
-}
-
-priv fn expand_uri(namespaces: &[Namespace], name: &str) -> ~str
-{
-	// TODO: need to % escape bogus characters (after converting to utf-8)
-	for vec::each(namespaces)
-	|namespace|
-	{
-		if str::starts_with(name, namespace.prefix + ":")
-		{
-			return namespace.path + str::slice(name, str::len(namespace.prefix) + 1u, str::len(name));
-		}
-	};
-	return name.to_owned();
-}
-
-priv fn expand_uri_or_blank(namespaces: &[Namespace], name: &str) -> ~str
+pub impl Store
 {
-	if str::starts_with(name, "_:")
+	/// Writes the store to the writer using a compact binary format (see snapshot.rs).
+	/// 
+	/// Extension functions and set_semantics are not saved.
+	fn save(&self, writer: io::Writer)
 	{
-		name.to_owned()
+		save_snapshot(self, writer);
 	}
-	else
+	
+	/// Creates a store using data written by save.
+	static fn load(reader: io::Reader) -> result::Result<Store, ~str>
 	{
-		expand_uri(namespaces, name)
+		load_snapshot(reader)
 	}
-}
-
-priv fn expand_object(namespaces: &[Namespace], obj: @Object) -> @Object
-{
-	match *obj
+	
+	/// Copies all of the triples in other into this store.
+	/// 
+	/// Blank nodes from other are renamed using get_blank_name so that they will not
+	/// collide with blank nodes already in this store. Namespaces from other are added
+	/// unless this store already has a namespace with the same prefix. Returns the
+	/// prefixes which were not added because they map to a different path.
+	fn merge(&self, other: &Store) -> ~[~str]
 	{
-		TypedValue(copy value, ref kind) =>
+		let mut conflicts = ~[];
+		for other.namespaces.each |namespace|
 		{
-			@TypedValue(value, expand_uri(namespaces, *kind))
-		}
-		IriValue(ref value) =>
-		{
-			@IriValue(expand_uri(namespaces, *value))
+			match vec::find(self.namespaces, |n| {n.prefix == namespace.prefix})
+			{
+				option::Some(ref existing) =>
+				{
+					if existing.path != namespace.path
+					{
+						vec::push(&mut conflicts, copy namespace.prefix);
+					}
+				}
+				option::None =>
+				{
+					add_namespace(self, copy *namespace);
+				}
+			}
 		}
-		BlankValue(ref value) =>
+		
+		// Grab the subjects first so that merging a store with itself works.
+		let mut subjects = ~[];
+		for other.subjects.each |subject, entries|
+		{
+			vec::push(&mut subjects, (other.terms.get_name(subject), entries.get().map(|entry| {decode_entry(other, entry)})));
+		};
+		
+		let blanks = HashMap();
+		for subjects.each |pair|
+		{
+			let (subject, entries) = copy *pair;
+			let subject = rename_blank(self, blanks, subject);
+			let entries = do entries.map |entry|
+			{
+				match *entry.object
+				{
+					BlankValue(ref name) => ({predicate: copy entry.predicate, object: @BlankValue(rename_blank(self, blanks, *name))}),
+					_ => copy *entry,
+				}
+			};
+			add_entries(self, subject, entries);
+		}
+		
+		conflicts
+	}
+	
+	/// Removes terms which are no longer used by any triple from the store's TermDictionary.
+	/// 
+	/// Removing triples does not remove their terms from the dictionary (that would require
+	/// reference counting every term) so stores which are changed a lot over a long time
+	/// should call this occasionally to reclaim the memory. The triples are not changed but
+	/// every TermId is reassigned so this should not be called while iterating over the
+	/// store. Returns the number of terms which were removed.
+	fn compact_terms(&self) -> uint
+	{
+		let old_count = self.terms.terms.len();
+		
+		let mut subjects = ~[];
+		for self.subjects.each |subject, entries|
+		{
+			vec::push(&mut subjects, (self.terms.get(subject), entries.get().map(|entry| {(self.terms.get(entry.predicate), self.terms.get(entry.object))})));
+		};
+		
+		remove_ids(self.subjects);
+		remove_ids(self.predicates);
+		remove_ids(self.objects);
+		self.terms.clear();
+		
+		for subjects.each |pair|
+		{
+			let (subject, entries) = copy *pair;
+			let subject_id = self.terms.intern(subject);
+			let encoded = @DVec();
+			for entries.each |entry|
+			{
+				let (predicate, object) = *entry;
+				let entry = {predicate: self.terms.intern(predicate), object: self.terms.intern(object)};
+				index_entry(self, subject_id, &entry);
+				encoded.push(entry);
+			}
+			self.subjects.insert(subject_id, encoded);
+		}
+		
+		old_count - self.terms.terms.len()
+	}
+	
+	/// Returns true if the stores have the same triples after renaming blank nodes.
+	/// 
+	/// See stores_are_isomorphic for details.
+	fn is_isomorphic(&self, other: &Store) -> bool
+	{
+		stores_are_isomorphic(self, other)
+	}
+	
+	/// Removes and then adds the patch's triples (see diff_stores).
+	///
+	/// Fails without changing the store if a triple to be removed is not in the store.
+	/// Blank nodes in added triples which are already used by the store refer to those
+	/// blank nodes, other blank nodes are renamed using get_blank_name.
+	fn apply_patch(&self, patch: &Patch) -> result::Result<(), ~str>
+	{
+		apply_store_patch(self, patch)
+	}
+	
+	/// Starts a transaction.
+	/// 
+	/// Changes made within a transaction are applied immediately (so queries and
+	/// loaders see them) but are also recorded so that rollback can undo them.
+	/// Nested transactions are not supported.
+	fn begin(&self)
+	{
+		begin_transaction(self);
+	}
+	
+	/// Ends the current transaction keeping all of the changes made within it.
+	fn commit(&self)
+	{
+		commit_transaction(self);
+	}
+	
+	/// Ends the current transaction undoing all of the changes made within it.
+	/// 
+	/// next_blank and namespaces are also restored. Note that the order of a subject's
+	/// entries may change.
+	fn rollback(&self)
+	{
+		rollback_transaction(self);
+	}
+	
+	/// Returns the changes made within the current transaction in the order they were made.
+	fn pending_changes(&self) -> ~[Change]
+	{
+		match self.transaction
+		{
+			option::Some(transaction) => transaction.changes.get(),
+			option::None => ~[],
+		}
+	}
+	
+	/// Registers a function to be called after each change to the store.
+	/// 
+	/// This includes changes made by the StoreTrait helpers (e.g. add_list adds multiple
+	/// triples) and by rollback. Returns an id which may be passed to remove_listener.
+	fn add_listener(&self, listener: ChangeListener) -> uint
+	{
+		let id = self.next_listener;
+		self.next_listener += 1;
+		self.listeners.push((id, listener));
+		id
+	}
+	
+	/// Unregisters a function added with add_listener. Returns false if there was no such listener.
+	fn remove_listener(&self, id: uint) -> bool
+	{
+		match self.listeners.position(|entry| {entry.first() == id})
+		{
+			option::Some(index) =>
+			{
+				let mut listeners = self.listeners.get();
+				vec::remove(&mut listeners, index);
+				self.listeners.set(listeners);
+				true
+			}
+			option::None =>
+			{
+				false
+			}
+		}
+	}
+	
+	/// Calls block within a transaction which is committed if block returns Ok and
+	/// rolled back if block returns Err.
+	fn with_transaction<T>(&self, block: fn () -> result::Result<T, ~str>) -> result::Result<T, ~str>
+	{
+		self.begin();
+		let result = block();
+		if result::is_ok(&result)
+		{
+			self.commit();
+		}
+		else
+		{
+			self.rollback();
+		}
+		result
+	}
+}
+
+pub impl  Triple : ToStr
+{
+	pure fn to_str() -> ~str
+	{
+		fmt!("{%s, %s, %s}", self.subject, self.predicate, self.object.to_str())
+	}
+}
+
+// ---- Private Functions -----------------------------------------------------
+priv fn default_namespaces() -> ~[Namespace]
+{
+	~[
+		Namespace {prefix: ~"_", path: ~"_:"},
+		Namespace {prefix: ~"xsd", path: ~"http://www.w3.org/2001/XMLSchema#"},
+		Namespace {prefix: ~"rdf", path: ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#"},
+		Namespace {prefix: ~"rdfs", path: ~"http://www.w3.org/2000/01/rdf-schema#"},
+		Namespace {prefix: ~"owl", path: ~"http://www.w3.org/2002/07/owl#"}
+	]
+}
+
+priv fn expand_uri(namespaces: &[Namespace], name: &str) -> ~str
+{
+	// TODO: need to % escape bogus characters (after converting to utf-8)
+	for vec::each(namespaces)
+	|namespace|
+	{
+		if str::starts_with(name, namespace.prefix + ":")
+		{
+			return namespace.path + str::slice(name, str::len(namespace.prefix) + 1u, str::len(name));
+		}
+	};
+	return name.to_owned();
+}
+
+priv fn expand_uri_or_blank(namespaces: &[Namespace], name: &str) -> ~str
+{
+	if str::starts_with(name, "_:")
+	{
+		name.to_owned()
+	}
+	else
+	{
+		expand_uri(namespaces, name)
+	}
+}
+
+priv fn expand_object(namespaces: &[Namespace], obj: @Object) -> @Object
+{
+	match *obj
+	{
+		TypedValue(copy value, ref kind) =>
+		{
+			@TypedValue(value, expand_uri(namespaces, *kind))
+		}
+		IriValue(ref value) =>
+		{
+			@IriValue(expand_uri(namespaces, *value))
+		}
+		BlankValue(ref value) =>
 		{
 			@BlankValue(expand_uri(namespaces, *value))
 		}
@@ -463,6 +1002,533 @@ priv fn expand_entry(namespaces: &[Namespace], entry: &(~str, @Object)) -> Entry
 	{predicate: expand_uri(namespaces, entry.first()), object: expand_object(namespaces, entry.second())}
 }
 
+// Adds entries whose names have already been expanded and returns the number added.
+priv fn add_entries(store: &Store, subject: ~str, entries: &[Entry]) -> uint
+{
+	if entries.is_empty()
+	{
+		return 0;
+	}
+	
+	let subject_id = store.terms.intern_name(subject);
+	let list = match store.subjects.find(subject_id)
+	{
+		option::Some(list) =>
+		{
+			list
+		}
+		option::None =>
+		{
+			let list = @DVec();
+			store.subjects.insert(subject_id, list);
+			list
+		}
+	};
+	
+	let mut count = 0;
+	for entries.each |entry|
+	{
+		// Note that this also catches duplicates within entries.
+		let encoded = {predicate: store.terms.intern_name(entry.predicate), object: store.terms.intern(entry.object)};
+		if !store.set_semantics || !has_entry(store, list, &encoded)
+		{
+			index_entry(store, subject_id, &encoded);
+			list.push(encoded);
+			store_changed(store, AddedTriple({subject: copy subject, predicate: copy entry.predicate, object: store.terms.get(encoded.object)}));
+			count += 1;
+		}
+	}
+	count
+}
+
+// Calls the listeners and records the change if there is a transaction.
+priv fn store_changed(store: &Store, change: Change)
+{
+	// Listeners are allowed to add and remove listeners so we need to iterate over a copy.
+	for store.listeners.get().each |entry|
+	{
+		let (_, listener) = *entry;
+		listener(&change);
+	}
+	record_change(store, change);
+}
+
+// Uses RDF term equality (not the value equality used by Object's Eq).
+priv fn has_entry(store: &Store, entries: @DVec<TermEntry>, entry: &TermEntry) -> bool
+{
+	let matches = |candidate: &TermEntry|
+	{
+		candidate.predicate == entry.predicate && (candidate.object == entry.object || same_term(store.terms.get(candidate.object), store.terms.get(entry.object)))
+	};
+	entries.position(matches).is_some()
+}
+
+// Returns the entries for a subject whose name has already been expanded.
+priv fn find_entries(store: &Store, subject: &str) -> option::Option<@DVec<TermEntry>>
+{
+	do store.terms.find_name(subject).chain |id| {store.subjects.find(id)}
+}
+
+priv fn decode_entry(store: &Store, entry: &TermEntry) -> Entry
+{
+	{predicate: store.terms.get_name(entry.predicate), object: store.terms.get(entry.object)}
+}
+
+// Returns the subject id, the subject's entries, and the index of the last entry matching
+// the triple (which should already be expanded). Matches is used to compare the objects.
+priv fn find_entry_index(store: &Store, triple: &Triple, matches: fn (TermId) -> bool) -> option::Option<(TermId, @DVec<TermEntry>, uint)>
+{
+	match (store.terms.find_name(triple.subject), store.terms.find_name(triple.predicate))
+	{
+		(option::Some(subject), option::Some(predicate)) =>
+		{
+			do store.subjects.find(subject).chain
+			|entries|
+			{
+				do vec::rposition(entries.get(), |e| {e.predicate == predicate && matches(e.object)}).map
+				|index|
+				{
+					(subject, entries, *index)
+				}
+			}
+		}
+		_ =>
+		{
+			option::None
+		}
+	}
+}
+
+// Removes the entry at index (and the subject if it has no entries left).
+priv fn remove_entry_at(store: &Store, subject: TermId, entries: @DVec<TermEntry>, index: uint)
+{
+	let mut kept = entries.get();
+	let entry = vec::remove(&mut kept, index);
+	unindex_entry(store, subject, &entry);
+	if kept.is_empty()
+	{
+		store.subjects.remove(subject);
+	}
+	else
+	{
+		entries.set(kept);
+	}
+	store_changed(store, RemovedTriple({subject: store.terms.get_name(subject), predicate: store.terms.get_name(entry.predicate), object: store.terms.get(entry.object)}));
+}
+
+// Replaces the object of the entry at index and returns the old object. Note that
+// store_changed is not called.
+priv fn replace_entry_at(store: &Store, subject: TermId, entries: @DVec<TermEntry>, index: uint, object: @Object) -> @Object
+{
+	let old = entries.get_elt(index);
+	let entry = {predicate: old.predicate, object: store.terms.intern(object)};
+	unindex_entry(store, subject, &old);
+	index_entry(store, subject, &entry);
+	entries.set_elt(index, entry);
+	store.terms.get(old.object)
+}
+
+// Returns a fresh name for blank nodes (and the name unchanged for IRIs). Blanks maps
+// the original names to the new names.
+priv fn rename_blank(store: &Store, blanks: HashMap<@~str, @~str>, name: &str) -> ~str
+{
+	if !name.starts_with("_:")
+	{
+		return name.to_owned();
+	}
+	
+	match blanks.find(@name.to_owned())
+	{
+		option::Some(new_name) =>
+		{
+			copy *new_name
+		}
+		option::None =>
+		{
+			// Strip the old suffix so names don't keep growing as stores are merged.
+			let label = str::slice(name, 2, name.len());
+			let label = match str::rfind_char(label, '-')
+			{
+				option::Some(i) if i > 0 && str::all(str::slice(label, i + 1, label.len()), char::is_digit) => str::slice(label, 0, i),
+				_ => label.to_owned(),
+			};
+			let new_name = get_blank_name(store, label);
+			blanks.insert(@name.to_owned(), @copy new_name);
+			new_name
+		}
+	}
+}
+
+// Removes the subject's entries for which matches returns true. If no entries are left
+// the subject is removed as well. Listeners are called after each entry is removed so
+// that they always see the store in a consistent state (persistent stores may checkpoint
+// from within a listener).
+priv fn remove_entries(store: &Store, subject: TermId, matches: fn (&Entry) -> bool) -> uint
+{
+	match store.subjects.find(subject)
+	{
+		option::Some(entries) =>
+		{
+			let mut indexes = ~[];
+			for entries.get().eachi |i, encoded|
+			{
+				if matches(&decode_entry(store, encoded))
+				{
+					vec::push(&mut indexes, i);
+				}
+			}
+			
+			// Each removal shifts the later entries down by one.
+			for indexes.eachi |count, index|
+			{
+				remove_entry_at(store, subject, entries, *index - count);
+			}
+			indexes.len()
+		}
+		option::None =>
+		{
+			0
+		}
+	}
+}
+
+// Adds the entry to the predicate and object indexes.
+priv fn index_entry(store: &Store, subject: TermId, entry: &TermEntry)
+{
+	let item = {subject: subject, object: entry.object};
+	match store.predicates.find(entry.predicate)
+	{
+		option::Some(items) =>
+		{
+			items.push(item);
+		}
+		option::None =>
+		{
+			store.predicates.insert(entry.predicate, @dvec::from_vec(~[item]));
+		}
+	}
+	
+	if is_indexed_object(store.terms.get(entry.object))
+	{
+		let item = {subject: subject, predicate: entry.predicate};
+		match store.objects.find(entry.object)
+		{
+			option::Some(items) =>
+			{
+				items.push(item);
+			}
+			option::None =>
+			{
+				store.objects.insert(entry.object, @dvec::from_vec(~[item]));
+			}
+		}
+	}
+}
+
+// Removes the entry from the predicate and object indexes.
+priv fn unindex_entry(store: &Store, subject: TermId, entry: &TermEntry)
+{
+	remove_index_item(store.predicates, entry.predicate, |item| {item.subject == subject && item.object == entry.object});
+	
+	if is_indexed_object(store.terms.get(entry.object))
+	{
+		remove_index_item(store.objects, entry.object, |item| {item.subject == subject && item.predicate == entry.predicate});
+	}
+}
+
+// Removes the first item in the index for key which matches.
+priv fn remove_index_item<T: Copy>(index: HashMap<TermId, @DVec<T>>, key: TermId, matches: fn (&T) -> bool)
+{
+	match index.find(key)
+	{
+		option::Some(items) =>
+		{
+			match items.position(matches)
+			{
+				option::Some(i) =>
+				{
+					let mut old = items.get();
+					vec::remove(&mut old, i);
+					if old.is_empty()
+					{
+						index.remove(key);
+					}
+					else
+					{
+						items.set(old);
+					}
+				}
+				option::None =>
+				{
+				}
+			}
+		}
+		option::None =>
+		{
+		}
+	}
+}
+
+// Literals are not indexed because queries match them using value equality.
+priv fn is_indexed_object(object: @Object) -> bool
+{
+	match *object
+	{
+		IriValue(_) | BlankValue(_) => true,
+		_ => false,
+	}
+}
+
+priv fn remove_keys<T: Copy>(map: HashMap<@~str, T>)
+{
+	// TODO: Replace this awful code once // https://github.com/mozilla/rust/issues/2775 is fixed.
+	// (Tried making subjects mutable but that lead to illegal borrows all over the place).
+	let mut keys = ~[];
+	for map.each_key
+	|key|
+	{
+		vec::push(&mut keys, key);
+	};
+	
+	for vec::each(keys)
+	|key|
+	{
+		map.remove(*key);
+	};
+}
+
+// Like remove_keys except for maps keyed by term ids.
+priv fn remove_ids<T: Copy>(map: HashMap<TermId, T>)
+{
+	let mut keys = ~[];
+	for map.each_key
+	|key|
+	{
+		vec::push(&mut keys, key);
+	};
+	
+	for keys.each
+	|key|
+	{
+		map.remove(*key);
+	};
+}
+
+// Calls blk with the subject and entry of each triple matching the (expanded) arguments
+// using the most selective index available.
+priv fn each_matching_entry(store: &Store, subject: &option::Option<~str>, predicate: &option::Option<~str>, object: &option::Option<@Object>, blk: fn (TermId, &TermEntry) -> bool)
+{
+	// Names which are not in the dictionary cannot match anything.
+	let subject_id = subject.map(|s| {store.terms.find_name(*s)});
+	let predicate_id = predicate.map(|p| {store.terms.find_name(*p)});
+	if subject_id == option::Some(option::None) || predicate_id == option::Some(option::None)
+	{
+		return;
+	}
+	let subject_id = subject_id.map(|id| {id.get()});
+	let predicate_id = predicate_id.map(|id| {id.get()});
+	
+	// Literals have to be compared with same_term but IRIs and blanks can use their ids.
+	let object_id = match *object
+	{
+		option::Some(o) if is_indexed_object(o) =>
+		{
+			match store.terms.find(o)
+			{
+				option::Some(id) => option::Some(id),
+				option::None => return,
+			}
+		}
+		_ =>
+		{
+			option::None
+		}
+	};
+	let matches = |entry: &TermEntry|
+	{
+		let predicate_matches = match predicate_id
+		{
+			option::Some(id) => entry.predicate == id,
+			option::None => true,
+		};
+		let object_matches = match (object_id, *object)
+		{
+			(option::Some(id), _) => entry.object == id,
+			(option::None, option::Some(o)) => same_term(store.terms.get(entry.object), o),
+			(option::None, option::None) => true,
+		};
+		predicate_matches && object_matches
+	};
+	
+	match (subject_id, predicate_id, object_id)
+	{
+		(option::Some(subject), _, _) =>
+		{
+			for store.subjects.find(subject).each |entries|
+			{
+				for entries.each |entry|
+				{
+					if matches(entry) && !blk(subject, entry)
+					{
+						return;
+					}
+				}
+			}
+		}
+		(option::None, option::Some(predicate), _) =>
+		{
+			for store.predicates.find(predicate).each |items|
+			{
+				for items.each |item|
+				{
+					let entry = {predicate: predicate, object: item.object};
+					if matches(&entry) && !blk(item.subject, &entry)
+					{
+						return;
+					}
+				}
+			}
+		}
+		(option::None, option::None, option::Some(object)) =>
+		{
+			for store.objects.find(object).each |items|
+			{
+				for items.each |item|
+				{
+					let entry = {predicate: item.predicate, object: object};
+					if !blk(item.subject, &entry)
+					{
+						return;
+					}
+				}
+			}
+		}
+		(option::None, option::None, option::None) =>
+		{
+			for store.subjects.each |subject, entries|
+			{
+				for entries.each |entry|
+				{
+					if matches(entry) && !blk(subject, entry)
+					{
+						return;
+					}
+				}
+			}
+		}
+	}
+}
+
+// Returns the values and names of the nodes of the list starting at head.
+priv fn walk_list(store: &Store, head: @Object) -> result::Result<(~[@Object], ~[~str]), ~str>
+{
+	let mut items = ~[];
+	let mut nodes = ~[];
+	let visited = HashMap();
+	let mut node = head;
+	loop
+	{
+		let name = match *node
+		{
+			_ if is_nil(node) =>
+			{
+				return result::Ok((items, nodes));
+			}
+			IriValue(ref name) | BlankValue(ref name) =>
+			{
+				copy *name
+			}
+			_ =>
+			{
+				return result::Err(fmt!("Expected a list node but found %s.", node.to_str()));
+			}
+		};
+		if visited.contains_key(@copy name)
+		{
+			return result::Err(fmt!("List cycles back to %s.", name));
+		}
+		visited.insert(@copy name, ());
+		
+		// add_list ends lists with a node which has only an rdf:rest.
+		let first = store.find_objects(name, ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#first");
+		let rest = store.find_objects(name, ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#rest");
+		if first.len() > 1 || rest.len() != 1
+		{
+			return result::Err(fmt!("List node %s has %? rdf:first and %? rdf:rest predicates.", name, first.len(), rest.len()));
+		}
+		if first.is_empty() && !is_nil(rest[0])
+		{
+			return result::Err(fmt!("List node %s is missing rdf:first.", name));
+		}
+		
+		if first.len() == 1
+		{
+			vec::push(&mut items, first[0]);
+		}
+		vec::push(&mut nodes, name);
+		node = rest[0];
+	}
+}
+
+priv fn is_nil(object: @Object) -> bool
+{
+	match *object
+	{
+		IriValue(ref iri) => *iri == ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#nil",
+		_ => false,
+	}
+}
+
+// Returns true if the (expanded) name is the object of a triple.
+priv fn is_object(store: &Store, name: &str) -> bool
+{
+	match store.terms.find_name(name)
+	{
+		option::Some(id) => store.objects.contains_key(id),
+		option::None => false,
+	}
+}
+
+// Removes a blank node (and the blank nodes only it refers to) if nothing refers to it.
+priv fn remove_orphan(store: &Store, object: @Object)
+{
+	match *object
+	{
+		BlankValue(ref name) if !is_object(store, *name) =>
+		{
+			let mut children = ~[];
+			for store.find_triples(option::Some(*name), option::None, option::None) |triple|
+			{
+				vec::push(&mut children, triple.object);
+			}
+			
+			store.remove_subject(*name);
+			for children.each |child|
+			{
+				remove_orphan(store, *child);
+			}
+		}
+		_ =>
+		{
+		}
+	}
+}
+
+priv fn entry_matches(entry: &Entry, predicate: &option::Option<~str>, object: &option::Option<@Object>) -> bool
+{
+	let predicate_matches = match *predicate
+	{
+		option::Some(ref p) => entry.predicate == *p,
+		option::None => true,
+	};
+	let object_matches = match *object
+	{
+		option::Some(o) => same_term(entry.object, o),
+		option::None => true,
+	};
+	predicate_matches && object_matches
+}
+
 priv fn make_triple_blank(store: &Store, subject: &str, predicate: &str, value: &str) -> Triple
 {
 	{
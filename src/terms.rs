@@ -0,0 +1,164 @@
+//! Dictionary used by stores to encode terms as integers.
+
+/// Index of a term within a store's TermDictionary.
+pub type TermId = uint;
+
+/// Maps terms (IRIs, blank nodes, and literals) to integer ids and back.
+///
+/// Each distinct term is stored once so stores which use the same terms over and over
+/// need much less memory and terms can be compared by comparing their ids. Note that
+/// literals are only considered the same if they are identical (e.g. "Ned"@en and
+/// "Ned"@EN are different terms).
+///
+/// Terms are never removed individually: they stay in the dictionary after the last
+/// triple using them is removed until clear is called or the store is compacted with
+/// Store::compact_terms.
+pub struct TermDictionary
+{
+	pub ids: HashMap<@~str, TermId>,		// keys are from term_dictionary_key
+	pub terms: DVec<@Object>,
+}
+
+pub fn TermDictionary() -> TermDictionary
+{
+	TermDictionary {ids: HashMap(), terms: DVec()}
+}
+
+pub impl TermDictionary
+{
+	/// Returns the id for the term adding it to the dictionary if it is not already present.
+	fn intern(&self, term: @Object) -> TermId
+	{
+		let key = @term_dictionary_key(term);
+		match self.ids.find(key)
+		{
+			option::Some(id) =>
+			{
+				id
+			}
+			option::None =>
+			{
+				let id = self.terms.len();
+				self.terms.push(term);
+				self.ids.insert(key, id);
+				id
+			}
+		}
+	}
+	
+	/// Like intern except that name is an IRI or blank node label (e.g. a subject or predicate).
+	fn intern_name(&self, name: &str) -> TermId
+	{
+		match self.find_name(name)
+		{
+			option::Some(id) => id,
+			option::None => self.intern(name_to_term(name)),
+		}
+	}
+	
+	/// Returns the id for the term or None if it is not in the dictionary.
+	fn find(&self, term: &Object) -> option::Option<TermId>
+	{
+		self.ids.find(@term_dictionary_key(term))
+	}
+	
+	/// Like find except that name is an IRI or blank node label.
+	fn find_name(&self, name: &str) -> option::Option<TermId>
+	{
+		let key = if name.starts_with("_:") {name.to_owned()} else {fmt!("<%s>", name)};
+		self.ids.find(@key)
+	}
+	
+	/// Returns the term with the id.
+	fn get(&self, id: TermId) -> @Object
+	{
+		self.terms.get_elt(id)
+	}
+	
+	/// Returns the IRI or blank node label for the id.
+	fn get_name(&self, id: TermId) -> ~str
+	{
+		let term = self.terms.get_elt(id);
+		match *term
+		{
+			IriValue(ref name) | BlankValue(ref name) =>
+			{
+				copy *name
+			}
+			_ =>
+			{
+				fail fmt!("Expected an IRI or blank node but found %s", term.to_str());
+			}
+		}
+	}
+	
+	/// Removes all of the terms from the dictionary.
+	fn clear(&self)
+	{
+		remove_keys(self.ids);
+		self.terms.set(~[]);
+	}
+}
+
+// ---- Private Functions -----------------------------------------------------
+// Returns a string which uniquely identifies the term. Literal keys start with a letter
+// for the variant so they won't collide with IRIs or blank nodes and variable length
+// parts other than the last are prefixed with their length.
+priv fn term_dictionary_key(term: &Object) -> ~str
+{
+	match *term
+	{
+		IriValue(ref iri) =>
+		{
+			fmt!("<%s>", *iri)
+		}
+		BlankValue(ref name) =>
+		{
+			copy *name
+		}
+		BoolValue(value) =>
+		{
+			if value {~"btrue"} else {~"bfalse"}
+		}
+		IntValue(value) =>
+		{
+			fmt!("i%?", value)
+		}
+		FloatValue(value) =>
+		{
+			// Use the bits so that values which print the same aren't merged.
+			let bits: u64 = unsafe {cast::transmute(value)};
+			fmt!("f%?", bits)
+		}
+		DateTimeValue(ref value) =>
+		{
+			let time = unsafe {value.to_timespec()};
+			fmt!("d%? %? %? %s", time.sec, time.nsec, value.tm_gmtoff, value.tm_zone)
+		}
+		StringValue(ref value, ref lang) =>
+		{
+			fmt!("s%u:%s%s", str::len(*lang), *lang, *value)
+		}
+		TypedValue(ref value, ref kind) =>
+		{
+			fmt!("t%u:%s%s", str::len(*kind), *kind, *value)
+		}
+		InvalidValue(ref value, ref kind) =>
+		{
+			fmt!("v%u:%s%s", str::len(*kind), *kind, *value)
+		}
+		UnboundValue =>
+		{
+			~"u"
+		}
+		ErrorValue(ref err) =>
+		{
+			fmt!("e%s", *err)
+		}
+	}
+}
+
+priv fn name_to_term(name: &str) -> @Object
+{
+	if name.starts_with("_:") {@BlankValue(name.to_owned())} else {@IriValue(name.to_owned())}
+}
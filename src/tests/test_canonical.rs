@@ -0,0 +1,66 @@
+use tests::test_data::*;
+use tests::test_helpers::*;
+
+fn turtle_store(text: &str) -> Store
+{
+	let store = Store(~[], &HashMap());
+	load_turtle(&store, text).get();
+	store
+}
+
+#[test]
+fn canonical_ntriples()
+{
+	let store = turtle_store("_:x <http://example.org/name> \"Ned\"@EN . _:x <http://example.org/knows> <http://example.org/jon> .");
+	let expected = ~"_:c14n0 <http://example.org/knows> <http://example.org/jon> .\n_:c14n0 <http://example.org/name> \"Ned\"@en .\n";
+	assert check_strs(store_to_canonical_ntriples(&store), expected);
+	
+	// Duplicate triples are only written once.
+	store.add_triple(~[], {subject: ~"_:x", predicate: ~"http://example.org/knows", object: @IriValue(~"http://example.org/jon")});
+	assert check_strs(store_to_canonical_ntriples(&store), expected);
+}
+
+#[test]
+fn canonical_hash()
+{
+	// Same triples as got_cast3 but the blank nodes are numbered differently.
+	let lhs = got_cast3();
+	let rhs = got_cast3();
+	rhs.clear();
+	rhs.next_blank = 7;
+	rhs.add_aggregate(~"got:Jon_Snow", ~"v:org", ~"jon-org", ~[
+		(~"v:organisation-name", @StringValue(~"Night's Watch", ~"")),
+		(~"v:organisation-unit", @StringValue(~"Stewards", ~""))
+	]);
+	rhs.add_aggregate(~"got:Eddard_Stark", ~"v:org", ~"ned-org", ~[
+		(~"v:organisation-name", @StringValue(~"Small Council", ~"")),
+		(~"v:organisation-unit", @StringValue(~"Hand", ~""))
+	]);
+	for lhs.each
+	|triple|
+	{
+		match *triple.object
+		{
+			BlankValue(_) => (),
+			_ if str::starts_with(triple.subject, "_:") => (),
+			_ => {rhs.add_triple(~[], copy *triple);}
+		}
+	};
+	assert check_strs(store_to_canonical_ntriples(&rhs), store_to_canonical_ntriples(&lhs));
+	assert check_strs(store_hash(&rhs), store_hash(&lhs));
+	
+	rhs.add_triple(~[], {subject: ~"got:Jon_Snow", predicate: ~"v:nickname", object: @StringValue(~"Bastard", ~"")});
+	assert store_hash(&rhs) != store_hash(&lhs);
+}
+
+#[test]
+fn canonical_cycles()
+{
+	// First degree hashes are the same for every blank node here.
+	let pairs = turtle_store("_:a <http://example.org/p> _:b . _:b <http://example.org/p> _:a . _:c <http://example.org/p> _:d . _:d <http://example.org/p> _:c .");
+	let pairs2 = turtle_store("_:w <http://example.org/p> _:x . _:y <http://example.org/p> _:z . _:z <http://example.org/p> _:y . _:x <http://example.org/p> _:w .");
+	let square = turtle_store("_:a <http://example.org/p> _:b . _:b <http://example.org/p> _:c . _:c <http://example.org/p> _:d . _:d <http://example.org/p> _:a .");
+	assert check_strs(store_to_canonical_ntriples(&pairs2), store_to_canonical_ntriples(&pairs));
+	assert store_hash(&pairs) == store_hash(&pairs2);
+	assert store_hash(&pairs) != store_hash(&square);
+}
@@ -0,0 +1,162 @@
+use tests::test_helpers::*;
+
+fn triples(store: &Store) -> ~[Triple]
+{
+	let mut actual = ~[];
+	for store.each
+	|triple|
+	{
+		vec::push(&mut actual, copy *triple);
+	};
+	actual
+}
+
+fn got_dataset() -> Dataset
+{
+	let dataset = Dataset(~[Namespace {prefix: ~"got", path: ~"http://awoiaf.westeros.org/index.php/"}], &HashMap());
+	dataset.add(~"", ~"got:Eddard_Stark", ~[
+		(~"got:name", @StringValue(~"Eddard Stark", ~"")),
+	]);
+	dataset.add(~"got:book1", ~"got:Eddard_Stark", ~[
+		(~"got:status", @StringValue(~"alive", ~"")),
+	]);
+	dataset.add(~"got:book2", ~"got:Eddard_Stark", ~[
+		(~"got:status", @StringValue(~"dead", ~"")),
+	]);
+	dataset
+}
+
+// Returns the number of different subjects used by all the graphs in the dataset.
+fn distinct_subjects(dataset: &Dataset) -> uint
+{
+	let subjects = HashMap();
+	for triples(dataset.default_graph).each |triple|
+	{
+		subjects.insert(@copy triple.subject, ());
+	}
+	for dataset.get_graph_names().each |name|
+	{
+		for triples(dataset.get_graph(*name).get()).each |triple|
+		{
+			subjects.insert(@copy triple.subject, ());
+		}
+	}
+	subjects.size()
+}
+
+#[test]
+fn dataset_graphs()
+{
+	let dataset = got_dataset();
+	assert dataset.get_graph_names() == ~[~"http://awoiaf.westeros.org/index.php/book1", ~"http://awoiaf.westeros.org/index.php/book2"];
+	assert dataset.find_object(~"", ~"got:Eddard_Stark", ~"got:status").is_none();
+	assert dataset.find_object(~"got:book1", ~"got:Eddard_Stark", ~"got:status") == option::Some(@StringValue(~"alive", ~""));
+	assert dataset.find_objects(~"got:book2", ~"got:Eddard_Stark", ~"got:status") == ~[@StringValue(~"dead", ~"")];
+	assert dataset.find_objects(~"got:book3", ~"got:Eddard_Stark", ~"got:status").is_empty();
+	assert dataset.get_graph(~"got:book3").is_none();
+	
+	dataset.replace_triple(~"got:book1", ~[], {subject: ~"got:Eddard_Stark", predicate: ~"got:status", object: @StringValue(~"imprisoned", ~"")});
+	assert dataset.find_object(~"got:book1", ~"got:Eddard_Stark", ~"got:status") == option::Some(@StringValue(~"imprisoned", ~""));
+	
+	assert dataset.remove_subject(~"got:book2", ~"got:Eddard_Stark") == 1;
+	assert dataset.remove_subject(~"got:book3", ~"got:Eddard_Stark") == 0;
+	assert dataset.get_graph(~"got:book2").is_some();
+	assert dataset.remove_graph(~"got:book2");
+	assert !dataset.remove_graph(~"got:book2");
+	
+	dataset.clear();
+	assert dataset.get_graph_names().is_empty();
+	assert triples(dataset.default_graph).is_empty();
+}
+
+#[test]
+fn nquads()
+{
+	let dataset = Dataset(~[], &HashMap());
+	let result = load_nquads_str(&dataset, "<http://example.org/x> <http://example.org/name> \"Ned\" .
+<http://example.org/x> <http://example.org/status> \"alive\" <http://example.org/book1> .
+_:b1 <http://example.org/name> \"Jon\" _:g .
+");
+	assert result.is_ok();
+	assert dataset.get_graph_names() == ~[~"_:g-1", ~"http://example.org/book1"];
+	assert check_triples(triples(dataset.default_graph), ~[
+		{subject: ~"http://example.org/x", predicate: ~"http://example.org/name", object: @StringValue(~"Ned", ~"")},
+	]);
+	assert check_triples(triples(dataset.get_graph(~"_:g-1").get()), ~[
+		{subject: ~"_:b1-0", predicate: ~"http://example.org/name", object: @StringValue(~"Jon", ~"")},
+	]);
+	
+	let result = load_nquads_str(&dataset, "<http://example.org/x> <http://example.org/name> \"Ned\" \"graph\" .");
+	assert check_strs(result.get_err(), ~"Expected '.' on line 1");
+	
+	let dataset = got_dataset();
+	assert check_strs(dataset_to_nquads(&dataset), ~"<http://awoiaf.westeros.org/index.php/Eddard_Stark> <http://awoiaf.westeros.org/index.php/name> \"Eddard Stark\" .
+<http://awoiaf.westeros.org/index.php/Eddard_Stark> <http://awoiaf.westeros.org/index.php/status> \"alive\" <http://awoiaf.westeros.org/index.php/book1> .
+<http://awoiaf.westeros.org/index.php/Eddard_Stark> <http://awoiaf.westeros.org/index.php/status> \"dead\" <http://awoiaf.westeros.org/index.php/book2> .
+");
+}
+
+#[test]
+fn trig()
+{
+	let dataset = Dataset(~[], &HashMap());
+	let result = load_trig(&dataset, "@prefix got: <http://awoiaf.westeros.org/index.php/> .
+got:Eddard_Stark got:name \"Eddard Stark\" .
+got:book1 { got:Eddard_Stark got:status \"alive\" . got:Jon_Snow got:status \"alive\" }
+GRAPH got:book2 { got:Eddard_Stark got:status \"dead\" . }
+{ got:Jon_Snow got:name \"Jon Snow\" }
+got:book3 { }
+");
+	assert result.is_ok();
+	assert dataset.get_graph_names() == ~[~"http://awoiaf.westeros.org/index.php/book1", ~"http://awoiaf.westeros.org/index.php/book2", ~"http://awoiaf.westeros.org/index.php/book3"];
+	assert triples(dataset.default_graph).len() == 2;
+	assert triples(dataset.get_graph(~"got:book1").get()).len() == 2;
+	assert dataset.find_object(~"got:book2", ~"got:Eddard_Stark", ~"got:status") == option::Some(@StringValue(~"dead", ~""));
+	assert triples(dataset.get_graph(~"got:book3").get()).is_empty();
+	
+	let dataset = got_dataset();
+	let expected = ~"@prefix got: <http://awoiaf.westeros.org/index.php/> .
+@prefix owl: <http://www.w3.org/2002/07/owl#> .
+@prefix rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#> .
+@prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+@prefix xsd: <http://www.w3.org/2001/XMLSchema#> .
+
+got:Eddard_Stark
+	got:name \"Eddard Stark\" .
+got:book1 {
+	got:Eddard_Stark
+		got:status \"alive\" .
+}
+
+#[test]
+fn trig_blank_scope()
+{
+	let dataset = Dataset(~[], &HashMap());
+	let result = load_trig(&dataset, "@prefix got: <http://awoiaf.westeros.org/index.php/> .
+[] got:name \"Eddard Stark\" .
+got:book1 { [] got:name \"Jon Snow\" . }
+got:book2 { [] got:name \"Arya Stark\" . }
+{ [] got:name \"Sansa Stark\" . }
+");
+	assert result.is_ok();
+	
+	// Blank nodes in different graphs are different nodes so they need different labels
+	// (otherwise they would be merged when the dataset is saved).
+	assert distinct_subjects(&dataset) == 4;
+	
+	let loaded = Dataset(~[], &HashMap());
+	assert load_nquads_str(&loaded, dataset_to_nquads(&dataset)).is_ok();
+	assert distinct_subjects(&loaded) == 4;
+}
+got:book2 {
+	got:Eddard_Stark
+		got:status \"dead\" .
+}
+";
+	assert check_strs(dataset_to_trig(&dataset), expected);
+	
+	let loaded = Dataset(~[], &HashMap());
+	assert load_trig(&loaded, expected).is_ok();
+	assert loaded.get_graph_names() == dataset.get_graph_names();
+	assert loaded.find_object(~"got:book2", ~"got:Eddard_Stark", ~"got:status") == option::Some(@StringValue(~"dead", ~""));
+}
@@ -147,6 +147,35 @@ pub fn check_eval(store: &Store, expr: ~str, expected: &Solution) -> bool
 	}
 }
 
+pub fn check_dataset_eval(dataset: &Dataset, expr: ~str, expected: &Solution) -> bool
+{
+	info!("----------------------------------------------------");
+	let expected = expected.sort();
+	match compile_dataset(expr)
+	{
+		result::Ok(selector) =>
+		{
+			match selector(dataset)
+			{
+				result::Ok(ref actual) =>
+				{
+					check_solution(actual, &expected)
+				}
+				result::Err(ref mesg) =>
+				{
+					io::stderr().write_line(fmt!("Eval error: %s", *mesg));
+					return false;
+				}
+			}
+		}
+		result::Err(ref mesg) =>
+		{
+			io::stderr().write_line(fmt!("Parse error: %s", *mesg));
+			return false;
+		}
+	}
+}
+
 pub fn check_solution(actual: &Solution, expected: &Solution) -> bool
 {
 	assert actual.bindings == expected.bindings;
@@ -275,3 +304,36 @@ fn print_failure(mesg: ~str, actual: &Solution, expected: &Solution)
 	io::stderr().write_line("Expected:");
 	print_result(expected);
 }
+
+// Returns the store's triples as sorted N-Triples lines (useful when the order of a
+// subject's entries doesn't matter).
+pub fn sorted_ntriples(store: &Store) -> ~str
+{
+	let mut lines = ~[];
+	for store.each
+	|triple|
+	{
+		vec::push(&mut lines, triple_to_ntriples(triple));
+	};
+	str::connect(std::sort::merge_sort(lines, |x, y| {*x <= *y}), "\n")
+}
+
+// Returns the number of entries in the predicate index for the (expanded) name.
+pub fn predicate_index_len(store: &Store, name: &str) -> uint
+{
+	match store.terms.find_name(name).chain(|id| {store.predicates.find(id)})
+	{
+		option::Some(items) => items.len(),
+		option::None => 0,
+	}
+}
+
+// Returns the number of entries in the object index for the (expanded) IRI or blank node.
+pub fn object_index_len(store: &Store, name: &str) -> uint
+{
+	match store.terms.find_name(name).chain(|id| {store.objects.find(id)})
+	{
+		option::Some(items) => items.len(),
+		option::None => 0,
+	}
+}
@@ -0,0 +1,96 @@
+use tests::test_data::*;
+
+fn turtle_store(text: &str) -> Store
+{
+	let store = Store(~[], &HashMap());
+	load_turtle(&store, text).get();
+	store
+}
+
+#[test]
+fn isomorphic_stores()
+{
+	// Same triples, but added in a different order so the blank nodes are numbered differently.
+	let lhs = got_cast3();
+	let rhs = Store(~[
+		Namespace {prefix: ~"got", path: ~"http://awoiaf.westeros.org/index.php/"},
+		Namespace {prefix: ~"v", path: ~"http://www.w3.org/2006/vcard/ns#"}
+		], &HashMap());
+	rhs.next_blank = 10;
+	rhs.add(~"got:Sandor_Clegane", ~[
+		(~"v:fn", @StringValue(~"Sandor Clegane", ~"")),
+		(~"v:nickname", @StringValue(~"The Hound", ~""))
+	]);
+	rhs.add_aggregate(~"got:Jon_Snow", ~"v:org", ~"jon-org", ~[
+		(~"v:organisation-name", @StringValue(~"Night's Watch", ~"")),
+		(~"v:organisation-unit", @StringValue(~"Stewards", ~""))
+	]);
+	rhs.add(~"got:Jon_Snow", ~[
+		(~"v:fn", @StringValue(~"Jon Snow", ~"")),
+		(~"v:nickname", @StringValue(~"Lord Snow", ~"")),
+		(~"v:pet", @StringValue(~"Ghost", ~""))
+	]);
+	rhs.add_aggregate(~"got:Eddard_Stark", ~"v:org", ~"ned-org", ~[
+		(~"v:organisation-name", @StringValue(~"Small Council", ~"")),
+		(~"v:organisation-unit", @StringValue(~"Hand", ~""))
+	]);
+	rhs.add(~"got:Eddard_Stark", ~[
+		(~"v:fn", @StringValue(~"Eddard Stark", ~"")),
+		(~"v:nickname", @StringValue(~"Ned", ~"")),
+		(~"v:honorific-prefix", @StringValue(~"Lord", ~""))
+	]);
+	assert lhs.is_isomorphic(&rhs);
+	assert rhs.is_isomorphic(&lhs);
+	
+	// Swapping the orgs changes the graph.
+	rhs.replace_triple(~[], {subject: ~"got:Eddard_Stark", predicate: ~"v:org", object: @BlankValue(~"_:jon-org-10")});
+	rhs.replace_triple(~[], {subject: ~"got:Jon_Snow", predicate: ~"v:org", object: @BlankValue(~"_:ned-org-11")});
+	assert !lhs.is_isomorphic(&rhs);
+}
+
+#[test]
+fn isomorphic_literals()
+{
+	let lhs = turtle_store("_:a <http://example.org/name> \"Ned\"@en .");
+	assert lhs.is_isomorphic(&turtle_store("_:b <http://example.org/name> \"Ned\"@EN ."));
+	assert !lhs.is_isomorphic(&turtle_store("_:b <http://example.org/name> \"Ned\" ."));
+	
+	let lhs = turtle_store("_:a <http://example.org/age> 35 .");
+	assert !lhs.is_isomorphic(&turtle_store("_:a <http://example.org/age> 35.0e0 ."));
+}
+
+#[test]
+fn isomorphic_cycles()
+{
+	// Refinement can't tell these blank nodes apart so the search has to.
+	let pairs = turtle_store("_:a <http://example.org/p> _:b . _:b <http://example.org/p> _:a . _:c <http://example.org/p> _:d . _:d <http://example.org/p> _:c .");
+	let pairs2 = turtle_store("_:w <http://example.org/p> _:x . _:y <http://example.org/p> _:z . _:z <http://example.org/p> _:y . _:x <http://example.org/p> _:w .");
+	let square = turtle_store("_:a <http://example.org/p> _:b . _:b <http://example.org/p> _:c . _:c <http://example.org/p> _:d . _:d <http://example.org/p> _:a .");
+	assert pairs.is_isomorphic(&pairs2);
+	assert !pairs.is_isomorphic(&square);
+	assert !square.is_isomorphic(&pairs);
+}
+
+#[test]
+fn isomorphic_solutions()
+{
+	let lhs = Solution {namespaces: ~[], bindings: ~[~"org", ~"name", ~"extra"], num_selected: 2, rows: ~[
+		~[@BlankValue(~"_:ned-org-0"), @StringValue(~"Small Council", ~""), @IntValue(1i64)],
+		~[@BlankValue(~"_:jon-org-1"), @StringValue(~"Night's Watch", ~""), @IntValue(2i64)],
+		~[@BlankValue(~"_:jon-org-1"), @UnboundValue, @IntValue(3i64)],
+	]};
+	let rhs = Solution {namespaces: ~[], bindings: ~[~"name", ~"org"], num_selected: 2, rows: ~[
+		~[@UnboundValue, @BlankValue(~"_:b")],
+		~[@StringValue(~"Small Council", ~""), @BlankValue(~"_:a")],
+		~[@StringValue(~"Night's Watch", ~""), @BlankValue(~"_:b")],
+	]};
+	assert lhs.is_isomorphic(&rhs);
+	
+	// The unbound row now refers to the other org.
+	let rhs = Solution {namespaces: ~[], bindings: ~[~"name", ~"org"], num_selected: 2, rows: ~[
+		~[@UnboundValue, @BlankValue(~"_:a")],
+		~[@StringValue(~"Small Council", ~""), @BlankValue(~"_:a")],
+		~[@StringValue(~"Night's Watch", ~""), @BlankValue(~"_:b")],
+	]};
+	assert !lhs.is_isomorphic(&rhs);
+}
@@ -0,0 +1,164 @@
+use tests::test_helpers::*;
+
+fn load(text: &str) -> Store
+{
+	let store = Store(~[], &HashMap());
+	match load_jsonld(&store, text)
+	{
+		result::Ok(()) =>
+		{
+			store
+		}
+		result::Err(ref mesg) =>
+		{
+			fail fmt!("JSON-LD error: %s", *mesg);
+		}
+	}
+}
+
+fn triples(store: &Store) -> ~[Triple]
+{
+	let mut actual = ~[];
+	for store.each
+	|triple|
+	{
+		vec::push(&mut actual, copy *triple);
+	};
+	actual
+}
+
+#[test]
+fn jsonld_expansion()
+{
+	let store = load("{
+	\"@context\": {
+		\"ex\": \"http://example.org/\",
+		\"name\": \"ex:name\",
+		\"knows\": {\"@id\": \"ex:knows\", \"@type\": \"@id\"},
+		\"cities\": {\"@id\": \"ex:cities\", \"@container\": \"@list\"},
+		\"@language\": \"en\"
+	},
+	\"@id\": \"ex:westeros\",
+	\"@type\": \"ex:Continent\",
+	\"name\": \"Westeros\",
+	\"knows\": \"ex:essos\",
+	\"ex:area\": {\"@value\": \"1.5\", \"@type\": \"http://www.w3.org/2001/XMLSchema#double\"},
+	\"ex:count\": 7,
+	\"ex:flag\": true,
+	\"cities\": [\"Lanisport\", \"Oldtown\"],
+	\"ex:ruler\": {\"@id\": \"_:r\", \"name\": {\"@value\": \"Robert\", \"@language\": \"fr\"}},
+	\"ignored\": \"not an IRI\"
+}");
+	
+	let expected = ~[
+		make_triple_uri(&store, ~"http://example.org/westeros", ~"rdf:type", ~"http://example.org/Continent"),
+		make_triple_blank(&store, ~"http://example.org/westeros", ~"http://example.org/cities", ~"list-0"),
+		{subject: ~"http://example.org/westeros", predicate: ~"http://example.org/area", object: @FloatValue(1.5f64)},
+		{subject: ~"http://example.org/westeros", predicate: ~"http://example.org/count", object: @IntValue(7i64)},
+		{subject: ~"http://example.org/westeros", predicate: ~"http://example.org/flag", object: @BoolValue(true)},
+		make_triple_blank(&store, ~"http://example.org/westeros", ~"http://example.org/ruler", ~"r-2"),
+		make_triple_uri(&store, ~"http://example.org/westeros", ~"http://example.org/knows", ~"http://example.org/essos"),
+		{subject: ~"http://example.org/westeros", predicate: ~"http://example.org/name", object: @StringValue(~"Westeros", ~"en")},
+		{subject: ~"_:list-0", predicate: ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#first", object: @StringValue(~"Lanisport", ~"en")},
+		make_triple_blank(&store, ~"_:list-0", ~"rdf:rest", ~"list-1"),
+		{subject: ~"_:list-1", predicate: ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#first", object: @StringValue(~"Oldtown", ~"en")},
+		make_triple_uri(&store, ~"_:list-1", ~"rdf:rest", ~"rdf:nil"),
+		{subject: ~"_:r-2", predicate: ~"http://example.org/name", object: @StringValue(~"Robert", ~"fr")},
+	];
+	assert check_triples(triples(&store), expected);
+}
+
+#[test]
+fn jsonld_graph()
+{
+	let store = load("{
+	\"@context\": {\"@vocab\": \"http://example.org/\", \"@base\": \"http://example.org/people/\"},
+	\"@graph\": [
+		{\"@id\": \"jon\", \"title\": \"Lord Commander\"},
+		{\"@id\": \"sam\", \"@type\": [\"Person\", \"Steward\"], \"friend\": {\"@id\": \"jon\"}}
+	]
+}");
+	
+	let expected = ~[
+		make_triple_str(&store, ~"http://example.org/people/jon", ~"http://example.org/title", ~"Lord Commander"),
+		make_triple_uri(&store, ~"http://example.org/people/sam", ~"rdf:type", ~"http://example.org/Person"),
+		make_triple_uri(&store, ~"http://example.org/people/sam", ~"rdf:type", ~"http://example.org/Steward"),
+		make_triple_uri(&store, ~"http://example.org/people/sam", ~"http://example.org/friend", ~"http://example.org/people/jon"),
+	];
+	assert check_triples(triples(&store), expected);
+}
+
+#[test]
+fn jsonld_errors()
+{
+	let store = Store(~[], &HashMap());
+	assert load_jsonld(&store, "{\"@id\": ").is_err();
+	assert check_strs(load_jsonld(&store, "{\"@context\": \"http://example.org/context.jsonld\"}").get_err(), ~"Remote contexts are not supported (http://example.org/context.jsonld).");
+	assert check_strs(load_jsonld(&store, "42").get_err(), ~"JSON-LD documents must be an object or an array.");
+}
+
+#[test]
+fn jsonld_writer()
+{
+	let store = Store(~[Namespace {prefix: ~"ex", path: ~"http://example.org/"}], &HashMap());
+	store.add(~"ex:westeros", ~[
+		(~"rdf:type", @IriValue(~"ex:Continent")),
+		(~"ex:name", @StringValue(~"Westeros", ~"en")),
+		(~"ex:area", @FloatValue(1.5f64)),
+		(~"ex:count", @IntValue(7i64)),
+	]);
+	store.add_list(~"ex:westeros", ~"ex:cities", ~[@StringValue(~"Lanisport", ~"")]);
+	
+	let actual = store_to_jsonld(&store);
+	let expected = ~"{
+	\"@context\": {
+		\"ex\": \"http://example.org/\",
+		\"owl\": \"http://www.w3.org/2002/07/owl#\",
+		\"rdf\": \"http://www.w3.org/1999/02/22-rdf-syntax-ns#\",
+		\"rdfs\": \"http://www.w3.org/2000/01/rdf-schema#\",
+		\"xsd\": \"http://www.w3.org/2001/XMLSchema#\"
+	},
+	\"@graph\": [
+		{
+			\"@id\": \"ex:westeros\",
+			\"@type\": \"ex:Continent\",
+			\"ex:area\": {\"@value\": \"1.5\", \"@type\": \"xsd:double\"},
+			\"ex:cities\": {\"@list\": [\"Lanisport\"]},
+			\"ex:count\": 7,
+			\"ex:name\": {\"@value\": \"Westeros\", \"@language\": \"en\"}
+		}
+	]
+}
+";
+	assert check_strs(actual, expected);
+}
+
+#[test]
+fn jsonld_round_trip()
+{
+	let store = Store(~[Namespace {prefix: ~"ex", path: ~"http://example.org/"}], &HashMap());
+	store.add(~"ex:westeros", ~[
+		(~"ex:name", @StringValue(~"Westeros \"the\" continent", ~"")),
+		(~"ex:area", @FloatValue(1.5f64)),
+		(~"ex:ruler", @BlankValue(~"_:robert")),
+	]);
+	store.add(~"_:robert", ~[(~"ex:name", @StringValue(~"Robert", ~"en"))]);
+	store.add_list(~"ex:westeros", ~"ex:cities", ~[@StringValue(~"Lanisport", ~""), @IntValue(3i64)]);
+	
+	let loaded = Store(~[], &HashMap());
+	assert load_jsonld(&loaded, store_to_jsonld(&store)).is_ok();
+	
+	assert loaded.find_object(~"http://example.org/westeros", ~"http://example.org/name") == option::Some(@StringValue(~"Westeros \"the\" continent", ~""));
+	assert loaded.find_object(~"http://example.org/westeros", ~"http://example.org/area") == option::Some(@FloatValue(1.5f64));
+	match loaded.find_object(~"http://example.org/westeros", ~"http://example.org/ruler")
+	{
+		option::Some(@BlankValue(ref name)) =>
+		{
+			assert loaded.find_object(*name, ~"http://example.org/name") == option::Some(@StringValue(~"Robert", ~"en"));
+		}
+		_ =>
+		{
+			fail ~"expected a blank ruler";
+		}
+	}
+}
@@ -0,0 +1,85 @@
+use tests::test_helpers::*;
+
+fn triples(store: &Store) -> ~[Triple]
+{
+	let mut actual = ~[];
+	for store.each
+	|triple|
+	{
+		vec::push(&mut actual, copy *triple);
+	};
+	actual
+}
+
+#[test]
+fn ntriples_load()
+{
+	let store = Store(~[], &HashMap());
+	let result = load_ntriples_str(&store, "# a comment
+<http://example.org/x> <http://example.org/name> \"Eddard \\\"Ned\\\" Stark\" .
+
+<http://example.org/x> <http://example.org/nick> \"Ned\"@en .
+<http://example.org/x> <http://example.org/age> \"35\"^^<http://www.w3.org/2001/XMLSchema#integer> .	# trailing comment
+<http://example.org/x> <http://example.org/knows> _:b1 .
+_:b1 <http://example.org/name> \"caf\\u00E9\" .
+");
+	assert result.is_ok();
+	
+	let expected = ~[
+		make_triple_str(&store, ~"http://example.org/x", ~"http://example.org/name", ~"Eddard \"Ned\" Stark"),
+		{subject: ~"http://example.org/x", predicate: ~"http://example.org/nick", object: @StringValue(~"Ned", ~"en")},
+		{subject: ~"http://example.org/x", predicate: ~"http://example.org/age", object: @IntValue(35i64)},
+		make_triple_blank(&store, ~"http://example.org/x", ~"http://example.org/knows", ~"b1-0"),
+		make_triple_str(&store, ~"_:b1-0", ~"http://example.org/name", ~"café"),
+	];
+	assert check_triples(triples(&store), expected);
+}
+
+#[test]
+fn ntriples_errors()
+{
+	let store = Store(~[], &HashMap());
+	
+	let result = load_ntriples_str(&store, "<http://example.org/x> <http://example.org/y> <http://example.org/z> .
+<http://example.org/x> <http://example.org/y> .
+");
+	assert result.is_err();
+	assert check_strs(result.get_err(), ~"Expected an IRI, blank node, or literal for the object on line 2");
+	assert triples(&store).len() == 1;
+	
+	let result = load_ntriples_str(&store, "<http://example.org/x> <http://example.org/y> \"z\"");
+	assert check_strs(result.get_err(), ~"Expected '.' on line 1");
+}
+
+#[test]
+fn ntriples_writer()
+{
+	let store = Store(~[], &HashMap());
+	store.add(~"http://example.org/x", ~[
+		(~"http://example.org/motto", @StringValue(~"Winter is\n\"coming\"", ~"en")),
+	]);
+	assert check_strs(store_to_ntriples(&store), ~"<http://example.org/x> <http://example.org/motto> \"Winter is\\n\\\"coming\\\"\"@en .\n");
+	
+	assert check_strs(object_to_ntriples(&IntValue(3i64)), ~"\"3\"^^<http://www.w3.org/2001/XMLSchema#integer>");
+	assert check_strs(object_to_ntriples(&IriValue(~"http://example.org/a b")), ~"<http://example.org/a\\u0020b>");
+	assert check_strs(object_to_ntriples(&BlankValue(~"_:list-0")), ~"_:list-0");
+}
+
+#[test]
+fn ntriples_round_trip()
+{
+	let store = Store(~[], &HashMap());
+	store.add(~"http://example.org/x", ~[
+		(~"http://example.org/name", @StringValue(~"tab\there", ~"")),
+		(~"http://example.org/flag", @BoolValue(true)),
+		(~"http://example.org/area", @FloatValue(1.5f64)),
+	]);
+	store.add_list(~"http://example.org/x", ~"http://example.org/cities", ~[@StringValue(~"Lanisport", ~"")]);
+	
+	let loaded = Store(~[], &HashMap());
+	assert load_ntriples_str(&loaded, store_to_ntriples(&store)).is_ok();
+	assert triples(&loaded).len() == triples(&store).len();
+	assert loaded.find_object(~"http://example.org/x", ~"http://example.org/name") == option::Some(@StringValue(~"tab\there", ~""));
+	assert loaded.find_object(~"http://example.org/x", ~"http://example.org/flag") == option::Some(@BoolValue(true));
+	assert loaded.find_object(~"http://example.org/x", ~"http://example.org/area") == option::Some(@FloatValue(1.5f64));
+}
@@ -0,0 +1,82 @@
+use tests::test_data::*;
+use tests::test_helpers::*;
+
+#[test]
+fn diff_and_apply()
+{
+	let old = got_cast3();
+	let new = got_cast3();
+	new.replace_triple(~[], {subject: ~"got:Jon_Snow", predicate: ~"v:nickname", object: @StringValue(~"Bastard", ~"")});
+	new.remove_subject(~"got:Sandor_Clegane");
+	new.add_aggregate(~"got:Sandor_Clegane", ~"v:org", ~"sandor-org", ~[
+		(~"v:organisation-name", @StringValue(~"Kingsguard", ~""))
+	]);
+	
+	let patch = diff_stores(&old, &new);
+	assert patch.removed.len() == 3;
+	assert patch.added.len() == 3;
+	
+	// Send the patch through the text format before applying it.
+	let patch = load_patch_str(patch_to_str(&patch)).get();
+	old.apply_patch(&patch).get();
+	assert old.is_isomorphic(&new);
+}
+
+#[test]
+fn diff_ignores_blank_labels()
+{
+	let old = got_cast3();
+	let new = Store(~[], &HashMap());
+	new.next_blank = 20;
+	load_ntriples_str(&new, store_to_ntriples(&old)).get();
+	
+	let patch = diff_stores(&old, &new);
+	assert patch.removed.is_empty();
+	assert patch.added.is_empty();
+}
+
+#[test]
+fn apply_missing_triple()
+{
+	let store = got_cast1();
+	let expected = sorted_ntriples(&store);
+	let text = ~"D <http://awoiaf.westeros.org/index.php/Eddard_Stark> <http://www.w3.org/2006/vcard/ns#fn> \"Eddard Stark\" .
+A <http://awoiaf.westeros.org/index.php/Eddard_Stark> <http://www.w3.org/2006/vcard/ns#nickname> \"Lord Stark\" .
+D <http://awoiaf.westeros.org/index.php/Eddard_Stark> <http://www.w3.org/2006/vcard/ns#nickname> \"The Hound\" .
+";
+	let patch = load_patch_str(text).get();
+	assert patch.removed.len() == 2;
+	
+	match store.apply_patch(&patch)
+	{
+		result::Ok(()) =>
+		{
+			fail ~"apply_patch should have failed";
+		}
+		result::Err(ref err) =>
+		{
+			assert str::contains(*err, "The Hound");
+		}
+	}
+	assert check_strs(sorted_ntriples(&store), expected);
+	
+	assert load_patch_str(~"X <http://example.org/a> <http://example.org/b> <http://example.org/c> .").is_err();
+}
+
+#[test]
+fn patch_container_labels()
+{
+	let old = Store(~[], &HashMap());
+	old.add_seq(~"http://example.org/kings", ~[@IriValue(~"got:Aegon_I"), @IriValue(~"got:Aenys_I")]);
+	let new = Store(~[], &HashMap());
+	new.add_seq(~"http://example.org/kings", ~[@IriValue(~"got:Aegon_I"), @IriValue(~"got:Maegor_I")]);
+	
+	// The container is named after the subject so its label includes ':', '/', and '.'.
+	let patch = diff_stores(&old, &new);
+	assert patch.removed.len() == 3;
+	assert patch.removed.any(|t| {str::contains(t.subject, "example.org")});
+	
+	let patch = load_patch_str(patch_to_str(&patch)).get();
+	old.apply_patch(&patch).get();
+	assert old.is_isomorphic(&new);
+}
@@ -0,0 +1,199 @@
+use io::WriterUtil;
+use tests::test_helpers::*;
+
+// Returns an empty directory to store the files in.
+fn test_dir(name: &str) -> Path
+{
+	let dir = os::tmpdir().push(~"rrdf-" + name);
+	if os::path_is_dir(&dir)
+	{
+		for os::list_dir(&dir).each |file|
+		{
+			os::remove_file(&dir.push(*file));
+		}
+	}
+	dir
+}
+
+fn got_namespaces() -> ~[Namespace]
+{
+	~[
+		Namespace {prefix: ~"got", path: ~"http://awoiaf.westeros.org/index.php/"},
+		Namespace {prefix: ~"v", path: ~"http://www.w3.org/2006/vcard/ns#"}
+	]
+}
+
+#[test]
+fn persistent_replay()
+{
+	let dir = test_dir("replay");
+	let pstore = open_persistent_store(&dir, got_namespaces(), &HashMap(), {sync: SyncAlways, checkpoint_interval: 0}).get();
+	pstore.store.add(~"got:Eddard_Stark", ~[
+		(~"v:fn", @StringValue(~"Eddard Stark", ~"")),
+		(~"v:nickname", @StringValue(~"Ned", ~"")),
+		(~"v:honorific-prefix", @StringValue(~"Lord", ~""))
+	]);
+	pstore.store.add_list(~"got:Eddard_Stark", ~"v:child", ~[@IriValue(~"got:Robb_Stark"), @IriValue(~"got:Sansa_Stark")]);
+	pstore.store.replace_triple(~[], {subject: ~"got:Eddard_Stark", predicate: ~"v:nickname", object: @StringValue(~"Lord Stark", ~"en")});
+	pstore.store.remove_triple(~[], {subject: ~"got:Eddard_Stark", predicate: ~"v:honorific-prefix", object: @StringValue(~"Lord", ~"")});
+	add_namespace(pstore.store, Namespace {prefix: ~"ex", path: ~"http://example.org/"});
+	pstore.store.add_triple(~[], {subject: ~"ex:winterfell", predicate: ~"ex:ruler", object: @IriValue(~"got:Eddard_Stark")});
+	
+	// Open a second copy without closing the first (as if the process had crashed).
+	let replayed = open_persistent_store(&dir, ~[], &HashMap(), {sync: SyncAlways, checkpoint_interval: 0}).get();
+	assert check_strs(sorted_ntriples(replayed.store), sorted_ntriples(pstore.store));
+	assert replayed.store.next_blank == pstore.store.next_blank;
+	assert replayed.store.namespaces.len() == pstore.store.namespaces.len();
+	assert *replayed.store.find_object(~"got:Eddard_Stark", ~"v:nickname").get() == StringValue(~"Lord Stark", ~"en");
+	assert replayed.store.find_object(~"ex:winterfell", ~"ex:ruler").is_some();
+	
+	replayed.close();
+	pstore.close();
+}
+
+#[test]
+fn persistent_checkpoint()
+{
+	let dir = test_dir("checkpoint");
+	let options = {sync: SyncNever, checkpoint_interval: 3};
+	let pstore = open_persistent_store(&dir, got_namespaces(), &HashMap(), options).get();
+	for uint::range(0, 7) |i|
+	{
+		pstore.store.add_triple(~[], {subject: fmt!("got:Person%?", i), predicate: ~"v:nickname", object: @IntValue(i as i64)});
+	}
+	assert os::path_exists(&dir.push("snapshot.2"));
+	assert os::path_exists(&dir.push("log.2"));
+	assert !os::path_exists(&dir.push("snapshot.1"));
+	assert !os::path_exists(&dir.push("log.1"));
+	pstore.close();
+	
+	let pstore = open_persistent_store(&dir, ~[], &HashMap(), options).get();
+	assert pstore.store.subjects.size() == 7;
+	assert *pstore.store.find_object(~"got:Person6", ~"v:nickname").get() == IntValue(6i64);
+	pstore.store.clear();
+	pstore.close();
+	
+	let pstore = open_persistent_store(&dir, ~[], &HashMap(), options).get();
+	assert pstore.store.subjects.size() == 0;
+	pstore.close();
+}
+
+#[test]
+fn persistent_blank_labels()
+{
+	let dir = test_dir("blank-labels");
+	let options = {sync: SyncAlways, checkpoint_interval: 0};
+	let pstore = open_persistent_store(&dir, ~[], &HashMap(), options).get();
+	pstore.store.add_list(~"http://example.org/westeros", ~"http://example.org/cities", ~[@StringValue(~"Lanisport", ~""), @StringValue(~"Oldtown", ~"")]);
+	pstore.checkpoint().get();
+	
+	// The list nodes are named after the predicate so their labels include ':', '/', and '.'.
+	let name = match *pstore.store.find_object(~"http://example.org/westeros", ~"http://example.org/cities").get()
+	{
+		BlankValue(copy name) => name,
+		_ => fail ~"Expected a blank node",
+	};
+	assert pstore.store.remove_triple(~[], {subject: copy name, predicate: ~"rdf:first", object: @StringValue(~"Lanisport", ~"")}) == 1;
+	pstore.store.add_triple(~[], {subject: copy name, predicate: ~"rdf:first", object: @StringValue(~"Gulltown", ~"")});
+	
+	let replayed = open_persistent_store(&dir, ~[], &HashMap(), options).get();
+	assert replayed.store.find_object(name, ~"rdf:first") == option::Some(@StringValue(~"Gulltown", ~""));
+	assert check_strs(sorted_ntriples(replayed.store), sorted_ntriples(pstore.store));
+	
+	replayed.close();
+	pstore.close();
+}
+
+#[test]
+fn persistent_torn_record()
+{
+	let dir = test_dir("torn");
+	let options = {sync: SyncAlways, checkpoint_interval: 0};
+	let pstore = open_persistent_store(&dir, got_namespaces(), &HashMap(), options).get();
+	pstore.store.add(~"got:Eddard_Stark", ~[(~"v:nickname", @StringValue(~"Ned", ~""))]);
+	pstore.close();
+	
+	// Simulate a crash while a record was being written.
+	{
+		let writer = io::file_writer(&dir.push("log.0"), ~[io::Append]).get();
+		writer.write_str("A <http://awoiaf.westeros.org/index.php/Jon_Snow> <http://www.w3.org/2006/vcard/ns#nickname> \"Lord Sn");
+	}
+	
+	let pstore = open_persistent_store(&dir, ~[], &HashMap(), options).get();
+	pstore.store.add(~"got:Jon_Snow", ~[(~"v:pet", @StringValue(~"Ghost", ~""))]);
+	pstore.close();
+	
+	let pstore = open_persistent_store(&dir, ~[], &HashMap(), options).get();
+	assert pstore.store.find_object(~"got:Eddard_Stark", ~"v:nickname") == option::Some(@StringValue(~"Ned", ~""));
+	assert pstore.store.find_object(~"got:Jon_Snow", ~"v:pet") == option::Some(@StringValue(~"Ghost", ~""));
+	assert pstore.store.find_object(~"got:Jon_Snow", ~"v:nickname").is_none();
+	pstore.close();
+}
+
+#[test]
+fn persistent_transactions()
+{
+	let dir = test_dir("transactions");
+	let options = {sync: SyncAlways, checkpoint_interval: 0};
+	let pstore = open_persistent_store(&dir, got_namespaces(), &HashMap(), options).get();
+	pstore.store.add(~"got:Eddard_Stark", ~[(~"v:nickname", @StringValue(~"Ned", ~""))]);
+	
+	pstore.store.begin();
+	pstore.store.add(~"got:Jon_Snow", ~[(~"v:pet", @StringValue(~"Ghost", ~""))]);
+	pstore.store.clear();
+	pstore.store.rollback();
+	
+	pstore.store.begin();
+	pstore.store.add(~"got:Arya_Stark", ~[(~"v:pet", @StringValue(~"Nymeria", ~""))]);
+	pstore.store.commit();
+	
+	// Changes within a transaction are only logged when it is committed.
+	pstore.store.begin();
+	pstore.store.add_aggregate(~"got:Sansa_Stark", ~"v:pet", ~"direwolf", ~[(~"v:fn", @StringValue(~"Lady", ~""))]);
+	
+	let replayed = open_persistent_store(&dir, ~[], &HashMap(), options).get();
+	assert replayed.store.find_object(~"got:Eddard_Stark", ~"v:nickname").is_some();
+	assert replayed.store.find_object(~"got:Jon_Snow", ~"v:pet").is_none();
+	assert replayed.store.find_object(~"got:Arya_Stark", ~"v:pet").is_some();
+	assert replayed.store.find_object(~"got:Sansa_Stark", ~"v:pet").is_none();
+	assert replayed.store.next_blank == 0;
+	replayed.close();
+	
+	pstore.store.commit();
+	let replayed = open_persistent_store(&dir, ~[], &HashMap(), options).get();
+	assert check_strs(sorted_ntriples(replayed.store), sorted_ntriples(pstore.store));
+	assert replayed.store.next_blank == pstore.store.next_blank;
+	replayed.close();
+	pstore.close();
+}
+
+#[test]
+fn persistent_checkpoint_mutations()
+{
+	let dir = test_dir("checkpoint-mutations");
+	let options = {sync: SyncNever, checkpoint_interval: 1};
+	let pstore = open_persistent_store(&dir, got_namespaces(), &HashMap(), options).get();
+	pstore.store.add(~"got:Eddard_Stark", ~[
+		(~"v:fn", @StringValue(~"Eddard Stark", ~"")),
+		(~"v:nickname", @StringValue(~"Ned", ~"")),
+		(~"v:nickname", @StringValue(~"Lord Stark", ~""))
+	]);
+	
+	// Each removal checkpoints so the later removals must not be in the earlier snapshots.
+	assert pstore.store.remove_matching(option::Some(~"got:Eddard_Stark"), option::Some(~"v:nickname"), option::None) == 2;
+	
+	pstore.store.begin();
+	pstore.store.add(~"got:Jon_Snow", ~[(~"v:pet", @StringValue(~"Ghost", ~""))]);
+	assert pstore.checkpoint().is_err();
+	pstore.store.commit();
+	
+	// Close logs the namespaces.
+	add_namespace(pstore.store, Namespace {prefix: ~"my docs", path: ~"file:///My Documents/"});
+	pstore.close();
+	
+	let pstore = open_persistent_store(&dir, ~[], &HashMap(), options).get();
+	assert pstore.store.find_objects(~"got:Eddard_Stark", ~"v:nickname").is_empty();
+	assert pstore.store.find_object(~"got:Jon_Snow", ~"v:pet").is_some();
+	assert pstore.store.namespaces.contains(&Namespace {prefix: ~"my docs", path: ~"file:///My Documents/"});
+	pstore.close();
+}
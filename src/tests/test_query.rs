@@ -248,7 +248,7 @@ fn test_basic()
 #[test]
 fn test_bind()
 {
-	let context = QueryContext {namespaces: ~[], extensions: HashMap(), algebra: Group(~[]), order_by: ~[], distinct: false, limit: option::None, rng: rand::Rng(), timestamp: time::now()};
+	let context = QueryContext {namespaces: ~[], extensions: HashMap(), algebra: Group(~[]), order_by: ~[], distinct: false, limit: option::None, rng: rand::Rng(), timestamp: time::now(), from: ~[], from_named: ~[], named_graphs: HashMap()};
 	let bindings = ~[~"subject", ~"predicate", ~"value", ~"x"];
 	let solution = Solution {namespaces: ~[], bindings: copy bindings, num_selected: 3, rows: 
 		~[
@@ -300,7 +300,7 @@ fn test_bind()
 #[test]
 fn test_filter()
 {
-	let context = QueryContext {namespaces: ~[], extensions: HashMap(), algebra: Group(~[]), order_by: ~[], distinct: false, limit: option::None, rng: rand::Rng(), timestamp: time::now()};
+	let context = QueryContext {namespaces: ~[], extensions: HashMap(), algebra: Group(~[]), order_by: ~[], distinct: false, limit: option::None, rng: rand::Rng(), timestamp: time::now(), from: ~[], from_named: ~[], named_graphs: HashMap()};
 	let bindings = ~[~"subject", ~"predicate", ~"value"];
 	let solution = Solution {namespaces: ~[], bindings: copy bindings, num_selected: 3, rows: 
 		~[
@@ -328,7 +328,7 @@ fn test_group()
 	let store = got_cast3();
 	let p1 = @Basic(TriplePattern {subject: Variable(~"subject"), predicate: Constant(@IriValue(~"http://www.w3.org/2006/vcard/ns#nickname")), object: Variable(~"name")});
 	let p2 = @Basic(TriplePattern {subject: Variable(~"subject"), predicate: Constant(@IriValue(~"http://www.w3.org/2006/vcard/ns#honorific-prefix")), object: Variable(~"title")});
-	let context = QueryContext {namespaces: ~[], extensions: HashMap(), algebra: Group(~[]), order_by: ~[], distinct: false, limit: option::None, rng: rand::Rng(), timestamp: time::now()};
+	let context = QueryContext {namespaces: ~[], extensions: HashMap(), algebra: Group(~[]), order_by: ~[], distinct: false, limit: option::None, rng: rand::Rng(), timestamp: time::now(), from: ~[], from_named: ~[], named_graphs: HashMap()};
 	let bindings = ~[~"name", ~"title", ~"subject"];
 	let actual = eval_group(&store, &context, copy bindings, 2, &[p1, p2]);
 	assert actual.is_ok();
@@ -342,7 +342,7 @@ fn test_group()
 #[test]
 fn test_order_by()
 {
-	let context = QueryContext {namespaces: ~[], extensions: HashMap(), algebra: Group(~[]), order_by: ~[], distinct: false, limit: option::None, rng: rand::Rng(), timestamp: time::now()};
+	let context = QueryContext {namespaces: ~[], extensions: HashMap(), algebra: Group(~[]), order_by: ~[], distinct: false, limit: option::None, rng: rand::Rng(), timestamp: time::now(), from: ~[], from_named: ~[], named_graphs: HashMap()};
 	let bindings = ~[~"name", ~"nickname"];
 	let mut solution = Solution {namespaces: ~[], bindings: copy bindings, num_selected: 2, rows: 
 		~[
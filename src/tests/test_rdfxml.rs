@@ -0,0 +1,212 @@
+use tests::test_helpers::*;
+
+fn load(text: &str) -> Store
+{
+	let store = Store(~[], &HashMap());
+	match load_rdfxml(&store, text)
+	{
+		result::Ok(()) =>
+		{
+			store
+		}
+		result::Err(ref mesg) =>
+		{
+			fail fmt!("RDF/XML error: %s", *mesg);
+		}
+	}
+}
+
+fn triples(store: &Store) -> ~[Triple]
+{
+	let mut actual = ~[];
+	for store.each
+	|triple|
+	{
+		vec::push(&mut actual, copy *triple);
+	};
+	actual
+}
+
+#[test]
+fn rdfxml_description()
+{
+	let store = load("<?xml version=\"1.0\"?>
+<!-- Dublin Core -->
+<rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\" xmlns:dc=\"http://purl.org/dc/elements/1.1/\">
+	<rdf:Description rdf:about=\"http://example.org/book\" dc:creator=\"George R. R. Martin\">
+		<dc:title xml:lang=\"en\">A Game of Thrones</dc:title>
+		<dc:publisher rdf:resource=\"http://example.org/bantam\"/>
+		<dc:date rdf:datatype=\"http://www.w3.org/2001/XMLSchema#integer\">1996</dc:date>
+		<dc:description>Fire &amp; Ice</dc:description>
+	</rdf:Description>
+</rdf:RDF>");
+	
+	let expected = ~[
+		make_triple_str(&store, ~"http://example.org/book", ~"http://purl.org/dc/elements/1.1/creator", ~"George R. R. Martin"),
+		{subject: ~"http://example.org/book", predicate: ~"http://purl.org/dc/elements/1.1/title", object: @StringValue(~"A Game of Thrones", ~"en")},
+		make_triple_uri(&store, ~"http://example.org/book", ~"http://purl.org/dc/elements/1.1/publisher", ~"http://example.org/bantam"),
+		{subject: ~"http://example.org/book", predicate: ~"http://purl.org/dc/elements/1.1/date", object: @IntValue(1996i64)},
+		make_triple_str(&store, ~"http://example.org/book", ~"http://purl.org/dc/elements/1.1/description", ~"Fire & Ice"),
+	];
+	assert check_triples(triples(&store), expected);
+}
+
+#[test]
+fn rdfxml_typed_nodes()
+{
+	let store = load("<rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\" xmlns:ex=\"http://example.org/\" xml:base=\"http://example.org/people\">
+	<ex:Person rdf:ID=\"jon\">
+		<ex:knows>
+			<ex:Person rdf:nodeID=\"sam\" ex:name=\"Samwell\"/>
+		</ex:knows>
+		<ex:sworn rdf:nodeID=\"sam\"/>
+		<ex:address rdf:parseType=\"Resource\">
+			<ex:city>Castle Black</ex:city>
+		</ex:address>
+	</ex:Person>
+</rdf:RDF>");
+	
+	let expected = ~[
+		make_triple_uri(&store, ~"http://example.org/people#jon", ~"rdf:type", ~"http://example.org/Person"),
+		make_triple_blank(&store, ~"http://example.org/people#jon", ~"http://example.org/knows", ~"sam-0"),
+		make_triple_uri(&store, ~"_:sam-0", ~"rdf:type", ~"http://example.org/Person"),
+		make_triple_str(&store, ~"_:sam-0", ~"http://example.org/name", ~"Samwell"),
+		make_triple_blank(&store, ~"http://example.org/people#jon", ~"http://example.org/sworn", ~"sam-0"),
+		make_triple_blank(&store, ~"http://example.org/people#jon", ~"http://example.org/address", ~"resource-1"),
+		make_triple_str(&store, ~"_:resource-1", ~"http://example.org/city", ~"Castle Black"),
+	];
+	assert check_triples(triples(&store), expected);
+}
+
+#[test]
+fn rdfxml_parse_types()
+{
+	let store = load("<rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\" xmlns:ex=\"http://example.org/\">
+	<rdf:Description rdf:about=\"http://example.org/westeros\">
+		<ex:cities rdf:parseType=\"Collection\">
+			<rdf:Description rdf:about=\"http://example.org/Lanisport\"/>
+			<rdf:Description rdf:about=\"http://example.org/Oldtown\"/>
+		</ex:cities>
+		<ex:note rdf:parseType=\"Literal\"><b>Winter</b> is coming</ex:note>
+		<ex:houses>
+			<rdf:Bag>
+				<rdf:li>Stark</rdf:li>
+				<rdf:li>Lannister</rdf:li>
+			</rdf:Bag>
+		</ex:houses>
+	</rdf:Description>
+</rdf:RDF>");
+	
+	let expected = ~[
+		make_triple_blank(&store, ~"http://example.org/westeros", ~"http://example.org/cities", ~"list-0"),
+		make_triple_uri(&store, ~"_:list-0", ~"rdf:first", ~"http://example.org/Lanisport"),
+		make_triple_blank(&store, ~"_:list-0", ~"rdf:rest", ~"list-1"),
+		make_triple_uri(&store, ~"_:list-1", ~"rdf:first", ~"http://example.org/Oldtown"),
+		make_triple_uri(&store, ~"_:list-1", ~"rdf:rest", ~"rdf:nil"),
+		{subject: ~"http://example.org/westeros", predicate: ~"http://example.org/note", object: @TypedValue(~"<b>Winter</b> is coming", ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#XMLLiteral")},
+		make_triple_blank(&store, ~"http://example.org/westeros", ~"http://example.org/houses", ~"node-2"),
+		make_triple_uri(&store, ~"_:node-2", ~"rdf:type", ~"rdf:Bag"),
+		make_triple_str(&store, ~"_:node-2", ~"rdf:_1", ~"Stark"),
+		make_triple_str(&store, ~"_:node-2", ~"rdf:_2", ~"Lannister"),
+	];
+	assert check_triples(triples(&store), expected);
+}
+
+#[test]
+fn rdfxml_reification()
+{
+	let store = load("<rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\" xmlns:ex=\"http://example.org/\" xml:base=\"http://example.org/doc\">
+	<rdf:Description rdf:about=\"http://example.org/jon\">
+		<ex:title rdf:ID=\"claim\">Lord Commander</ex:title>
+	</rdf:Description>
+</rdf:RDF>");
+	
+	let expected = ~[
+		make_triple_str(&store, ~"http://example.org/jon", ~"http://example.org/title", ~"Lord Commander"),
+		make_triple_uri(&store, ~"http://example.org/doc#claim", ~"rdf:type", ~"rdf:Statement"),
+		make_triple_uri(&store, ~"http://example.org/doc#claim", ~"rdf:subject", ~"http://example.org/jon"),
+		make_triple_uri(&store, ~"http://example.org/doc#claim", ~"rdf:predicate", ~"http://example.org/title"),
+		make_triple_str(&store, ~"http://example.org/doc#claim", ~"rdf:object", ~"Lord Commander"),
+	];
+	assert check_triples(triples(&store), expected);
+}
+
+#[test]
+fn rdfxml_errors()
+{
+	let store = Store(~[], &HashMap());
+	
+	let result = load_rdfxml(&store, "<rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">
+	<rdf:Description rdf:about=\"http://example.org/x\">
+</rdf:RDF>");
+	assert result.is_err();
+	
+	let result = load_rdfxml(&store, "<rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">
+	<foo:x/>
+</rdf:RDF>");
+	assert check_strs(result.get_err(), ~"Prefix 'foo' has not been declared on line 2");
+}
+
+#[test]
+fn rdfxml_writer()
+{
+	let store = Store(~[Namespace {prefix: ~"ex", path: ~"http://example.org/"}], &HashMap());
+	store.add(~"ex:westeros", ~[
+		(~"ex:name", @StringValue(~"Westeros & Essos", ~"en")),
+		(~"ex:area", @FloatValue(1.5f64)),
+		(~"ex:knows", @IriValue(~"http://example.org/north")),
+	]);
+	store.add_bag(~"ex:houses", ~[@StringValue(~"Stark", ~""), @StringValue(~"Tully", ~"")]);
+	
+	let actual = store_to_rdfxml(&store);
+	let expected = ~"<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<rdf:RDF
+	xmlns:ex=\"http://example.org/\"
+	xmlns:owl=\"http://www.w3.org/2002/07/owl#\"
+	xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\"
+	xmlns:rdfs=\"http://www.w3.org/2000/01/rdf-schema#\"
+	xmlns:xsd=\"http://www.w3.org/2001/XMLSchema#\">
+	<rdf:Description rdf:about=\"http://example.org/houses\">
+		<rdf:Bag>
+			<rdf:Description>
+				<rdf:li>Stark</rdf:li>
+				<rdf:li>Tully</rdf:li>
+			</rdf:Description>
+		</rdf:Bag>
+	</rdf:Description>
+	<rdf:Description rdf:about=\"http://example.org/westeros\">
+		<ex:area rdf:datatype=\"http://www.w3.org/2001/XMLSchema#double\">1.5</ex:area>
+		<ex:knows rdf:resource=\"http://example.org/north\"/>
+		<ex:name xml:lang=\"en\">Westeros &amp; Essos</ex:name>
+	</rdf:Description>
+</rdf:RDF>
+";
+	assert actual.is_ok();
+	assert check_strs(actual.get(), expected);
+}
+
+#[test]
+fn rdfxml_round_trip()
+{
+	let store = Store(~[Namespace {prefix: ~"ex", path: ~"http://example.org/"}], &HashMap());
+	store.add(~"ex:jon", ~[
+		(~"ex:knows", @BlankValue(~"_:sam")),
+		(~"ex:sworn", @BlankValue(~"_:sam")),
+		(~"http://other.org/terms#rank", @StringValue(~"steward", ~"")),
+	]);
+	store.add(~"_:sam", ~[(~"ex:name", @StringValue(~"Samwell", ~""))]);
+	store.add(~"_:seq", ~[
+		(~"rdf:type", @IriValue(~"rdf:Seq")),
+		(~"rdf:_1", @IntValue(1i64)),
+		(~"rdf:_2", @IntValue(2i64)),
+	]);
+	
+	let loaded = Store(~[], &HashMap());
+	assert load_rdfxml(&loaded, store_to_rdfxml(&store).get()).is_ok();
+	assert triples(&loaded).len() == triples(&store).len();
+	
+	let knows = loaded.find_objects(~"http://example.org/jon", ~"http://example.org/knows");
+	let sworn = loaded.find_objects(~"http://example.org/jon", ~"http://example.org/sworn");
+	assert knows.len() == 1 && knows == sworn;
+	assert loaded.find_object(~"http://example.org/jon", ~"http://other.org/terms#rank") == option::Some(@StringValue(~"steward", ~""));
+}
@@ -0,0 +1,117 @@
+use tests::test_helpers::*;
+
+fn make_solution() -> Solution
+{
+	Solution {
+		namespaces: ~[],
+		bindings: ~[~"s", ~"o", ~"hidden"],
+		num_selected: 2,
+		rows: ~[
+			~[@IriValue(~"http://example.org/jon"), @StringValue(~"Lord \"Snow\"", ~"en"), @IntValue(1i64)],
+			~[@BlankValue(~"_:b0"), @IntValue(42i64), @IntValue(2i64)],
+			~[@IriValue(~"http://example.org/sam"), @UnboundValue, @IntValue(3i64)],
+		],
+	}
+}
+
+#[test]
+fn results_json_writer()
+{
+	let actual = solution_to_json(&make_solution());
+	let expected = ~"{
+	\"head\": {\"vars\": [\"s\", \"o\"]},
+	\"results\": {\"bindings\": [
+		{\"s\": {\"type\": \"uri\", \"value\": \"http://example.org/jon\"}, \"o\": {\"type\": \"literal\", \"value\": \"Lord \\\"Snow\\\"\", \"xml:lang\": \"en\"}},
+		{\"s\": {\"type\": \"bnode\", \"value\": \"b0\"}, \"o\": {\"type\": \"literal\", \"value\": \"42\", \"datatype\": \"http://www.w3.org/2001/XMLSchema#integer\"}},
+		{\"s\": {\"type\": \"uri\", \"value\": \"http://example.org/sam\"}}
+	]}
+}
+";
+	assert check_strs(actual, expected);
+}
+
+#[test]
+fn results_json_round_trip()
+{
+	let solution = make_solution();
+	let actual = json_to_solution(solution_to_json(&solution));
+	assert actual.is_ok();
+	
+	let rows = do solution.rows.map |row| {row.slice(0, 2)};
+	let expected = Solution {namespaces: ~[], bindings: ~[~"s", ~"o"], num_selected: 2, rows: rows};
+	assert check_solution(&actual.get(), &expected.sort());
+}
+
+#[test]
+fn results_json_parser()
+{
+	let actual = json_to_solution("{
+		\"head\": {\"vars\": [\"x\", \"y\"], \"link\": []},
+		\"results\": {\"bindings\": [
+			{\"x\": {\"type\": \"typed-literal\", \"value\": \"true\", \"datatype\": \"http://www.w3.org/2001/XMLSchema#boolean\"}, \"y\": {\"type\": \"literal\", \"value\": \"plain\"}}
+		]}
+	}");
+	let expected = Solution {namespaces: ~[], bindings: ~[~"x", ~"y"], num_selected: 2, rows: ~[~[@BoolValue(true), @StringValue(~"plain", ~"")]]};
+	assert check_solution(&actual.get(), &expected);
+	
+	assert check_strs(json_to_solution("{\"head\": {\"vars\": [\"x\"]}, \"results\": {\"bindings\": [{\"z\": {\"type\": \"uri\", \"value\": \"a\"}}]}}").get_err(), ~"z is not one of the variables in the head.");
+	assert json_to_solution("[]").is_err();
+}
+
+#[test]
+fn results_xml_writer()
+{
+	let actual = solution_to_xml(&make_solution());
+	let expected = ~"<?xml version=\"1.0\"?>
+<sparql xmlns=\"http://www.w3.org/2005/sparql-results#\">
+	<head>
+		<variable name=\"s\"/>
+		<variable name=\"o\"/>
+	</head>
+	<results>
+		<result>
+			<binding name=\"s\"><uri>http://example.org/jon</uri></binding>
+			<binding name=\"o\"><literal xml:lang=\"en\">Lord &quot;Snow&quot;</literal></binding>
+		</result>
+		<result>
+			<binding name=\"s\"><bnode>b0</bnode></binding>
+			<binding name=\"o\"><literal datatype=\"http://www.w3.org/2001/XMLSchema#integer\">42</literal></binding>
+		</result>
+		<result>
+			<binding name=\"s\"><uri>http://example.org/sam</uri></binding>
+		</result>
+	</results>
+</sparql>
+";
+	assert check_strs(actual, expected);
+}
+
+#[test]
+fn results_xml_round_trip()
+{
+	let solution = make_solution();
+	let actual = xml_to_solution(solution_to_xml(&solution));
+	assert actual.is_ok();
+	
+	let rows = do solution.rows.map |row| {row.slice(0, 2)};
+	let expected = Solution {namespaces: ~[], bindings: ~[~"s", ~"o"], num_selected: 2, rows: rows};
+	assert check_solution(&actual.get(), &expected.sort());
+	
+	assert xml_to_solution("<sparql xmlns=\"http://www.w3.org/2005/sparql-results#\"><results/></sparql>").is_err();
+}
+
+#[test]
+fn results_csv()
+{
+	let actual = solution_to_csv(&make_solution());
+	let expected = ~"s,o\r\nhttp://example.org/jon,\"Lord \"\"Snow\"\"\"\r\n_:b0,42\r\nhttp://example.org/sam,\r\n";
+	assert check_strs(actual, expected);
+}
+
+#[test]
+fn results_tsv()
+{
+	let actual = solution_to_tsv(&make_solution());
+	let expected = ~"?s\t?o\n<http://example.org/jon>\t\"Lord \\\"Snow\\\"\"@en\n_:b0\t\"42\"^^<http://www.w3.org/2001/XMLSchema#integer>\n<http://example.org/sam>\t\n";
+	assert check_strs(actual, expected);
+}
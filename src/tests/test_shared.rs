@@ -0,0 +1,51 @@
+use tests::test_data::*;
+
+// Returns the number of nicknames in the latest store.
+fn count_nicknames(reader: &StoreReader) -> uint
+{
+	let selector = compile("SELECT ?name WHERE {?s <http://www.w3.org/2006/vcard/ns#nickname> ?name}").get();
+	reader.query(selector).get().rows.len()
+}
+
+#[test]
+fn shared_readers()
+{
+	let store = got_cast3();
+	let shared = SharedStore(&store);
+	
+	let mut futures = ~[];
+	for uint::range(0, 4) |_i|
+	{
+		let reference = shared.clone();
+		vec::push(&mut futures, do std::future::spawn
+		|move reference|
+		{
+			let reader = StoreReader(reference, &HashMap());
+			count_nicknames(&reader)
+		});
+	}
+	for futures.each |f|
+	{
+		assert f.get() == 3;
+	}
+}
+
+#[test]
+fn shared_publish()
+{
+	let store = got_cast3();
+	let shared = SharedStore(&store);
+	let reader = StoreReader(shared.clone(), &HashMap());
+	assert count_nicknames(&reader) == 3;
+	
+	// The reader doesn't see changes until they are published.
+	store.add_triple(~[], {subject: ~"got:Jon_Snow", predicate: ~"v:nickname", object: @StringValue(~"Bastard", ~"")});
+	assert count_nicknames(&reader) == 3;
+	
+	shared.publish(&store);
+	assert shared.version() == 1;
+	assert count_nicknames(&reader) == 4;
+	
+	let thawed = thaw_store(&freeze_store(&store), &HashMap());
+	assert thawed.is_isomorphic(&store);
+}
@@ -0,0 +1,85 @@
+use io::WriterUtil;
+use tests::test_helpers::*;
+
+fn triples(store: &Store) -> ~[Triple]
+{
+	let mut actual = ~[];
+	for store.each
+	|triple|
+	{
+		vec::push(&mut actual, copy *triple);
+	};
+	actual
+}
+
+#[test]
+fn snapshot_round_trip()
+{
+	let store = Store(~[Namespace {prefix: ~"got", path: ~"http://awoiaf.westeros.org/index.php/"}], &HashMap());
+	let born = std::time::now_utc();
+	store.add(~"got:Eddard_Stark", ~[
+		(~"got:alive", @BoolValue(false)),
+		(~"got:age", @IntValue(35i64)),
+		(~"got:height", @FloatValue(1.85f64)),
+		(~"got:born", @DateTimeValue(copy born)),
+		(~"got:name", @StringValue(~"Eddard Stark", ~"en")),
+		(~"got:title", @StringValue(~"Lord of Winterfell", ~"")),
+		(~"got:sigil", @TypedValue(~"direwolf", ~"http://example.org/heraldry")),
+		(~"got:wife", @IriValue(~"http://awoiaf.westeros.org/index.php/Catelyn_Stark")),
+		(~"got:weight", @InvalidValue(~"heavy", ~"http://www.w3.org/2001/XMLSchema#integer")),
+		(~"got:fate", @ErrorValue(~"beheaded")),
+	]);
+	store.add_container(~"got:Eddard_Stark", ~"rdf:Seq", ~[
+		@IriValue(~"http://awoiaf.westeros.org/index.php/Robb_Stark"),
+		@IriValue(~"http://awoiaf.westeros.org/index.php/Sansa_Stark"),
+	]);
+	
+	let bytes = do io::with_bytes_writer |writer| {store.save(writer)};
+	let result = do io::with_bytes_reader(bytes) |reader| {Store::load(reader)};
+	assert result.is_ok();
+	
+	let loaded = result.get();
+	assert loaded.next_blank == store.next_blank;
+	assert loaded.namespaces == store.namespaces;
+	assert check_triples(triples(&loaded), triples(&store));
+}
+
+#[test]
+fn snapshot_errors()
+{
+	let result = do io::with_bytes_reader(str::to_bytes("@prefix got: <http://awoiaf.westeros.org/index.php/> .")) |reader| {Store::load(reader)};
+	assert check_strs(result.get_err(), ~"Not a store snapshot.");
+	
+	let bytes = do io::with_bytes_writer |writer| {writer.write_str("RRDF"); writer.write_be_u32(99u32)};
+	let result = do io::with_bytes_reader(bytes) |reader| {Store::load(reader)};
+	assert check_strs(result.get_err(), ~"Snapshot version 99 is not supported (expected 1).");
+	
+	let store = Store(~[], &HashMap());
+	store.add(~"http://example.org/x", ~[(~"http://example.org/y", @IntValue(1i64))]);
+	let bytes = do io::with_bytes_writer |writer| {store.save(writer)};
+	let result = do io::with_bytes_reader(vec::slice(bytes, 0, bytes.len() - 4)) |reader| {Store::load(reader)};
+	assert check_strs(result.get_err(), ~"Snapshot is truncated.");
+}
+
+#[test]
+fn snapshot_corrupt_lengths()
+{
+	// A string which claims to be 4 GB long.
+	let bytes = do io::with_bytes_writer |writer| {writer.write_str("RRDF"); writer.write_be_u32(1u32); writer.write_be_u32(1u32); writer.write_be_u32(0xFFFFFFFFu32); writer.write_str("abc")};
+	let result = do io::with_bytes_reader(bytes) |reader| {Store::load(reader)};
+	assert check_strs(result.get_err(), ~"Snapshot is truncated.");
+	
+	// Huge string, namespace, and subject counts.
+	let bytes = do io::with_bytes_writer |writer| {writer.write_str("RRDF"); writer.write_be_u32(1u32); writer.write_be_u32(0xFFFFFFFFu32)};
+	let result = do io::with_bytes_reader(bytes) |reader| {Store::load(reader)};
+	assert check_strs(result.get_err(), ~"Snapshot is truncated.");
+	
+	let bytes = do io::with_bytes_writer |writer| {writer.write_str("RRDF"); writer.write_be_u32(1u32); writer.write_be_u32(0u32); writer.write_be_i64(0i64); writer.write_be_u32(0u32); writer.write_be_u32(0xFFFFFFFFu32)};
+	let result = do io::with_bytes_reader(bytes) |reader| {Store::load(reader)};
+	assert check_strs(result.get_err(), ~"Snapshot is truncated.");
+	
+	// A subject which refers to a string that isn't in the dictionary.
+	let bytes = do io::with_bytes_writer |writer| {writer.write_str("RRDF"); writer.write_be_u32(1u32); writer.write_be_u32(0u32); writer.write_be_i64(0i64); writer.write_be_u32(0u32); writer.write_be_u32(1u32); writer.write_be_u32(7u32); writer.write_be_u32(0u32)};
+	let result = do io::with_bytes_reader(bytes) |reader| {Store::load(reader)};
+	assert check_strs(result.get_err(), ~"Bad string index 7.");
+}
@@ -931,3 +931,113 @@ fn bad_optional()
 	
 	assert check_eval(&store, expr, &expected);
 }
+
+fn got_books() -> Dataset
+{
+	let dataset = Dataset(~[Namespace {prefix: ~"got", path: ~"http://awoiaf.westeros.org/index.php/"}], &HashMap());
+	dataset.add(~"", ~"got:Eddard_Stark", ~[(~"got:name", @StringValue(~"Eddard Stark", ~""))]);
+	dataset.add(~"got:book1", ~"got:Eddard_Stark", ~[(~"got:status", @StringValue(~"alive", ~""))]);
+	dataset.add(~"got:book2", ~"got:Eddard_Stark", ~[(~"got:status", @StringValue(~"dead", ~""))]);
+	dataset.add(~"got:book2", ~"got:Jon_Snow", ~[(~"got:status", @StringValue(~"alive", ~""))]);
+	dataset
+}
+
+#[test]
+fn graph_variable()
+{
+	let expr = ~"
+	PREFIX got: <http://awoiaf.westeros.org/index.php/>
+	SELECT ?g ?s ?status
+	WHERE
+	{
+		GRAPH ?g {?s got:status ?status}
+	}";
+	let dataset = got_books();
+	let expected = Solution {namespaces: ~[], bindings: ~[~"g", ~"s", ~"status"], num_selected: 3, rows: ~[
+		~[@IriValue(got(~"book1")), @IriValue(got(~"Eddard_Stark")), @StringValue(~"alive", ~"")],
+		~[@IriValue(got(~"book2")), @IriValue(got(~"Eddard_Stark")), @StringValue(~"dead", ~"")],
+		~[@IriValue(got(~"book2")), @IriValue(got(~"Jon_Snow")), @StringValue(~"alive", ~"")]
+	]};
+	
+	assert check_dataset_eval(&dataset, expr, &expected);
+}
+
+#[test]
+fn graph_iri()
+{
+	let expr = ~"
+	PREFIX got: <http://awoiaf.westeros.org/index.php/>
+	SELECT ?name ?status
+	WHERE
+	{
+		?s got:name ?name .
+		GRAPH got:book2 {?s got:status ?status}
+	}";
+	let dataset = got_books();
+	let expected = Solution {namespaces: ~[], bindings: ~[~"name", ~"status", ~"s"], num_selected: 2, rows: ~[
+		~[@StringValue(~"Eddard Stark", ~""), @StringValue(~"dead", ~""), @IriValue(got(~"Eddard_Stark"))]
+	]};
+	
+	assert check_dataset_eval(&dataset, expr, &expected);
+}
+
+#[test]
+fn from_clauses()
+{
+	let expr = ~"
+	PREFIX got: <http://awoiaf.westeros.org/index.php/>
+	SELECT ?s ?g
+	FROM got:book1
+	FROM NAMED got:book2
+	WHERE
+	{
+		?s got:status \"alive\" .
+		GRAPH ?g {?s got:status \"dead\"}
+	}";
+	let dataset = got_books();
+	let expected = Solution {namespaces: ~[], bindings: ~[~"s", ~"g"], num_selected: 2, rows: ~[
+		~[@IriValue(got(~"Eddard_Stark")), @IriValue(got(~"book2"))]
+	]};
+	
+	assert check_dataset_eval(&dataset, expr, &expected);
+	assert check_strs(compile(expr).get_err(), ~"FROM and FROM NAMED can only be used with compile_dataset.");
+}
+
+#[test]
+fn from_merges_graphs()
+{
+	let dataset = got_books();
+	dataset.add(~"got:book1", ~"got:Jon_Snow", ~[(~"got:status", @StringValue(~"alive", ~""))]);
+	dataset.add(~"got:book1", ~"_:b", ~[(~"got:name", @StringValue(~"Jon Snow", ~""))]);
+	dataset.add(~"got:book2", ~"_:b", ~[(~"got:pet", @StringValue(~"Ghost", ~""))]);
+	
+	// Triples in both graphs are only matched once.
+	let expr = ~"
+	PREFIX got: <http://awoiaf.westeros.org/index.php/>
+	SELECT ?s
+	FROM got:book1
+	FROM got:book2
+	WHERE
+	{
+		?s got:status \"alive\" .
+	}";
+	let expected = Solution {namespaces: ~[], bindings: ~[~"s"], num_selected: 1, rows: ~[
+		~[@IriValue(got(~"Eddard_Stark"))],
+		~[@IriValue(got(~"Jon_Snow"))]
+	]};
+	assert check_dataset_eval(&dataset, expr, &expected);
+	
+	// And blank nodes from different graphs are different nodes.
+	let expr = ~"
+	PREFIX got: <http://awoiaf.westeros.org/index.php/>
+	SELECT ?name ?pet
+	FROM got:book1
+	FROM got:book2
+	WHERE
+	{
+		?s got:name ?name .
+		?s got:pet ?pet .
+	}";
+	let expected = Solution {namespaces: ~[], bindings: ~[~"name", ~"pet", ~"s"], num_selected: 2, rows: ~[]};
+	assert check_dataset_eval(&dataset, expr, &expected);
+}
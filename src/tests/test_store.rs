@@ -176,6 +176,78 @@ fn list1()
 	assert check_triples(actual, expected);
 }
 
+#[test]
+fn get_list() 
+{
+	let store = Store(~[Namespace {prefix: ~"got", path: ~"http://awoiaf.westeros.org/index.php/"}], &HashMap());
+	store.add_list(~"got:westeros", ~"got:cities", ~[@StringValue(~"Lanisport", ~""), @IriValue(~"got:Oldtown"), @StringValue(~"Lanisport", ~"")]);
+	store.add_list(~"got:essos", ~"got:cities", ~[]);
+	
+	let cities = store.get_list(~"got:westeros", ~"got:cities").get();
+	assert cities == ~[@StringValue(~"Lanisport", ~""), @IriValue(~"http://awoiaf.westeros.org/index.php/Oldtown"), @StringValue(~"Lanisport", ~"")];
+	assert store.get_list(~"got:essos", ~"got:cities").get().is_empty();
+	assert store.get_list(~"got:essos", ~"got:castles").is_err();
+	
+	// Lists with an extra value.
+	store.add_triple(~[], {subject: ~"_:cities-1", predicate: ~"rdf:first", object: @StringValue(~"Gulltown", ~"")});
+	assert str::contains(store.get_list(~"got:westeros", ~"got:cities").get_err(), "_:cities-1");
+	
+	// Lists which never end.
+	store.add(~"got:sothoryos", ~[(~"got:cities", @BlankValue(~"_:a"))]);
+	store.add(~"_:a", ~[(~"rdf:first", @StringValue(~"Yeen", ~"")), (~"rdf:rest", @BlankValue(~"_:b"))]);
+	store.add(~"_:b", ~[(~"rdf:first", @StringValue(~"Zamettar", ~"")), (~"rdf:rest", @BlankValue(~"_:a"))]);
+	assert str::contains(store.get_list(~"got:sothoryos", ~"got:cities").get_err(), "cycles");
+}
+
+#[test]
+fn get_container() 
+{
+	let store = Store(~[Namespace {prefix: ~"got", path: ~"http://awoiaf.westeros.org/index.php/"}], &HashMap());
+	store.add_seq(~"got:kings", ~[@IriValue(~"got:Aegon_I"), @IriValue(~"got:Aenys_I")]);
+	let (kind, values) = store.get_container(~"got:kings").get();
+	assert kind == ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#Seq";
+	assert values == ~[@IriValue(~"http://awoiaf.westeros.org/index.php/Aegon_I"), @IriValue(~"http://awoiaf.westeros.org/index.php/Aenys_I")];
+	
+	// Containers loaded from Turtle or RDF/XML are typed.
+	store.add(~"got:houses", ~[
+		(~"rdf:type", @IriValue(~"rdf:Bag")),
+		(~"rdf:_2", @StringValue(~"Stark", ~"")),
+		(~"rdf:_10", @StringValue(~"Tully", ~"")),
+		(~"rdf:_1", @StringValue(~"Lannister", ~""))
+	]);
+	let (kind, values) = store.get_container(~"got:houses").get();
+	assert kind == ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#Bag";
+	assert values == ~[@StringValue(~"Lannister", ~""), @StringValue(~"Stark", ~""), @StringValue(~"Tully", ~"")];
+	
+	store.add(~"got:houses", ~[(~"rdf:_2", @StringValue(~"Targaryen", ~""))]);
+	assert str::contains(store.get_container(~"got:houses").get_err(), "_2");
+	assert store.get_container(~"got:Aegon_I").is_err();
+}
+
+#[test]
+fn replace_list() 
+{
+	let store = Store(~[Namespace {prefix: ~"got", path: ~"http://awoiaf.westeros.org/index.php/"}], &HashMap());
+	let ned = @BlankValue(~"_:ned");
+	store.add(~"_:ned", ~[(~"got:name", @StringValue(~"Eddard", ~""))]);
+	store.add(~"_:jon", ~[(~"got:name", @StringValue(~"Jon", ~""))]);
+	store.add_list(~"got:Winterfell", ~"got:residents", ~[ned, @BlankValue(~"_:jon")]);
+	store.add(~"got:Castle_Black", ~[(~"got:lord", @BlankValue(~"_:jon"))]);
+	
+	// Eddard is no longer referenced so he goes away, Jon is still referenced by Castle Black.
+	store.replace_list(~"got:Winterfell", ~"got:residents", ~[@StringValue(~"Bran", ~"")]).get();
+	
+	let expected = Store(~[Namespace {prefix: ~"got", path: ~"http://awoiaf.westeros.org/index.php/"}], &HashMap());
+	expected.add(~"_:jon", ~[(~"got:name", @StringValue(~"Jon", ~""))]);
+	expected.add(~"got:Castle_Black", ~[(~"got:lord", @BlankValue(~"_:jon"))]);
+	expected.add_list(~"got:Winterfell", ~"got:residents", ~[@StringValue(~"Bran", ~"")]);
+	assert store.is_isomorphic(&expected);
+	
+	// Replacing a missing list adds it.
+	store.replace_list(~"got:The_Wall", ~"got:residents", ~[]).get();
+	assert store.get_list(~"got:The_Wall", ~"got:residents").get().is_empty();
+}
+
 #[test]
 fn reify() 
 {
@@ -222,6 +294,244 @@ fn replace()
 	assert check_triples(actual, expected);
 }
 
+#[test]
+fn remove() 
+{
+	let store = got_cast3();
+	assert store.remove_triple(~[], {subject: ~"got:Jon_Snow", predicate: ~"v:pet", object: @StringValue(~"Ghost", ~"")}) == 1;
+	assert store.remove_triple(~[], {subject: ~"got:Jon_Snow", predicate: ~"v:pet", object: @StringValue(~"Ghost", ~"")}) == 0;
+	assert store.remove_subject(~"got:Sandor_Clegane") == 2;
+	assert store.remove_subject(~"got:Sandor_Clegane") == 0;
+	assert store.remove_matching(option::Some(~"got:Eddard_Stark"), option::Some(~"v:nickname"), option::None) == 1;
+	assert store.remove_matching(option::None, option::Some(~"v:organisation-unit"), option::None) == 2;
+	assert store.remove_matching(option::None, option::None, option::Some(@StringValue(~"Small Council", ~""))) == 1;
+	
+	let mut actual = ~[];
+	for store.each
+	|triple|
+	{
+		vec::push(&mut actual, copy *triple);
+	};
+	
+	let expected = ~[
+		make_triple_str(&store, ~"got:Eddard_Stark", ~"v:fn", ~"Eddard Stark"),
+		make_triple_str(&store, ~"got:Eddard_Stark", ~"v:honorific-prefix", ~"Lord"),
+		make_triple_blank(&store, ~"got:Eddard_Stark", ~"v:org", ~"ned-org-0"),
+		
+		make_triple_str(&store, ~"got:Jon_Snow", ~"v:fn", ~"Jon Snow"),
+		make_triple_str(&store, ~"got:Jon_Snow", ~"v:nickname", ~"Lord Snow"),
+		make_triple_blank(&store, ~"got:Jon_Snow", ~"v:org", ~"jon-org-1"),
+		
+		make_triple_str(&store, ~"_:jon-org-1", ~"v:organisation-name", ~"Night's Watch"),
+	];
+	assert check_triples(actual, expected);
+	
+	assert store.remove_matching(option::None, option::None, option::None) == 6;
+	assert store.subjects.size() == 0;
+}
+
+#[test]
+fn remove_same_term() 
+{
+	let store = got_cast1();
+	store.add(~"got:Eddard_Stark", ~[
+		(~"v:nickname", @StringValue(~"Ned", ~"EN")),
+		(~"got:age", @IntValue(35)),
+		(~"got:age", @TypedValue(~"035", ~"xsd:integer")),
+	]);
+	
+	// Removal uses the same RDF term equality as find_triples so the tag case and the
+	// way the literal was constructed don't matter but the lexical form does.
+	assert store.remove_triple(~[], {subject: ~"got:Eddard_Stark", predicate: ~"v:nickname", object: @StringValue(~"Ned", ~"en")}) == 1;
+	assert store.remove_matching(option::None, option::Some(~"got:age"), option::Some(@TypedValue(~"35", ~"xsd:integer"))) == 1;
+	assert store.find_objects(~"got:Eddard_Stark", ~"got:age") == ~[@TypedValue(~"035", ~"http://www.w3.org/2001/XMLSchema#integer")];
+	assert store.find_objects(~"got:Eddard_Stark", ~"v:nickname") == ~[@StringValue(~"Ned", ~"")];
+}
+
+#[test]
+fn find_triples() 
+{
+	let store = got_cast3();
+	
+	let mut actual = ~[];
+	for store.find_triples(option::Some("got:Jon_Snow"), option::Some("v:pet"), option::None) |triple|
+	{
+		vec::push(&mut actual, copy *triple);
+	}
+	assert check_triples(actual, ~[make_triple_str(&store, ~"got:Jon_Snow", ~"v:pet", ~"Ghost")]);
+	
+	let mut actual = ~[];
+	for store.find_triples(option::None, option::Some("v:org"), option::None) |triple|
+	{
+		vec::push(&mut actual, copy *triple);
+	}
+	assert check_triples(actual, ~[
+		make_triple_blank(&store, ~"got:Eddard_Stark", ~"v:org", ~"ned-org-0"),
+		make_triple_blank(&store, ~"got:Jon_Snow", ~"v:org", ~"jon-org-1"),
+	]);
+	
+	// Reverse lookups.
+	let mut actual = ~[];
+	for store.find_triples(option::None, option::None, option::Some(@BlankValue(~"_:jon-org-1"))) |triple|
+	{
+		vec::push(&mut actual, copy *triple);
+	}
+	assert check_triples(actual, ~[make_triple_blank(&store, ~"got:Jon_Snow", ~"v:org", ~"jon-org-1")]);
+	
+	let mut count = 0;
+	for store.find_triples(option::None, option::None, option::None) |_triple|
+	{
+		count += 1;
+		if count == 3
+		{
+			break;
+		}
+	}
+	assert count == 3;
+	
+	for store.find_triples(option::Some("got:Arya_Stark"), option::None, option::None) |_triple|
+	{
+		fail ~"Arya isn't in the store";
+	}
+	for store.find_triples(option::None, option::Some("v:fn"), option::Some(@StringValue(~"Ned", ~""))) |_triple|
+	{
+		fail ~"Ned isn't anyone's fn";
+	}
+}
+
+#[test]
+fn find_subjects() 
+{
+	let store = got_cast3();
+	store.add(~"got:Eddard_Stark", ~[(~"v:nickname", @StringValue(~"Lord Stark", ~""))]);
+	store.add(~"got:Robb_Stark", ~[(~"v:nickname", @StringValue(~"Lord Stark", ~""))]);
+	
+	let subjects = std::sort::merge_sort(store.find_subjects(~"v:nickname", @StringValue(~"Lord Stark", ~"")), |x, y| {*x <= *y});
+	assert subjects == ~[~"http://awoiaf.westeros.org/index.php/Eddard_Stark", ~"http://awoiaf.westeros.org/index.php/Robb_Stark"];
+	assert store.find_subjects(~"v:nickname", @StringValue(~"Lord Stark", ~"en")).is_empty();
+	assert store.find_subjects(~"v:pet", @StringValue(~"Ghost", ~"")) == ~[~"http://awoiaf.westeros.org/index.php/Jon_Snow"];
+	assert store.find_subjects(~"v:org", @BlankValue(~"_:ned-org-0")) == ~[~"http://awoiaf.westeros.org/index.php/Eddard_Stark"];
+	
+	let predicates = std::sort::merge_sort(store.find_predicates(~"got:Eddard_Stark"), |x, y| {*x <= *y});
+	assert predicates == ~[
+		~"http://www.w3.org/2006/vcard/ns#fn",
+		~"http://www.w3.org/2006/vcard/ns#honorific-prefix",
+		~"http://www.w3.org/2006/vcard/ns#nickname",
+		~"http://www.w3.org/2006/vcard/ns#org",
+	];
+	assert store.find_predicates(~"got:Arya_Stark").is_empty();
+}
+
+#[test]
+fn set_semantics() 
+{
+	let store = got_cast1();
+	assert store.add_triple(~[], {subject: ~"got:Eddard_Stark", predicate: ~"v:nickname", object: @StringValue(~"Ned", ~"")});
+	assert store.find_objects(~"got:Eddard_Stark", ~"v:nickname").len() == 2;
+	
+	let store = got_cast1();
+	store.set_semantics = true;
+	assert !store.add_triple(~[], {subject: ~"got:Eddard_Stark", predicate: ~"v:nickname", object: @StringValue(~"Ned", ~"")});
+	assert store.add_triple(~[], {subject: ~"got:Eddard_Stark", predicate: ~"v:nickname", object: @StringValue(~"Ned", ~"en")});
+	assert !store.add_triple(~[], {subject: ~"got:Eddard_Stark", predicate: ~"v:nickname", object: @StringValue(~"Ned", ~"EN")});
+	assert store.find_objects(~"got:Eddard_Stark", ~"v:nickname").len() == 2;
+	
+	// Terms are compared, not values.
+	assert store.add(~"got:Eddard_Stark", ~[(~"v:age", @IntValue(35)), (~"v:age", @IntValue(35)), (~"v:age", @FloatValue(35.0f64))]) == 2;
+	assert !store.add_triple(~[], {subject: ~"got:Eddard_Stark", predicate: ~"v:age", object: @TypedValue(~"35", ~"xsd:integer")});
+	assert predicate_index_len(&store, "http://www.w3.org/2006/vcard/ns#age") == 2;
+	
+	// Replacing with an existing triple removes the old triple.
+	store.replace_triple(~[], {subject: ~"got:Eddard_Stark", predicate: ~"v:age", object: @FloatValue(35.0f64)});
+	assert *store.find_object(~"got:Eddard_Stark", ~"v:age").get() == FloatValue(35.0f64);
+	assert predicate_index_len(&store, "http://www.w3.org/2006/vcard/ns#age") == 1;
+}
+
+#[test]
+fn listeners() 
+{
+	let store = got_cast1();
+	let changes = @DVec();
+	let id = do store.add_listener |change| {changes.push(change.to_str())};
+	
+	store.add_triple(~[], {subject: ~"got:Eddard_Stark", predicate: ~"v:honorific-prefix", object: @StringValue(~"Lord", ~"")});
+	store.replace_triple(~[], {subject: ~"got:Eddard_Stark", predicate: ~"v:nickname", object: @StringValue(~"Lord Stark", ~"")});
+	store.remove_triple(~[], {subject: ~"got:Eddard_Stark", predicate: ~"v:fn", object: @StringValue(~"Eddard Stark", ~"")});
+	store.add_reify(~"got:Eddard_Stark", ~"v:nickname", @StringValue(~"Lord Stark", ~""));
+	store.clear();
+	
+	store.begin();
+	store.add_triple(~[], {subject: ~"got:Jon_Snow", predicate: ~"v:fn", object: @StringValue(~"Jon Snow", ~"")});
+	store.rollback();
+	
+	assert store.remove_listener(id);
+	assert !store.remove_listener(id);
+	store.add_triple(~[], {subject: ~"got:Jon_Snow", predicate: ~"v:fn", object: @StringValue(~"Jon Snow", ~"")});
+	
+	let changes = changes.get();
+	assert changes.len() == 11;
+	assert check_strs(copy changes[0], ~"added {http://awoiaf.westeros.org/index.php/Eddard_Stark, http://www.w3.org/2006/vcard/ns#honorific-prefix, \"Lord\"}");
+	assert check_strs(copy changes[1], ~"replaced \"Ned\" with {http://awoiaf.westeros.org/index.php/Eddard_Stark, http://www.w3.org/2006/vcard/ns#nickname, \"Lord Stark\"}");
+	assert check_strs(copy changes[2], ~"removed {http://awoiaf.westeros.org/index.php/Eddard_Stark, http://www.w3.org/2006/vcard/ns#fn, \"Eddard Stark\"}");
+	assert vec::all(vec::view(changes, 3, 7), |change| {change.starts_with("added {_:nickname-0, ")});
+	assert check_strs(copy changes[7], ~"cleared");
+	assert check_strs(copy changes[8], ~"added {http://awoiaf.westeros.org/index.php/Jon_Snow, http://www.w3.org/2006/vcard/ns#fn, \"Jon Snow\"}");
+	assert check_strs(copy changes[9], ~"removed {http://awoiaf.westeros.org/index.php/Jon_Snow, http://www.w3.org/2006/vcard/ns#fn, \"Jon Snow\"}");
+	assert check_strs(copy changes[10], ~"rolled back");
+}
+
+#[test]
+fn indexes() 
+{
+	let store = got_cast3();
+	let nickname = "http://www.w3.org/2006/vcard/ns#nickname";
+	let org = "http://www.w3.org/2006/vcard/ns#org";
+	assert predicate_index_len(&store, nickname) == 3;
+	assert predicate_index_len(&store, org) == 2;
+	let ned = store.terms.find_name("_:ned-org-0").get();
+	assert store.objects.get(ned).get() == ~[{subject: store.terms.find_name("http://awoiaf.westeros.org/index.php/Eddard_Stark").get(), predicate: store.terms.find_name(org).get()}];
+	assert !store.objects.contains_key(store.terms.find(@StringValue(~"Ned", ~"")).get());
+	
+	store.replace_triple(~[], {subject: ~"got:Eddard_Stark", predicate: ~"v:org", object: @IriValue(~"got:Small_Council")});
+	assert object_index_len(&store, "_:ned-org-0") == 0;
+	assert object_index_len(&store, "http://awoiaf.westeros.org/index.php/Small_Council") == 1;
+	assert predicate_index_len(&store, org) == 2;
+	
+	store.remove_subject(~"got:Jon_Snow");
+	assert predicate_index_len(&store, nickname) == 2;
+	assert predicate_index_len(&store, org) == 1;
+	assert object_index_len(&store, "_:jon-org-1") == 0;
+	
+	store.clear();
+	assert store.predicates.size() == 0;
+	assert store.objects.size() == 0;
+	assert store.terms.terms.len() == 0;
+}
+
+#[test]
+fn merge() 
+{
+	let store = got_cast3();
+	let other = Store(~[
+		Namespace {prefix: ~"got", path: ~"http://awoiaf.westeros.org/index.php/"},
+		Namespace {prefix: ~"v", path: ~"http://example.org/vcard#"},
+		Namespace {prefix: ~"wiki", path: ~"http://en.wikipedia.org/wiki/"}
+		], &HashMap());
+	other.add(~"got:Arya_Stark", ~[(~"wiki:name", @StringValue(~"Arya Stark", ~""))]);
+	other.add_aggregate(~"got:Arya_Stark", ~"wiki:org", ~"ned-org", ~[(~"wiki:name", @StringValue(~"Faceless Men", ~""))]);
+	
+	let conflicts = store.merge(&other);
+	assert conflicts == ~[~"v"];
+	assert store.namespaces.contains(&Namespace {prefix: ~"wiki", path: ~"http://en.wikipedia.org/wiki/"});
+	assert store.namespaces.contains(&Namespace {prefix: ~"v", path: ~"http://www.w3.org/2006/vcard/ns#"});
+	
+	// The blank node from other had the same name as one of store's blank nodes.
+	assert store.find_object(~"got:Arya_Stark", ~"wiki:org") == option::Some(@BlankValue(~"_:ned-org-2"));
+	assert store.find_object(~"_:ned-org-2", ~"wiki:name") == option::Some(@StringValue(~"Faceless Men", ~""));
+	assert store.find_object(~"_:ned-org-0", ~"v:organisation-name") == option::Some(@StringValue(~"Small Council", ~""));
+	assert predicate_index_len(&store, "http://en.wikipedia.org/wiki/name") == 2;
+}
+
 #[test]
 fn trivial_bgp() 
 {
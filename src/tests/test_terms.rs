@@ -0,0 +1,73 @@
+use tests::test_data::*;
+use tests::test_helpers::*;
+
+#[test]
+fn interning()
+{
+	let terms = TermDictionary();
+	let ned = terms.intern(@StringValue(~"Ned", ~""));
+	assert terms.intern(@StringValue(~"Ned", ~"")) == ned;
+	assert terms.intern(@StringValue(~"Ned", ~"en")) != ned;
+	assert terms.intern(@TypedValue(~"Ned", ~"http://www.w3.org/2001/XMLSchema#string")) != ned;
+	assert terms.intern(@StringValue(~"ed", ~"N")) != terms.intern(@StringValue(~"Ned", ~""));
+	assert terms.intern(@InvalidValue(~"Ned", ~"http://www.w3.org/2001/XMLSchema#string")) != terms.intern(@TypedValue(~"Ned", ~"http://www.w3.org/2001/XMLSchema#string"));
+	assert terms.intern(@IntValue(35i64)) != terms.intern(@TypedValue(~"35", ~"http://www.w3.org/2001/XMLSchema#integer"));
+	assert terms.intern(@FloatValue(0.1f64 + 0.2f64)) != terms.intern(@FloatValue(0.3f64));
+	assert terms.intern(@FloatValue(0.5f64)) == terms.intern(@FloatValue(0.5f64));
+	
+	let iri = terms.intern_name("http://example.org/ned");
+	let blank = terms.intern_name("_:ned");
+	assert iri != blank;
+	assert terms.find(@IriValue(~"http://example.org/ned")) == option::Some(iri);
+	assert terms.find(@BlankValue(~"_:ned")) == option::Some(blank);
+	assert terms.find_name("http://example.org/jon").is_none();
+	
+	assert *terms.get(ned) == StringValue(~"Ned", ~"");
+	assert check_strs(terms.get_name(iri), ~"http://example.org/ned");
+	assert check_strs(terms.get_name(blank), ~"_:ned");
+	
+	terms.clear();
+	assert terms.find_name("_:ned").is_none();
+	assert terms.terms.len() == 0;
+}
+
+#[test]
+fn store_terms()
+{
+	let store = got_cast3();
+	let expected = sorted_ntriples(&store);
+	let count = store.terms.terms.len();
+	
+	// Adding a triple with existing terms doesn't add to the dictionary.
+	store.add_triple(~[], {subject: ~"got:Sandor_Clegane", predicate: ~"v:nickname", object: @StringValue(~"Ned", ~"")});
+	assert store.terms.terms.len() == count;
+	
+	// And triples are decoded on the way out.
+	store.remove_triple(~[], {subject: ~"got:Sandor_Clegane", predicate: ~"v:nickname", object: @StringValue(~"Ned", ~"")});
+	assert check_strs(sorted_ntriples(&store), expected);
+	assert store.find_objects(~"got:Eddard_Stark", ~"v:nickname") == ~[@StringValue(~"Ned", ~"")];
+}
+
+#[test]
+fn compact_terms()
+{
+	let store = got_cast3();
+	let count = store.terms.terms.len();
+	
+	// Removing triples leaves their terms in the dictionary.
+	assert store.remove_subject(~"got:Sandor_Clegane") == 2;
+	assert store.terms.terms.len() == count;
+	let expected = sorted_ntriples(&store);
+	
+	// The subject and the two literals are no longer used but the predicates are.
+	assert store.compact_terms() == 3;
+	assert store.terms.terms.len() == count - 3;
+	assert store.terms.find(@StringValue(~"The Hound", ~"")).is_none();
+	assert store.compact_terms() == 0;
+	
+	// The indexes use the new ids.
+	assert check_strs(sorted_ntriples(&store), expected);
+	assert store.find_objects(~"got:Jon_Snow", ~"v:pet") == ~[@StringValue(~"Ghost", ~"")];
+	assert predicate_index_len(&store, "http://www.w3.org/2006/vcard/ns#nickname") == 2;
+	assert object_index_len(&store, "_:jon-org-1") == 1;
+}
@@ -0,0 +1,102 @@
+use tests::test_data::*;
+use tests::test_helpers::*;
+
+#[test]
+fn rollback()
+{
+	let store = got_cast3();
+	let expected = sorted_ntriples(&store);
+	let next_blank = store.next_blank;
+	let num_namespaces = store.namespaces.len();
+	
+	store.begin();
+	store.add_triple(~[], {subject: ~"got:Jon_Snow", predicate: ~"v:pet", object: @StringValue(~"Ghost", ~"")});
+	store.replace_triple(~[], {subject: ~"got:Eddard_Stark", predicate: ~"v:org", object: @IriValue(~"got:Small_Council")});
+	store.remove_subject(~"got:Sandor_Clegane");
+	store.add_list(~"got:Jon_Snow", ~"v:sibling", ~[@IriValue(~"got:Arya_Stark"), @IriValue(~"got:Bran_Stark")]);
+	add_namespace(&store, Namespace {prefix: ~"ex", path: ~"http://example.org/"});
+	store.clear();
+	store.add(~"got:Eddard_Stark", ~[(~"v:nickname", @StringValue(~"Ned", ~""))]);
+	assert store.pending_changes().len() > 0;
+	store.rollback();
+	
+	assert check_strs(sorted_ntriples(&store), expected);
+	assert store.next_blank == next_blank;
+	assert store.namespaces.len() == num_namespaces;
+	assert store.transaction.is_none();
+	assert store.pending_changes().is_empty();
+	
+	assert predicate_index_len(&store, "http://www.w3.org/2006/vcard/ns#nickname") == 3;
+	assert object_index_len(&store, "_:ned-org-0") == 1;
+	assert object_index_len(&store, "http://awoiaf.westeros.org/index.php/Small_Council") == 0;
+}
+
+#[test]
+fn commit()
+{
+	let store = got_cast1();
+	store.begin();
+	store.add_triple(~[], {subject: ~"got:Eddard_Stark", predicate: ~"v:honorific-prefix", object: @StringValue(~"Lord", ~"")});
+	store.replace_triple(~[], {subject: ~"got:Eddard_Stark", predicate: ~"v:nickname", object: @StringValue(~"Lord Stark", ~"")});
+	store.remove_triple(~[], {subject: ~"got:Eddard_Stark", predicate: ~"v:fn", object: @StringValue(~"Eddard Stark", ~"")});
+	
+	let changes = do store.pending_changes().map |change| {change.to_str()};
+	assert check_strs(str::connect(changes, "\n"), str::connect(~[
+		~"added {http://awoiaf.westeros.org/index.php/Eddard_Stark, http://www.w3.org/2006/vcard/ns#honorific-prefix, \"Lord\"}",
+		~"replaced \"Ned\" with {http://awoiaf.westeros.org/index.php/Eddard_Stark, http://www.w3.org/2006/vcard/ns#nickname, \"Lord Stark\"}",
+		~"removed {http://awoiaf.westeros.org/index.php/Eddard_Stark, http://www.w3.org/2006/vcard/ns#fn, \"Eddard Stark\"}",
+	], "\n"));
+	
+	store.commit();
+	assert store.pending_changes().is_empty();
+	assert *store.find_object(~"got:Eddard_Stark", ~"v:nickname").get() == StringValue(~"Lord Stark", ~"");
+	assert store.find_object(~"got:Eddard_Stark", ~"v:fn").is_none();
+}
+
+#[test]
+fn with_transaction()
+{
+	let store = got_cast1();
+	let expected = sorted_ntriples(&store);
+	
+	let result = do store.with_transaction
+	{
+		do load_turtle(&store, "@prefix ex: <http://example.org/> . ex:winterfell ex:ruler ex:Eddard_Stark .").chain
+		|_unit|
+		{
+			load_turtle(&store, "ex:kings_landing ex:ruler")
+		}
+	};
+	assert result.is_err();
+	assert check_strs(sorted_ntriples(&store), expected);
+	assert vec::find(store.namespaces, |n| {n.prefix == ~"ex"}).is_none();
+	
+	let result = do store.with_transaction
+	{
+		load_turtle(&store, "@prefix ex: <http://example.org/> . ex:winterfell ex:ruler ex:Eddard_Stark .")
+	};
+	assert result.is_ok();
+	assert store.find_object(~"ex:winterfell", ~"ex:ruler").is_some();
+}
+
+#[test]
+fn rollback_clear_with_listener()
+{
+	let store = got_cast1();
+	let expected = sorted_ntriples(&store);
+	let changes = @DVec();
+	let _id = do store.add_listener |change| {changes.push(change.to_str())};
+	
+	store.begin();
+	store.clear();
+	assert store.pending_changes().len() == 2;
+	store.rollback();
+	
+	assert check_strs(sorted_ntriples(&store), expected);
+	let changes = changes.get();
+	assert changes.len() == 4;
+	assert check_strs(copy changes[0], ~"cleared");
+	assert changes[1].starts_with("added ");
+	assert changes[2].starts_with("added ");
+	assert check_strs(copy changes[3], ~"rolled back");
+}
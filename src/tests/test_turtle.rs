@@ -0,0 +1,231 @@
+use tests::test_data::*;
+use tests::test_helpers::*;
+
+fn load(text: &str) -> Store
+{
+	let store = Store(~[], &HashMap());
+	match load_turtle(&store, text)
+	{
+		result::Ok(()) =>
+		{
+			store
+		}
+		result::Err(ref mesg) =>
+		{
+			fail fmt!("Turtle error: %s", *mesg);
+		}
+	}
+}
+
+fn triples(store: &Store) -> ~[Triple]
+{
+	let mut actual = ~[];
+	for store.each
+	|triple|
+	{
+		vec::push(&mut actual, copy *triple);
+	};
+	actual
+}
+
+#[test]
+fn turtle_prefixes()
+{
+	let store = load("
+@prefix got: <http://awoiaf.westeros.org/index.php/> .
+@prefix v: <http://www.w3.org/2006/vcard/ns#> .
+
+got:Eddard_Stark v:fn \"Eddard Stark\" ;
+	v:nickname \"Ned\"@en, 'Lord Stark' .		# comments are ignored
+");
+	
+	let expected = ~[
+		make_triple_str(&store, ~"got:Eddard_Stark", ~"v:fn", ~"Eddard Stark"),
+		{subject: ~"http://awoiaf.westeros.org/index.php/Eddard_Stark", predicate: ~"http://www.w3.org/2006/vcard/ns#nickname", object: @StringValue(~"Ned", ~"en")},
+		make_triple_str(&store, ~"got:Eddard_Stark", ~"v:nickname", ~"Lord Stark"),
+	];
+	assert check_triples(triples(&store), expected);
+	assert store.namespaces.contains(&Namespace {prefix: ~"got", path: ~"http://awoiaf.westeros.org/index.php/"});
+}
+
+#[test]
+fn turtle_base()
+{
+	let store = load("
+@base <http://example.org/people/> .
+<bob> a <#Person> ;
+	<http://xmlns.com/foaf/0.1/knows> </alice> .
+");
+	
+	let expected = ~[
+		make_triple_uri(&store, ~"http://example.org/people/bob", ~"rdf:type", ~"http://example.org/people/#Person"),
+		make_triple_uri(&store, ~"http://example.org/people/bob", ~"http://xmlns.com/foaf/0.1/knows", ~"http://example.org/alice"),
+	];
+	assert check_triples(triples(&store), expected);
+}
+
+#[test]
+fn turtle_literals()
+{
+	let store = load("
+@prefix ex: <http://example.org/> .
+ex:x ex:int 42 ;
+	ex:neg -3 ;
+	ex:dbl 1.5e0 ;
+	ex:flag true ;
+	ex:typed \"7\"^^xsd:integer ;
+	ex:custom \"abc\"^^ex:code ;
+	ex:escaped \"a\\tb\\\"c\" .
+");
+	
+	let subject = ~"http://example.org/x";
+	assert store.find_object(subject, ~"http://example.org/int") == option::Some(@IntValue(42i64));
+	assert store.find_object(subject, ~"http://example.org/neg") == option::Some(@IntValue(-3i64));
+	assert store.find_object(subject, ~"http://example.org/dbl") == option::Some(@FloatValue(1.5f64));
+	assert store.find_object(subject, ~"http://example.org/flag") == option::Some(@BoolValue(true));
+	assert store.find_object(subject, ~"http://example.org/typed") == option::Some(@IntValue(7i64));
+	assert store.find_object(subject, ~"http://example.org/custom") == option::Some(@TypedValue(~"abc", ~"http://example.org/code"));
+	assert store.find_object(subject, ~"http://example.org/escaped") == option::Some(@StringValue(~"a\tb\"c", ~""));
+}
+
+#[test]
+fn turtle_blanks()
+{
+	let store = load("
+@prefix ex: <http://example.org/> .
+_:a ex:knows _:b .
+_:b ex:knows _:a .
+ex:x ex:org [ ex:name \"Night's Watch\" ] .
+");
+	
+	let expected = ~[
+		make_triple_blank(&store, ~"_:a-0", ~"http://example.org/knows", ~"b-1"),
+		make_triple_blank(&store, ~"_:b-1", ~"http://example.org/knows", ~"a-0"),
+		make_triple_blank(&store, ~"http://example.org/x", ~"http://example.org/org", ~"anon-2"),
+		make_triple_str(&store, ~"_:anon-2", ~"http://example.org/name", ~"Night's Watch"),
+	];
+	assert check_triples(triples(&store), expected);
+}
+
+#[test]
+fn turtle_collection()
+{
+	let store = load("
+@prefix ex: <http://example.org/> .
+ex:westeros ex:cities (\"Lanisport\" \"Oldtown\") ;
+	ex:empty () .
+");
+	
+	let expected = ~[
+		make_triple_blank(&store, ~"http://example.org/westeros", ~"http://example.org/cities", ~"list-0"),
+		make_triple_uri(&store, ~"http://example.org/westeros", ~"http://example.org/empty", ~"rdf:nil"),
+		make_triple_str(&store, ~"_:list-0", ~"rdf:first", ~"Lanisport"),
+		make_triple_blank(&store, ~"_:list-0", ~"rdf:rest", ~"list-1"),
+		make_triple_str(&store, ~"_:list-1", ~"rdf:first", ~"Oldtown"),
+		make_triple_uri(&store, ~"_:list-1", ~"rdf:rest", ~"rdf:nil"),
+	];
+	assert check_triples(triples(&store), expected);
+}
+
+#[test]
+fn turtle_errors()
+{
+	let store = Store(~[], &HashMap());
+	
+	let result = load_turtle(&store, "foo:x foo:y foo:z .");
+	assert result.is_err();
+	assert check_strs(result.get_err(), ~"Prefix 'foo' has not been declared.");
+	
+	let result = load_turtle(&store, "<x> <y> .");
+	assert result.is_err();
+}
+
+#[test]
+fn turtle_reader()
+{
+	let store = Store(~[], &HashMap());
+	let result = do io::with_str_reader("<http://example.org/x> <http://example.org/y> \"z\" .") |reader|
+	{
+		load_turtle_reader(&store, reader)
+	};
+	assert result.is_ok();
+	
+	let expected = ~[make_triple_str(&store, ~"http://example.org/x", ~"http://example.org/y", ~"z")];
+	assert check_triples(triples(&store), expected);
+}
+
+#[test]
+fn turtle_writer()
+{
+	let store = got_cast1();
+	store.add_list(~"got:westeros", ~"got:cities", ~[@StringValue(~"Lanisport", ~""), @IntValue(3i64)]);
+	store.add(~"got:westeros", ~[
+		(~"rdf:type", @IriValue(~"got:Continent")),
+		(~"got:motto", @StringValue(~"Winter is \"coming\"", ~"en")),
+		(~"got:area", @FloatValue(1.5f64)),
+	]);
+	
+	let actual = store_to_turtle(&store);
+	let expected = ~"@prefix got: <http://awoiaf.westeros.org/index.php/> .
+@prefix owl: <http://www.w3.org/2002/07/owl#> .
+@prefix rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#> .
+@prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+@prefix v: <http://www.w3.org/2006/vcard/ns#> .
+@prefix xsd: <http://www.w3.org/2001/XMLSchema#> .
+
+got:Eddard_Stark
+	v:fn \"Eddard Stark\" ;
+	v:nickname \"Ned\" .
+got:westeros
+	got:area \"1.5\"^^xsd:double ;
+	got:cities (\"Lanisport\" 3) ;
+	got:motto \"Winter is \\\"coming\\\"\"@en ;
+	a got:Continent .
+";
+	assert check_strs(actual, expected);
+}
+
+#[test]
+fn turtle_round_trip()
+{
+	let store = got_cast3();
+	store.add_list(~"got:westeros", ~"got:cities", ~[@StringValue(~"Lanisport", ~""), @StringValue(~"Oldtown", ~"")]);
+	
+	let loaded = Store(~[], &HashMap());
+	assert load_turtle(&loaded, store_to_turtle(&store)).is_ok();
+	
+	assert loaded.find_object(~"got:Jon_Snow", ~"v:nickname") == option::Some(@StringValue(~"Lord Snow", ~""));
+	assert loaded.find_objects(~"got:Eddard_Stark", ~"v:org").len() == 1;
+	assert loaded.find_objects(~"got:westeros", ~"got:cities").len() == 1;
+}
+
+#[test]
+fn turtle_escapes_round_trip()
+{
+	let store = Store(~[], &HashMap());
+	store.add(~"http://example.org/dir\\name", ~[
+		(~"http://example.org/path", @StringValue(~"C:\\dir\\", ~"")),
+		(~"http://example.org/control", @StringValue(~"bell\x07 \"quoted\"", ~"")),
+		(~"http://example.org/link", @IriValue(~"http://example.org/my \"docs\"")),
+	]);
+	
+	let loaded = Store(~[], &HashMap());
+	match load_turtle(&loaded, store_to_turtle(&store))
+	{
+		result::Ok(()) => {}
+		result::Err(ref mesg) => fail fmt!("Turtle error: %s", *mesg),
+	}
+	
+	let subject = ~"http://example.org/dir\\name";
+	assert loaded.find_object(subject, ~"http://example.org/path") == option::Some(@StringValue(~"C:\\dir\\", ~""));
+	assert loaded.find_object(subject, ~"http://example.org/control") == option::Some(@StringValue(~"bell\x07 \"quoted\"", ~""));
+	assert loaded.find_object(subject, ~"http://example.org/link") == option::Some(@IriValue(~"http://example.org/my \"docs\""));
+}
+
+#[test]
+fn turtle_unicode_escapes()
+{
+	let store = load("<http://example.org/caf\\u00E9> <http://example.org/name> \"caf\\u00E9 \\U0001F600\\\\\" .");
+	
+	assert store.find_object(~"http://example.org/café", ~"http://example.org/name") == option::Some(@StringValue(~"café \U0001F600\\", ~""));
+}
@@ -0,0 +1,170 @@
+//! Support for grouping store changes so that they can be committed or rolled back as a unit
+//! (see Store::begin, Store::commit, and Store::rollback).
+
+/// A change made to a store (see Store::pending_changes and Store::add_listener). Names are fully expanded.
+pub enum Change
+{
+	AddedTriple(Triple),
+	RemovedTriple(Triple),
+	ReplacedTriple(Triple, @Object),		// triple has the new object, the second field is the old object
+	ClearedStore,								// only used with listeners (transactions record a RemovedTriple for each triple)
+	CommittedTransaction,						// only used with listeners
+	RolledBackTransaction,					// only used with listeners (sent after the changes which undo the transaction)
+}
+
+/// The state of the store when the transaction began along with the changes made since then.
+pub type Transaction = {next_blank: int, namespaces: ~[Namespace], changes: @DVec<Change>};
+
+pub impl Change : ToStr
+{
+	pure fn to_str() -> ~str
+	{
+		match self
+		{
+			AddedTriple(ref triple) =>
+			{
+				~"added " + triple.to_str()
+			}
+			RemovedTriple(ref triple) =>
+			{
+				~"removed " + triple.to_str()
+			}
+			ReplacedTriple(ref triple, old) =>
+			{
+				fmt!("replaced %s with %s", old.to_str(), triple.to_str())
+			}
+			ClearedStore =>
+			{
+				~"cleared"
+			}
+			CommittedTransaction =>
+			{
+				~"committed"
+			}
+			RolledBackTransaction =>
+			{
+				~"rolled back"
+			}
+		}
+	}
+}
+
+// ---- Private Functions -----------------------------------------------------
+priv fn begin_transaction(store: &Store)
+{
+	if store.transaction.is_some()
+	{
+		fail ~"begin was called while a transaction was in progress.";
+	}
+	
+	store.transaction = option::Some(@{next_blank: store.next_blank, namespaces: copy store.namespaces, changes: @DVec()});
+}
+
+priv fn commit_transaction(store: &Store)
+{
+	if store.transaction.is_none()
+	{
+		fail ~"commit was called without begin.";
+	}
+	
+	store.transaction = option::None;
+	store_changed(store, CommittedTransaction);
+}
+
+priv fn rollback_transaction(store: &Store)
+{
+	match store.transaction
+	{
+		option::Some(transaction) =>
+		{
+			// Undo the changes using a scratch transaction so that listeners can tell that the
+			// changes are part of the transaction but they aren't recorded.
+			store.transaction = option::Some(@{next_blank: transaction.next_blank, namespaces: copy transaction.namespaces, changes: @DVec()});
+			
+			let changes = transaction.changes.get();
+			let mut i = changes.len();
+			while i > 0
+			{
+				i -= 1;
+				match changes[i]
+				{
+					AddedTriple(ref triple) =>
+					{
+						undo_add(store, triple);
+					}
+					RemovedTriple(ref triple) =>
+					{
+						add_entries(store, copy triple.subject, ~[{predicate: copy triple.predicate, object: triple.object}]);
+					}
+					ReplacedTriple(ref triple, old) =>
+					{
+						undo_replace(store, triple, old);
+					}
+					ClearedStore | CommittedTransaction | RolledBackTransaction =>
+					{
+						fail fmt!("%s should not be recorded.", changes[i].to_str());
+					}
+				}
+			}
+			
+			store.transaction = option::None;
+			store.next_blank = transaction.next_blank;
+			store.namespaces = copy transaction.namespaces;
+			store_changed(store, RolledBackTransaction);
+		}
+		option::None =>
+		{
+			fail ~"rollback was called without begin.";
+		}
+	}
+}
+
+// Only changes to the triples are recorded: clear records a RemovedTriple for each triple
+// before its ClearedStore and the transaction changes don't need to be undone.
+priv fn record_change(store: &Store, change: Change)
+{
+	match (store.transaction, change)
+	{
+		(_, ClearedStore) | (_, CommittedTransaction) | (_, RolledBackTransaction) =>
+		{
+		}
+		(option::Some(transaction), change) =>
+		{
+			transaction.changes.push(change);
+		}
+		(option::None, _) =>
+		{
+		}
+	}
+}
+
+// Objects are compared using their ids so that we undo an identical copy of a duplicated
+// triple.
+priv fn find_entry(store: &Store, triple: &Triple) -> (TermId, @DVec<TermEntry>, uint)
+{
+	let object = store.terms.find(triple.object);
+	match find_entry_index(store, triple, |id| {option::Some(id) == object})
+	{
+		option::Some(result) =>
+		{
+			result
+		}
+		option::None =>
+		{
+			fail fmt!("Couldn't find %s while rolling back a transaction.", triple.to_str());
+		}
+	}
+}
+
+priv fn undo_add(store: &Store, triple: &Triple)
+{
+	let (subject, entries, index) = find_entry(store, triple);
+	remove_entry_at(store, subject, entries, index);
+}
+
+priv fn undo_replace(store: &Store, triple: &Triple, old: @Object)
+{
+	let (subject, entries, index) = find_entry(store, triple);
+	replace_entry_at(store, subject, entries, index, old);
+	store_changed(store, ReplacedTriple({subject: copy triple.subject, predicate: copy triple.predicate, object: old}, triple.object));
+}
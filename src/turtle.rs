@@ -0,0 +1,1039 @@
+//! Loads and saves stores using the turtle format (http://www.w3.org/TR/2011/WD-turtle-20110809)
+//! and datasets using the TriG format (http://www.w3.org/TR/trig/).
+use io::WriterUtil;
+use rparse::{Parser, StringParsers, GenericParsers, Combinators, forward_ref};
+
+use bug4260::{seq2, seq3, seq4, seq2_ret0, seq2_ret1, seq3_ret1, or_v, ret, Combinators2};
+use sparql::{pn_local, pname_ns, prefixed_name, iri_ref, string_text, langtag, numeric_literal, MyParserTrait};
+
+// Parsing produces a list of statements which are then added to the store. This allows
+// prefixes and blank node labels to be resolved in document order.
+enum Node
+{
+	IriNode(~str),					// contents of an IRI_REF (may be relative)
+	PrefixedNode(~str),				// PNAME_LN or PNAME_NS
+	LabelNode(~str),				// _:name
+	AnonNode(~[Property]),			// [ predicateObjectList ]
+	CollectionNode(~[Node]),		// ( object* )
+	StringNode(~str, ~str),		// value + lang (escapes have not been expanded)
+	TypedNode(~str, @Node),		// value + datatype IRI
+	ValueNode(Object)				// numeric and boolean literals
+}
+
+struct Property {verb: Node, objects: ~[Node]}
+
+enum Statement
+{
+	PrefixStatement(~str, ~str),			// prefix (without the colon) + IRI
+	BaseStatement(~str),
+	TriplesStatement(Node, ~[Property]),
+	GraphStatement(option::Option<Node>, ~[Statement])		// TriG graph name (if any) + triples
+}
+
+// Lists that can be written using the collection syntax.
+struct Lists
+{
+	items: HashMap<@~str, @~[@Object]>,		// maps the blank node at the head of a list to the list items
+	skipped: HashMap<@~str, bool>,			// blank nodes that are written as part of a list
+}
+
+// State used while adding statements to the store.
+struct Loader
+{
+	mut base: ~str,
+	blanks: HashMap<@~str, @~str>,		// maps document labels to store blank names
+}
+
+/// Parses turtle text and adds the resulting triples to the store.
+///
+/// Prefixes declared with @prefix are added to the store's namespaces. Blank node
+/// labels are renamed using get_blank_name so they will not collide with blank
+/// nodes already in the store. Returns an error message if the text could not be
+/// parsed (in which case triples before the error may have been added).
+pub fn load_turtle(store: &Store, text: &str) -> result::Result<(), ~str>
+{
+	let parser = make_parser(false);
+	match parser.parse(@~"turtle", text)
+	{
+		result::Ok(statements) =>
+		{
+			let loader = Loader {base: ~"", blanks: HashMap()};
+			for vec::each(*statements) |statement|
+			{
+				match add_statement(store, &loader, statement)
+				{
+					result::Ok(()) => (),
+					result::Err(copy err) => return result::Err(err),
+				}
+			}
+			result::Ok(())
+		}
+		result::Err(ref err) =>
+		{
+			result::Err(fmt!("%s on line %? col %?", *err.mesg, err.line, err.col))
+		}
+	}
+}
+
+/// Like load_turtle except that the text is read from a reader.
+pub fn load_turtle_reader(store: &Store, reader: io::Reader) -> result::Result<(), ~str>
+{
+	let bytes = reader.read_whole_stream();
+	if str::is_utf8(bytes)
+	{
+		load_turtle(store, str::from_bytes(bytes))
+	}
+	else
+	{
+		result::Err(~"Turtle documents must be UTF-8.")
+	}
+}
+
+/// Parses TriG text and adds the resulting triples to the dataset.
+///
+/// Triples outside a graph block are added to the default graph. Prefixes are added
+/// to every graph in the dataset and blank node labels are shared by all the graphs
+/// in the document.
+pub fn load_trig(dataset: &Dataset, text: &str) -> result::Result<(), ~str>
+{
+	let parser = make_parser(true);
+	match parser.parse(@~"trig", text)
+	{
+		result::Ok(statements) =>
+		{
+			let loader = Loader {base: ~"", blanks: HashMap()};
+			for vec::each(*statements) |statement|
+			{
+				match add_dataset_statement(dataset, &loader, statement)
+				{
+					result::Ok(()) => (),
+					result::Err(copy err) => return result::Err(err),
+				}
+			}
+			result::Ok(())
+		}
+		result::Err(ref err) =>
+		{
+			result::Err(fmt!("%s on line %? col %?", *err.mesg, err.line, err.col))
+		}
+	}
+}
+
+/// Like load_trig except that the text is read from a reader.
+pub fn load_trig_reader(dataset: &Dataset, reader: io::Reader) -> result::Result<(), ~str>
+{
+	let bytes = reader.read_whole_stream();
+	if str::is_utf8(bytes)
+	{
+		load_trig(dataset, str::from_bytes(bytes))
+	}
+	else
+	{
+		result::Err(~"TriG documents must be UTF-8.")
+	}
+}
+
+/// Expands the escape sequences used within turtle and SPARQL strings and IRIs.
+pub fn unescape_string(text: &str) -> ~str
+{
+	let chars = str::chars(text);
+	let mut result = ~"";
+	str::reserve(&mut result, str::len(text));
+	
+	let mut i = 0;
+	while i < chars.len()
+	{
+		if chars[i] == '\\' && i + 1 < chars.len()
+		{
+			let (ch, count) = match chars[i + 1]
+			{
+				't'	=> ('\t', 2),
+				'b'	=> ('\x08', 2),
+				'n'	=> ('\n', 2),
+				'r'	=> ('\r', 2),
+				'f'	=> ('\x0c', 2),
+				'u'	=> unescape_hex(chars, i, 4),
+				'U'	=> unescape_hex(chars, i, 8),
+				ch	=> (ch, 2),						// ", ', and \
+			};
+			str::push_char(&mut result, ch);
+			i += count;
+		}
+		else
+		{
+			str::push_char(&mut result, chars[i]);
+			i += 1;
+		}
+	}
+	
+	result
+}
+
+/// Writes the store using the turtle format.
+/// 
+/// Subjects, predicates, and objects are sorted so that the output is stable. IRIs are
+/// contracted using the store's namespaces and lists added with add_list are written
+/// using the collection syntax.
+pub fn write_turtle(store: &Store, writer: io::Writer)
+{
+	let namespaces = write_prefixes(store, writer);
+	write_statements(store, namespaces, "", writer);
+}
+
+/// Returns the store formatted using the turtle format.
+pub fn store_to_turtle(store: &Store) -> ~str
+{
+	do io::with_str_writer |writer| {write_turtle(store, writer)}
+}
+
+/// Writes the dataset using the TriG format.
+/// 
+/// The default graph's triples are written first followed by a block for each named
+/// graph (in sorted order). Otherwise this works like write_turtle.
+pub fn write_trig(dataset: &Dataset, writer: io::Writer)
+{
+	let namespaces = write_prefixes(dataset.default_graph, writer);
+	write_statements(dataset.default_graph, namespaces, "", writer);
+	
+	for dataset.get_graph_names().each |name|
+	{
+		writer.write_line(fmt!("%s {", subject_to_turtle(namespaces, *name)));
+		write_statements(dataset.get_graph(*name).get(), namespaces, "\t", writer);
+		writer.write_line("}");
+	}
+}
+
+/// Returns the dataset formatted using the TriG format.
+pub fn dataset_to_trig(dataset: &Dataset) -> ~str
+{
+	do io::with_str_writer |writer| {write_trig(dataset, writer)}
+}
+
+/// Escapes a string so that it can be used within a quoted turtle or N-Triples literal.
+pub fn escape_string(text: &str) -> ~str
+{
+	let mut result = ~"";
+	str::reserve(&mut result, str::len(text));
+	
+	for str::each_char(text) |ch|
+	{
+		match ch
+		{
+			'\t'	=> str::push_str(&mut result, "\\t"),
+			'\x08'	=> str::push_str(&mut result, "\\b"),
+			'\n'	=> str::push_str(&mut result, "\\n"),
+			'\r'	=> str::push_str(&mut result, "\\r"),
+			'\x0c'	=> str::push_str(&mut result, "\\f"),
+			'"'		=> str::push_str(&mut result, "\\\""),
+			'\\'	=> str::push_str(&mut result, "\\\\"),
+			_ if (ch as uint) < 0x20 => str::push_str(&mut result, fmt!("\\u%04X", ch as uint)),
+			_		=> str::push_char(&mut result, ch),
+		}
+	}
+	
+	result
+}
+
+/// Escapes the characters which are not allowed within an IRI_REF.
+pub fn escape_iri(iri: &str) -> ~str
+{
+	let mut result = ~"";
+	str::reserve(&mut result, str::len(iri));
+	
+	for str::each_char(iri) |ch|
+	{
+		if (ch as uint) <= 0x20 || str::contains_char("<>\"{}|^`\\", ch)
+		{
+			str::push_str(&mut result, fmt!("\\u%04X", ch as uint));
+		}
+		else
+		{
+			str::push_char(&mut result, ch);
+		}
+	}
+	
+	result
+}
+
+/// Returns a blank node label which is legal in both turtle and N-Triples.
+///
+/// Blank node names are generated by get_blank_name from labels that may contain
+/// characters which are not legal in turtle so those are replaced with hex escapes.
+pub fn blank_to_turtle(name: &str) -> ~str
+{
+	let mut result = ~"_:";
+	for str::each_chari(str::slice(name, 2, str::len(name))) |i, ch|
+	{
+		if char::is_alphanumeric(ch) || ch == '_' || (ch == '-' && i > 0)
+		{
+			str::push_char(&mut result, ch);
+		}
+		else
+		{
+			str::push_str(&mut result, fmt!("_%X", ch as uint));
+		}
+	}
+	result
+}
+
+/// Resolves a possibly relative IRI against a base IRI.
+///
+/// This follows RFC 3986 section 5.2 except that dot segments are not removed.
+pub fn resolve_iri(base: &str, iri: &str) -> ~str
+{
+	if base.is_empty() || has_scheme(iri)
+	{
+		iri.to_owned()
+	}
+	else if iri.is_empty()
+	{
+		strip_fragment(base)
+	}
+	else if iri.starts_with("#")
+	{
+		strip_fragment(base) + iri
+	}
+	else if iri.starts_with("//")
+	{
+		match str::find_char(base, ':')
+		{
+			option::Some(i) => str::slice(base, 0, i + 1) + iri,
+			option::None => iri.to_owned(),
+		}
+	}
+	else if iri.starts_with("/")
+	{
+		// Keep the scheme and authority.
+		match str::find_str(base, "://")
+		{
+			option::Some(i) =>
+			{
+				match str::find_char_from(base, '/', i + 3)
+				{
+					option::Some(j) => str::slice(base, 0, j) + iri,
+					option::None => base + iri,
+				}
+			}
+			option::None =>
+			{
+				iri.to_owned()
+			}
+		}
+	}
+	else
+	{
+		let base = strip_fragment(base);
+		match str::rfind_char(base, '/')
+		{
+			option::Some(i) => str::slice(base, 0, i + 1) + iri,
+			option::None => base + iri,
+		}
+	}
+}
+
+// ---- Private Functions -----------------------------------------------------
+// Returns the character for the \u or \U escape at index along with the number of
+// characters in the escape. Malformed escapes are treated like other unknown escapes.
+priv fn unescape_hex(chars: &[char], index: uint, digits: uint) -> (char, uint)
+{
+	let mut value = 0u;
+	for uint::range(index + 2, index + 2 + digits) |i|
+	{
+		if i >= chars.len()
+		{
+			return (chars[index + 1], 2);
+		}
+		match char::to_digit(chars[i], 16)
+		{
+			option::Some(d) => value = 16*value + d,
+			option::None => return (chars[index + 1], 2),
+		}
+	}
+	
+	if value <= 0x10FFFF
+	{
+		(value as char, digits + 2)
+	}
+	else
+	{
+		(chars[index + 1], 2)
+	}
+}
+
+// http://www.w3.org/TR/2011/WD-turtle-20110809/#sec-grammar-grammar
+// If trig is set then statements may also be graph blocks.
+priv fn make_parser(trig: bool) -> Parser<@~[Statement]>
+{
+	// [30] PNAME_NS ::= (PN_PREFIX)? ":"
+	let PNAME_NS = pname_ns();
+	
+	// [18] IRI_REF ::= '<' ([^<>\"{}|^`\\] - [#0000- ] | UCHAR)* '>'
+	let IRI_REF = iri_ref();
+	
+	// [15] IRIref ::= IRI_REF | PrefixedName
+	let IRIref = or_v(@~[
+		do IRI_REF.thene |v| {ret(IriNode(copy *v))},
+		do prefixed_name().thene |v| {ret(PrefixedNode(copy *v))}
+	]).note(~"IRIref");
+	
+	// [32] BLANK_NODE_LABEL ::= "_:" PN_LOCAL
+	let BLANK_NODE_LABEL = do seq2_ret1("_:".lit(), pn_local()).ws().thene |v| {ret(LabelNode(copy *v))};
+	
+	// [24] ANON ::= '[' WS* ']'
+	let ANON = do seq2("[".lit().ws(), "]".lit().ws()) |_l, _r| {result::Ok(AnonNode(~[]))};
+	
+	// [26] BooleanLiteral ::= 'true' | 'false'
+	let BooleanLiteral = do ("true".lit()).or("false".lit()).ws().thene
+		|v| {ret(ValueNode(literal_to_object(*v, "http://www.w3.org/2001/XMLSchema#boolean", "")))};
+	
+	// [22] NumericLiteral ::= INTEGER | DECIMAL | DOUBLE
+	let NumericLiteral = do numeric_literal().thene |v| {ret(ValueNode(v))};
+	
+	// [21] RDFLiteral ::= String ( LANGTAG | ( '^^' IRIref ) )?
+	let RDFLiteral1 = do string_text().thene |v| {ret(StringNode(copy *v, ~""))};
+	let RDFLiteral2 = do seq2(string_text(), langtag().ws())
+		|v, l| {result::Ok(StringNode(copy *v, copy *l))};
+	let RDFLiteral3 = do seq3(string_text(), "^^".lit(), IRIref)
+		|v, _m, t| {result::Ok(TypedNode(copy *v, @t))};
+	let RDFLiteral = or_v(@~[RDFLiteral3, RDFLiteral2, RDFLiteral1]);
+	
+	// [13] literal ::= RDFLiteral | NumericLiteral | BooleanLiteral
+	let literal = or_v(@~[RDFLiteral, NumericLiteral, BooleanLiteral]).note(~"literal");
+	
+	// [11] object ::= IRIref | blank | literal
+	let object_ptr = @mut ret(ValueNode(UnboundValue));
+	let object_ref = forward_ref(object_ptr);
+	
+	// [9] verb ::= predicate | "a"
+	let verb = IRIref.or(do "a".lit().ws().thene
+		|_v| {ret(IriNode(~"http://www.w3.org/1999/02/22-rdf-syntax-ns#type"))}).note(~"verb");
+	
+	// [8] objectList ::= object ( "," object )*
+	let objectList = object_ref.list(",".lit().ws());
+	
+	// [7] predicateObjectList ::= verb objectList ( ";" verb objectList )* ( ";")?
+	let property = do seq2(verb, objectList) |v, o| {result::Ok(Property {verb: v, objects: copy *o})};
+	let predicateObjectList = seq2_ret0(property.list(";".lit().ws()), ";".lit().ws().optional());
+	
+	// [16] blank ::= BlankNode | blankNodePropertyList | collection
+	// [17] blankNodePropertyList ::= "[" predicateObjectList "]"
+	let blankNodePropertyList = do seq3_ret1("[".lit().ws(), predicateObjectList, "]".lit().ws()).thene
+		|v| {ret(AnonNode(copy *v))};
+	
+	// [27] collection ::= "(" object* ")"
+	let collection = do seq3_ret1("(".lit().ws(), object_ref.r0(), ")".lit().ws()).thene
+		|v| {ret(CollectionNode(copy *v))};
+	
+	let blank = or_v(@~[BLANK_NODE_LABEL, ANON, blankNodePropertyList, collection]).note(~"blank");
+	
+	let object = or_v(@~[IRIref, blank, literal]).note(~"object");
+	*object_ptr = object;
+	
+	// [10] subject ::= IRIref | blank
+	let subject = IRIref.or(blank).note(~"subject");
+	
+	// [6] triples ::= subject predicateObjectList
+	// Turtle also allows a blankNodePropertyList to appear on its own.
+	let triples_body = do seq2(subject, predicateObjectList.optional())
+		|s, p|
+		{
+			match p
+			{
+				option::Some(properties) => result::Ok(TriplesStatement(s, copy *properties)),
+				option::None => result::Ok(TriplesStatement(s, ~[])),
+			}
+		};
+	let triples = seq2_ret0(triples_body, ".".lit().ws());
+	
+	// [4] prefixID ::= "@prefix" PNAME_NS IRI_REF
+	let prefixID = do seq4("@prefix".lit().ws(), PNAME_NS.ws(), IRI_REF, ".".lit().ws())
+		|_p, ns, r, _d| {result::Ok(PrefixStatement(str::slice(*ns, 0u, str::len(*ns)-1u), copy *r))};
+	
+	// [5] base ::= "@base" IRI_REF
+	let base = do seq3("@base".lit().ws(), IRI_REF, ".".lit().ws())
+		|_b, r, _d| {result::Ok(BaseStatement(copy *r))};
+	
+	// [2] statement ::= directive "." | triples "." | ws+
+	// [3] directive ::= prefixID | base
+	let statement = if trig
+	{
+		// TriG allows the final "." within a graph block to be omitted.
+		// graph ::= "GRAPH"? (IRIref | BLANK_NODE_LABEL)? "{" (triples ("." triples)* "."?)? "}"
+		let triplesBlock = seq2_ret0(triples_body.list(".".lit().ws()), ".".lit().ws().optional());
+		let wrappedGraph = seq3_ret1("{".lit().ws(), triplesBlock.optional(), "}".lit().ws());
+		let graph = do seq3("GRAPH".lit().ws().optional(), IRIref.or(BLANK_NODE_LABEL).optional(), wrappedGraph)
+			|_g, n, t|
+			{
+				match t
+				{
+					option::Some(statements) => result::Ok(GraphStatement(n, copy *statements)),
+					option::None => result::Ok(GraphStatement(n, ~[])),
+				}
+			};
+		or_v(@~[prefixID, base, graph, triples]).note(~"statement")
+	}
+	else
+	{
+		or_v(@~[prefixID, base, triples]).note(~"statement")
+	};
+	
+	// [1] turtleDoc ::= statement*
+	statement.r0().everything2(ret(0).ws())
+}
+
+// Returns the namespaces used to contract IRIs.
+priv fn write_prefixes(store: &Store, writer: io::Writer) -> ~[Namespace]
+{
+	let namespaces = do std::sort::merge_sort(store.namespaces) |x, y| {x.prefix <= y.prefix};
+	let namespaces = do vec::filter(namespaces) |n| {n.prefix != ~"_"};
+	for namespaces.each |namespace|
+	{
+		writer.write_line(fmt!("@prefix %s: <%s> .", namespace.prefix, escape_iri(namespace.path)));
+	}
+	if namespaces.is_not_empty()
+	{
+		writer.write_line("");
+	}
+	namespaces
+}
+
+priv fn write_statements(store: &Store, namespaces: &[Namespace], indent: &str, writer: io::Writer)
+{
+	let lists = find_lists(store);
+	for sorted_subjects(store).each |subject|
+	{
+		if !lists.skipped.contains_key(@copy *subject)
+		{
+			let entries = sorted_entries(store, find_entries(store, *subject).get());
+			writer.write_line(indent + subject_to_turtle(namespaces, *subject));
+			
+			let mut i = 0;
+			while i < entries.len()
+			{
+				let predicate = copy entries[i].predicate;
+				let mut objects = ~[];
+				while i < entries.len() && entries[i].predicate == predicate
+				{
+					objects.push(object_to_turtle(namespaces, &lists, entries[i].object));
+					i += 1;
+				}
+				
+				let separator = if i == entries.len() {" .\n"} else {" ;\n"};
+				writer.write_str(fmt!("%s\t%s %s%s", indent, predicate_to_turtle(namespaces, predicate), str::connect(objects, ", "), separator));
+			}
+		}
+	}
+}
+
+priv fn add_statement(store: &Store, loader: &Loader, statement: &Statement) -> result::Result<(), ~str>
+{
+	match *statement
+	{
+		PrefixStatement(ref prefix, ref iri) =>
+		{
+			add_namespace(store, Namespace {prefix: copy *prefix, path: resolve_iri(loader.base, *iri)});
+			result::Ok(())
+		}
+		BaseStatement(ref iri) =>
+		{
+			loader.base = resolve_iri(loader.base, *iri);
+			result::Ok(())
+		}
+		GraphStatement(*) =>
+		{
+			result::Err(~"Graphs can only be used with TriG.")
+		}
+		TriplesStatement(ref node, ref properties) =>
+		{
+			if properties.is_empty()
+			{
+				// "[ :p :o ] ." is legal but "<foo> ." is not.
+				match *node
+				{
+					AnonNode(ref inner) if inner.is_not_empty() =>
+					{
+						do node_to_object(store, loader, node).chain |_o| {result::Ok(())}
+					}
+					_ =>
+					{
+						result::Err(~"Expected a predicate object list.")
+					}
+				}
+			}
+			else
+			{
+				do node_to_object(store, loader, node).chain |subject|
+				{
+					match *subject
+					{
+						IriValue(ref name) | BlankValue(ref name) =>
+						{
+							add_properties(store, loader, *name, *properties)
+						}
+						_ =>
+						{
+							result::Err(fmt!("Literals cannot be used as subjects (%s).", subject.to_str()))
+						}
+					}
+				}
+			}
+		}
+	}
+}
+
+priv fn add_dataset_statement(dataset: &Dataset, loader: &Loader, statement: &Statement) -> result::Result<(), ~str>
+{
+	match *statement
+	{
+		PrefixStatement(ref prefix, ref iri) =>
+		{
+			add_dataset_namespace(dataset, Namespace {prefix: copy *prefix, path: resolve_iri(loader.base, *iri)});
+			result::Ok(())
+		}
+		GraphStatement(option::Some(ref name), ref statements) =>
+		{
+			match node_to_object(dataset.add_graph(""), loader, name)
+			{
+				result::Ok(@IriValue(ref graph)) | result::Ok(@BlankValue(ref graph)) =>
+				{
+					let store = dataset.add_graph(*graph);
+					for statements.each |statement|
+					{
+						match add_statement(store, loader, statement)
+						{
+							result::Ok(()) => (),
+							result::Err(copy err) => return result::Err(err),
+						}
+					}
+					result::Ok(())
+				}
+				result::Ok(ref value) =>
+				{
+					result::Err(fmt!("Graph names must be IRIs or blank nodes, not %s.", value.to_str()))
+				}
+				result::Err(copy err) =>
+				{
+					result::Err(err)
+				}
+			}
+		}
+		GraphStatement(option::None, ref statements) =>
+		{
+			for statements.each |statement|
+			{
+				match add_statement(dataset.add_graph(""), loader, statement)
+				{
+					result::Ok(()) => (),
+					result::Err(copy err) => return result::Err(err),
+				}
+			}
+			result::Ok(())
+		}
+		_ =>
+		{
+			add_statement(dataset.add_graph(""), loader, statement)
+		}
+	}
+}
+
+priv fn add_properties(store: &Store, loader: &Loader, subject: &str, properties: &[Property]) -> result::Result<(), ~str>
+{
+	for properties.each |property|
+	{
+		let predicate = match node_to_object(store, loader, &property.verb)
+		{
+			result::Ok(@IriValue(copy iri)) => iri,
+			result::Ok(ref value) => return result::Err(fmt!("Predicates must be IRIs, not %s.", value.to_str())),
+			result::Err(copy err) => return result::Err(err),
+		};
+		
+		for property.objects.each |node|
+		{
+			match node_to_object(store, loader, node)
+			{
+				result::Ok(object) =>
+				{
+					store.add_triple(~[], {subject: subject.to_owned(), predicate: copy predicate, object: object});
+				}
+				result::Err(copy err) =>
+				{
+					return result::Err(err);
+				}
+			}
+		}
+	}
+	result::Ok(())
+}
+
+// Converts a node into an object adding triples to the store for blank node property lists and collections.
+priv fn node_to_object(store: &Store, loader: &Loader, node: &Node) -> result::Result<@Object, ~str>
+{
+	match *node
+	{
+		IriNode(ref iri) =>
+		{
+			result::Ok(@IriValue(resolve_iri(loader.base, *iri)))
+		}
+		PrefixedNode(ref name) =>
+		{
+			do expand_pname(store, *name).chain |iri| {result::Ok(@IriValue(iri))}
+		}
+		LabelNode(ref label) =>
+		{
+			match loader.blanks.find(@copy *label)
+			{
+				option::Some(name) =>
+				{
+					result::Ok(@BlankValue(copy *name))
+				}
+				option::None =>
+				{
+					let name = get_blank_name(store, *label);
+					loader.blanks.insert(@copy *label, @copy name);
+					result::Ok(@BlankValue(name))
+				}
+			}
+		}
+		AnonNode(ref properties) =>
+		{
+			let name = get_blank_name(store, "anon");
+			do add_properties(store, loader, name, *properties).chain |_r| {result::Ok(@BlankValue(copy name))}
+		}
+		CollectionNode(ref nodes) =>
+		{
+			add_collection(store, loader, *nodes)
+		}
+		StringNode(ref value, ref lang) =>
+		{
+			result::Ok(@literal_to_object(unescape_string(*value), "http://www.w3.org/2001/XMLSchema#string", *lang))
+		}
+		TypedNode(ref value, kind) =>
+		{
+			do node_to_object(store, loader, kind).chain |kind|
+			{
+				match *kind
+				{
+					IriValue(ref iri) => result::Ok(@literal_to_object(unescape_string(*value), *iri, "")),
+					_ => result::Err(fmt!("Datatypes must be IRIs, not %s.", kind.to_str())),
+				}
+			}
+		}
+		ValueNode(copy value) =>
+		{
+			result::Ok(@value)
+		}
+	}
+}
+
+// Collections use the standard rdf:first/rdf:rest chain ending with rdf:nil. Unlike
+// StoreTrait::add_list there is no trailing node with only an rdf:rest (get_list and the
+// writer accept both) and an empty collection is simply rdf:nil.
+priv fn add_collection(store: &Store, loader: &Loader, nodes: &[Node]) -> result::Result<@Object, ~str>
+{
+	if nodes.is_empty()
+	{
+		return result::Ok(@IriValue(~"http://www.w3.org/1999/02/22-rdf-syntax-ns#nil"));
+	}
+	
+	let head = get_blank_name(store, "list");
+	let mut blank = copy head;
+	for nodes.eachi |i, node|
+	{
+		match node_to_object(store, loader, node)
+		{
+			result::Ok(object) =>
+			{
+				store.add_triple(~[], {subject: copy blank, predicate: ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#first", object: object});
+			}
+			result::Err(copy err) =>
+			{
+				return result::Err(err);
+			}
+		}
+		
+		if i + 1 < nodes.len()
+		{
+			let next = get_blank_name(store, "list");
+			store.add_triple(~[], {subject: copy blank, predicate: ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#rest", object: @BlankValue(copy next)});
+			blank = next;
+		}
+		else
+		{
+			store.add_triple(~[], {subject: copy blank, predicate: ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#rest", object: @IriValue(~"http://www.w3.org/1999/02/22-rdf-syntax-ns#nil")});
+		}
+	}
+	
+	result::Ok(@BlankValue(head))
+}
+
+priv fn expand_pname(store: &Store, name: &str) -> result::Result<~str, ~str>
+{
+	let i = str::find_char(name, ':').get();
+	let prefix = str::slice(name, 0, i);
+	match vec::find(store.namespaces, |n| {n.prefix == prefix})
+	{
+		option::Some(ref namespace) =>
+		{
+			result::Ok(namespace.path + unescape_local(str::slice(name, i + 1, str::len(name))))
+		}
+		option::None =>
+		{
+			result::Err(fmt!("Prefix '%s' has not been declared.", prefix))
+		}
+	}
+}
+
+// PN_LOCAL allows characters like ~ and . to be escaped with a backslash.
+priv fn unescape_local(text: &str) -> ~str
+{
+	if str::contains_char(text, '\\')
+	{
+		str::replace(text, "\\", "")
+	}
+	else
+	{
+		text.to_owned()
+	}
+}
+
+priv fn has_scheme(iri: &str) -> bool
+{
+	match str::find_char(iri, ':')
+	{
+		option::Some(i) =>
+		{
+			i > 0 && str::all(str::slice(iri, 0, i), |c| {char::is_alphanumeric(c) || c == '+' || c == '-' || c == '.'})
+		}
+		option::None =>
+		{
+			false
+		}
+	}
+}
+
+priv fn strip_fragment(iri: &str) -> ~str
+{
+	match str::find_char(iri, '#')
+	{
+		option::Some(i) => str::slice(iri, 0, i),
+		option::None => iri.to_owned(),
+	}
+}
+
+priv fn sorted_subjects(store: &Store) -> ~[~str]
+{
+	let mut subjects = ~[];
+	for store.subjects.each_key |subject|
+	{
+		vec::push(&mut subjects, store.terms.get_name(subject));
+	};
+	std::sort::merge_sort(subjects, |x, y| {*x <= *y})
+}
+
+priv fn sorted_entries(store: &Store, entries: @DVec<TermEntry>) -> ~[Entry]
+{
+	let decoded = do entries.get().map |entry| {decode_entry(store, entry)};
+	do std::sort::merge_sort(decoded) |x, y|
+	{
+		x.predicate < y.predicate || (x.predicate == y.predicate && *x.object <= *y.object)
+	}
+}
+
+// Finds the blank nodes which can be written as collections. To qualify every node in
+// the list has to be referenced exactly once and have only rdf:first and rdf:rest
+// predicates.
+priv fn find_lists(store: &Store) -> Lists
+{
+	let counts: HashMap<@~str, uint> = HashMap();
+	let rests: HashMap<@~str, bool> = HashMap();
+	for store.each |triple|
+	{
+		match *triple.object
+		{
+			BlankValue(ref name) =>
+			{
+				let count = option::get_default(counts.find(@copy *name), 0);
+				counts.insert(@copy *name, count + 1);
+				if triple.predicate == ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#rest"
+				{
+					rests.insert(@copy *name, true);
+				}
+			}
+			_ =>
+			{
+			}
+		}
+	};
+	
+	let lists = Lists {items: HashMap(), skipped: HashMap()};
+	for counts.each |name, count|
+	{
+		if count == 1 && !rests.contains_key(name)
+		{
+			match collect_list(store, *name, counts)
+			{
+				option::Some((items, nodes)) =>
+				{
+					lists.items.insert(name, @items);
+					for nodes.each |node| {lists.skipped.insert(@copy *node, true);}
+				}
+				option::None =>
+				{
+				}
+			}
+		}
+	};
+	lists
+}
+
+// Returns the items in the list and the names of the blank nodes used by the list. Note
+// that add_list terminates lists with a blank node which has only an rdf:rest predicate.
+priv fn collect_list(store: &Store, head: &str, counts: HashMap<@~str, uint>) -> option::Option<(~[@Object], ~[~str])>
+{
+	let mut items = ~[];
+	let mut nodes = ~[];
+	let mut name = head.to_owned();
+	loop
+	{
+		if counts.find(@copy name) != option::Some(1)
+		{
+			return option::None;
+		}
+		
+		let entries = match find_entries(store, name)
+		{
+			option::Some(entries) => do entries.get().map |entry| {decode_entry(store, entry)},
+			option::None => return option::None,
+		};
+		let first = do entries.filter |e| {e.predicate == ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#first"};
+		let rest = do entries.filter |e| {e.predicate == ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#rest"};
+		if rest.len() != 1 || first.len() > 1 || first.len() + rest.len() != entries.len()
+		{
+			return option::None;
+		}
+		
+		vec::push(&mut nodes, copy name);
+		if first.len() == 1
+		{
+			vec::push(&mut items, first[0].object);
+		}
+		
+		match *rest[0].object
+		{
+			IriValue(ref iri) if *iri == ~"http://www.w3.org/1999/02/22-rdf-syntax-ns#nil" =>
+			{
+				return option::Some((items, nodes));
+			}
+			BlankValue(copy next) if first.len() == 1 =>
+			{
+				name = next;
+			}
+			_ =>
+			{
+				return option::None;
+			}
+		}
+	}
+}
+
+priv fn subject_to_turtle(namespaces: &[Namespace], subject: &str) -> ~str
+{
+	if subject.starts_with("_:")
+	{
+		blank_to_turtle(subject)
+	}
+	else
+	{
+		iri_to_turtle(namespaces, subject)
+	}
+}
+
+priv fn predicate_to_turtle(namespaces: &[Namespace], predicate: &str) -> ~str
+{
+	if predicate == "http://www.w3.org/1999/02/22-rdf-syntax-ns#type"
+	{
+		~"a"
+	}
+	else
+	{
+		iri_to_turtle(namespaces, predicate)
+	}
+}
+
+priv fn object_to_turtle(namespaces: &[Namespace], lists: &Lists, object: @Object) -> ~str
+{
+	match *object
+	{
+		IriValue(ref iri) =>
+		{
+			iri_to_turtle(namespaces, *iri)
+		}
+		BlankValue(ref name) =>
+		{
+			match lists.items.find(@copy *name)
+			{
+				option::Some(items) =>
+				{
+					let items = do items.map |item| {object_to_turtle(namespaces, lists, *item)};
+					~"(" + str::connect(items, " ") + ~")"
+				}
+				option::None =>
+				{
+					blank_to_turtle(*name)
+				}
+			}
+		}
+		BoolValue(_) | IntValue(_) =>
+		{
+			object.to_str()
+		}
+		StringValue(ref value, ref lang) =>
+		{
+			if lang.is_empty()
+			{
+				fmt!("\"%s\"", escape_string(*value))
+			}
+			else
+			{
+				fmt!("\"%s\"@%s", escape_string(*value), *lang)
+			}
+		}
+		_ =>
+		{
+			let (value, kind, _lang) = object_to_literal(object);
+			fmt!("\"%s\"^^%s", escape_string(value), iri_to_turtle(namespaces, kind))
+		}
+	}
+}
+
+// Returns a prefixed name if the IRI can be contracted to a valid prefixed name.
+priv fn iri_to_turtle(namespaces: &[Namespace], iri: &str) -> ~str
+{
+	let name = contract_uri(namespaces, iri);
+	if name != iri.to_owned() && is_local_name(str::slice(name, str::find_char(name, ':').get() + 1, str::len(name)))
+	{
+		name
+	}
+	else
+	{
+		fmt!("<%s>", escape_iri(iri))
+	}
+}
+
+// Conservative version of PN_LOCAL.
+priv fn is_local_name(name: &str) -> bool
+{
+	if name.is_empty() || name.ends_with(".")
+	{
+		return false;
+	}
+	
+	for str::each_chari(name) |i, ch|
+	{
+		if !(char::is_alphanumeric(ch) || ch == '_' || (i > 0 && (ch == '-' || ch == '.')))
+		{
+			return false;
+		}
+	}
+	true
+}
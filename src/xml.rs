@@ -0,0 +1,563 @@
+//! Minimal non-validating XML parser used by the RDF/XML support.
+//!
+//! Document type declarations are skipped (so only the predefined and character
+//! entities are expanded) and namespace prefixes are resolved as elements are parsed.
+
+/// An expanded XML name.
+pub struct XmlName
+{
+	pub namespace: ~str,		// empty for unqualified attributes
+	pub local: ~str,
+}
+
+pub struct XmlAttribute
+{
+	pub qname: ~str,			// the name as it appeared in the document
+	pub name: XmlName,
+	pub value: ~str,
+}
+
+pub struct XmlElement
+{
+	pub qname: ~str,
+	pub name: XmlName,
+	pub attributes: ~[XmlAttribute],
+	pub children: ~[XmlNode],
+	pub line: uint,
+}
+
+pub enum XmlNode
+{
+	XmlElementNode(@XmlElement),
+	XmlTextNode(~str),				// entities have been expanded
+}
+
+// Position within the document being parsed.
+struct Scanner
+{
+	chars: ~[char],
+	mut index: uint,
+	mut line: uint,
+}
+
+/// Parses an XML document and returns the root element.
+pub fn parse_xml(text: &str) -> result::Result<@XmlElement, ~str>
+{
+	let scanner = Scanner {chars: str::chars(text), index: 0, line: 1};
+	match skip_misc(&scanner)
+	{
+		result::Ok(()) => (),
+		result::Err(copy err) => return result::Err(err),
+	}
+	if !looking_at(&scanner, "<")
+	{
+		return result::Err(~"Expected the root element on line 1");
+	}
+	
+	let scopes = ~[(~"xml", ~"http://www.w3.org/XML/1998/namespace"), (~"xmlns", ~"http://www.w3.org/2000/xmlns/")];
+	do parse_element(&scanner, scopes).chain |root|
+	{
+		do skip_misc(&scanner).chain |_r|
+		{
+			if at_end(&scanner)
+			{
+				result::Ok(root)
+			}
+			else
+			{
+				result::Err(fmt!("Unexpected content after the root element on line %?", scanner.line))
+			}
+		}
+	}
+}
+
+/// Returns the concatenated text of an element's children.
+pub fn xml_text(element: &XmlElement) -> ~str
+{
+	let mut result = ~"";
+	for element.children.each |child|
+	{
+		match *child
+		{
+			XmlTextNode(ref text) => str::push_str(&mut result, *text),
+			XmlElementNode(child) => str::push_str(&mut result, xml_text(child)),
+		}
+	}
+	result
+}
+
+/// Writes a node using the names and attributes that appeared in the document.
+pub fn xml_node_to_str(node: &XmlNode) -> ~str
+{
+	match *node
+	{
+		XmlTextNode(ref text) =>
+		{
+			escape_xml(*text)
+		}
+		XmlElementNode(element) =>
+		{
+			let mut result = ~"<" + element.qname;
+			for element.attributes.each |attr|
+			{
+				str::push_str(&mut result, fmt!(" %s=\"%s\"", attr.qname, escape_xml(attr.value)));
+			}
+			if element.children.is_empty()
+			{
+				result + ~"/>"
+			}
+			else
+			{
+				str::push_char(&mut result, '>');
+				for element.children.each |child|
+				{
+					str::push_str(&mut result, xml_node_to_str(child));
+				}
+				result + fmt!("</%s>", element.qname)
+			}
+		}
+	}
+}
+
+/// Escapes the characters which cannot appear literally within XML text or attribute values.
+pub fn escape_xml(text: &str) -> ~str
+{
+	let mut result = ~"";
+	str::reserve(&mut result, str::len(text));
+	
+	for str::each_char(text) |ch|
+	{
+		match ch
+		{
+			'<'		=> str::push_str(&mut result, "&lt;"),
+			'>'		=> str::push_str(&mut result, "&gt;"),
+			'&'		=> str::push_str(&mut result, "&amp;"),
+			'"'		=> str::push_str(&mut result, "&quot;"),
+			'\r'	=> str::push_str(&mut result, "&#13;"),
+			_		=> str::push_char(&mut result, ch),
+		}
+	}
+	
+	result
+}
+
+// ---- Private Functions -----------------------------------------------------
+
+// Scopes are searched from the end so that nested declarations hide outer ones.
+priv fn parse_element(scanner: &Scanner, scopes: &[(~str, ~str)]) -> result::Result<@XmlElement, ~str>
+{
+	let line = scanner.line;
+	advance(scanner, 1);		// <
+	let qname = parse_name(scanner);
+	if qname.is_empty()
+	{
+		return error(scanner, ~"Expected an element name");
+	}
+	
+	// Attributes.
+	let mut raw = ~[];
+	loop
+	{
+		skip_ws(scanner);
+		if looking_at(scanner, "/>") || looking_at(scanner, ">")
+		{
+			break;
+		}
+		
+		let name = parse_name(scanner);
+		if name.is_empty()
+		{
+			return error(scanner, fmt!("Expected an attribute name in '%s'", qname));
+		}
+		skip_ws(scanner);
+		if !accept(scanner, "=")
+		{
+			return error(scanner, fmt!("Expected '=' after '%s'", name));
+		}
+		skip_ws(scanner);
+		match parse_quoted(scanner)
+		{
+			result::Ok(copy value) => vec::push(&mut raw, (name, value)),
+			result::Err(copy err) => return result::Err(err),
+		}
+	}
+	
+	// Namespace declarations apply to the element they appear on.
+	let mut scopes = vec::from_slice(scopes);
+	for raw.each |pair|
+	{
+		let (name, value) = copy *pair;
+		if name == ~"xmlns"
+		{
+			vec::push(&mut scopes, (~"", value));
+		}
+		else if name.starts_with("xmlns:")
+		{
+			vec::push(&mut scopes, (str::slice(name, 6, str::len(name)), value));
+		}
+	}
+	
+	let mut attributes = ~[];
+	for raw.each |pair|
+	{
+		let (qname, value) = copy *pair;
+		if qname != ~"xmlns" && !qname.starts_with("xmlns:")
+		{
+			let name = if str::contains_char(qname, ':') {resolve_name(scopes, qname)} else {result::Ok(XmlName {namespace: ~"", local: copy qname})};
+			match name
+			{
+				result::Ok(name) => vec::push(&mut attributes, XmlAttribute {qname: qname, name: name, value: value}),
+				result::Err(copy err) => return error(scanner, err),
+			}
+		}
+	}
+	
+	let name = match resolve_name(scopes, qname)
+	{
+		result::Ok(name) => name,
+		result::Err(copy err) => return error(scanner, err),
+	};
+	
+	if accept(scanner, "/>")
+	{
+		return result::Ok(@XmlElement {qname: qname, name: name, attributes: attributes, children: ~[], line: line});
+	}
+	advance(scanner, 1);		// >
+	
+	// Content.
+	let mut children = ~[];
+	let mut text = ~"";
+	loop
+	{
+		if at_end(scanner)
+		{
+			return error(scanner, fmt!("Missing end tag for '%s'", qname));
+		}
+		else if looking_at(scanner, "</")
+		{
+			advance(scanner, 2);
+			let end = parse_name(scanner);
+			skip_ws(scanner);
+			if end != qname || !accept(scanner, ">")
+			{
+				return error(scanner, fmt!("Expected '</%s>'", qname));
+			}
+			break;
+		}
+		else if looking_at(scanner, "<!--")
+		{
+			match skip_past(scanner, "-->")
+			{
+				result::Ok(()) => (),
+				result::Err(copy err) => return result::Err(err),
+			}
+		}
+		else if looking_at(scanner, "<![CDATA[")
+		{
+			advance(scanner, 9);
+			match read_until(scanner, "]]>")
+			{
+				result::Ok(copy data) => str::push_str(&mut text, data),
+				result::Err(copy err) => return result::Err(err),
+			}
+		}
+		else if looking_at(scanner, "<?")
+		{
+			match skip_past(scanner, "?>")
+			{
+				result::Ok(()) => (),
+				result::Err(copy err) => return result::Err(err),
+			}
+		}
+		else if looking_at(scanner, "<")
+		{
+			if text.is_not_empty()
+			{
+				vec::push(&mut children, XmlTextNode(copy text));
+				text = ~"";
+			}
+			match parse_element(scanner, scopes)
+			{
+				result::Ok(child) => vec::push(&mut children, XmlElementNode(child)),
+				result::Err(copy err) => return result::Err(err),
+			}
+		}
+		else if looking_at(scanner, "&")
+		{
+			match parse_reference(scanner)
+			{
+				result::Ok(ch) => str::push_char(&mut text, ch),
+				result::Err(copy err) => return result::Err(err),
+			}
+		}
+		else
+		{
+			str::push_char(&mut text, next(scanner));
+		}
+	}
+	if text.is_not_empty()
+	{
+		vec::push(&mut children, XmlTextNode(text));
+	}
+	
+	result::Ok(@XmlElement {qname: qname, name: name, attributes: attributes, children: children, line: line})
+}
+
+priv fn resolve_name(scopes: &[(~str, ~str)], qname: &str) -> result::Result<XmlName, ~str>
+{
+	let (prefix, local) = match str::find_char(qname, ':')
+	{
+		option::Some(i) => (str::slice(qname, 0, i), str::slice(qname, i + 1, str::len(qname))),
+		option::None => (~"", qname.to_owned()),
+	};
+	
+	let mut i = scopes.len();
+	while i > 0
+	{
+		i -= 1;
+		let (ref p, ref namespace) = scopes[i];
+		if *p == prefix
+		{
+			return result::Ok(XmlName {namespace: copy *namespace, local: local});
+		}
+	}
+	
+	if prefix.is_empty()
+	{
+		result::Ok(XmlName {namespace: ~"", local: local})
+	}
+	else
+	{
+		result::Err(fmt!("Prefix '%s' has not been declared", prefix))
+	}
+}
+
+priv fn parse_quoted(scanner: &Scanner) -> result::Result<~str, ~str>
+{
+	let quote = if accept(scanner, "\"") {'"'} else if accept(scanner, "'") {'\''} else {return error(scanner, ~"Expected a quoted attribute value")};
+	
+	let mut value = ~"";
+	loop
+	{
+		if at_end(scanner)
+		{
+			return error(scanner, ~"Missing closing quote");
+		}
+		else if peek(scanner) == quote
+		{
+			advance(scanner, 1);
+			return result::Ok(value);
+		}
+		else if peek(scanner) == '&'
+		{
+			match parse_reference(scanner)
+			{
+				result::Ok(ch) => str::push_char(&mut value, ch),
+				result::Err(copy err) => return result::Err(err),
+			}
+		}
+		else
+		{
+			// Attribute value normalization.
+			let ch = next(scanner);
+			str::push_char(&mut value, if ch == '\t' || ch == '\n' || ch == '\r' {' '} else {ch});
+		}
+	}
+}
+
+priv fn parse_reference(scanner: &Scanner) -> result::Result<char, ~str>
+{
+	advance(scanner, 1);		// &
+	let mut name = ~"";
+	while !at_end(scanner) && peek(scanner) != ';' && name.len() < 10
+	{
+		str::push_char(&mut name, next(scanner));
+	}
+	if !accept(scanner, ";")
+	{
+		return error(scanner, ~"Expected ';' after entity reference");
+	}
+	
+	match name
+	{
+		~"lt" => result::Ok('<'),
+		~"gt" => result::Ok('>'),
+		~"amp" => result::Ok('&'),
+		~"quot" => result::Ok('"'),
+		~"apos" => result::Ok('\''),
+		_ if name.starts_with("#x") =>
+		{
+			parse_code_point(scanner, str::slice(name, 2, str::len(name)), 16)
+		}
+		_ if name.starts_with("#") =>
+		{
+			parse_code_point(scanner, str::slice(name, 1, str::len(name)), 10)
+		}
+		_ =>
+		{
+			error(scanner, fmt!("Unknown entity '&%s;'", name))
+		}
+	}
+}
+
+priv fn parse_code_point(scanner: &Scanner, digits: &str, radix: uint) -> result::Result<char, ~str>
+{
+	match uint::from_str_radix(digits, radix)
+	{
+		option::Some(value) if digits.is_not_empty() => result::Ok(value as char),
+		_ => error(scanner, fmt!("Bad character reference '%s'", digits)),
+	}
+}
+
+// Skips the prolog and anything else that may appear outside the root element.
+priv fn skip_misc(scanner: &Scanner) -> result::Result<(), ~str>
+{
+	loop
+	{
+		skip_ws(scanner);
+		let result = if looking_at(scanner, "<?")
+		{
+			skip_past(scanner, "?>")
+		}
+		else if looking_at(scanner, "<!--")
+		{
+			skip_past(scanner, "-->")
+		}
+		else if looking_at(scanner, "<!")
+		{
+			skip_doctype(scanner)
+		}
+		else
+		{
+			return result::Ok(());
+		};
+		if result.is_err()
+		{
+			return result;
+		}
+	}
+}
+
+// The internal subset is skipped so entities it declares are not supported.
+priv fn skip_doctype(scanner: &Scanner) -> result::Result<(), ~str>
+{
+	let mut depth = 0;
+	while !at_end(scanner)
+	{
+		match next(scanner)
+		{
+			'[' => depth += 1,
+			']' => depth -= 1,
+			'>' if depth == 0 => return result::Ok(()),
+			_ => (),
+		}
+	}
+	error(scanner, ~"Unterminated document type declaration")
+}
+
+priv fn skip_past(scanner: &Scanner, terminator: &str) -> result::Result<(), ~str>
+{
+	do read_until(scanner, terminator).chain |_text| {result::Ok(())}
+}
+
+// Returns the text before the terminator and consumes the terminator.
+priv fn read_until(scanner: &Scanner, terminator: &str) -> result::Result<~str, ~str>
+{
+	let mut text = ~"";
+	while !at_end(scanner)
+	{
+		if accept(scanner, terminator)
+		{
+			return result::Ok(text);
+		}
+		str::push_char(&mut text, next(scanner));
+	}
+	error(scanner, fmt!("Expected '%s'", terminator))
+}
+
+priv fn parse_name(scanner: &Scanner) -> ~str
+{
+	let mut name = ~"";
+	while !at_end(scanner) && is_name_char(peek(scanner))
+	{
+		str::push_char(&mut name, next(scanner));
+	}
+	name
+}
+
+priv pure fn is_name_char(ch: char) -> bool
+{
+	char::is_alphanumeric(ch) || ch == '_' || ch == ':' || ch == '-' || ch == '.' || (ch as uint) > 0x7F
+}
+
+priv fn skip_ws(scanner: &Scanner)
+{
+	while !at_end(scanner) && char::is_whitespace(peek(scanner))
+	{
+		advance(scanner, 1);
+	}
+}
+
+priv fn looking_at(scanner: &Scanner, text: &str) -> bool
+{
+	let mut i = scanner.index;
+	for str::each_char(text) |ch|
+	{
+		if i >= scanner.chars.len() || scanner.chars[i] != ch
+		{
+			return false;
+		}
+		i += 1;
+	}
+	true
+}
+
+priv fn accept(scanner: &Scanner, text: &str) -> bool
+{
+	if looking_at(scanner, text)
+	{
+		advance(scanner, str::char_len(text));
+		true
+	}
+	else
+	{
+		false
+	}
+}
+
+priv fn error<T>(scanner: &Scanner, mesg: ~str) -> result::Result<T, ~str>
+{
+	result::Err(fmt!("%s on line %?", mesg, scanner.line))
+}
+
+priv pure fn at_end(scanner: &Scanner) -> bool
+{
+	scanner.index >= scanner.chars.len()
+}
+
+priv fn peek(scanner: &Scanner) -> char
+{
+	scanner.chars[scanner.index]
+}
+
+priv fn next(scanner: &Scanner) -> char
+{
+	let ch = scanner.chars[scanner.index];
+	advance(scanner, 1);
+	ch
+}
+
+priv fn advance(scanner: &Scanner, count: uint)
+{
+	for count.times
+	{
+		if scanner.index < scanner.chars.len()
+		{
+			if scanner.chars[scanner.index] == '\n'
+			{
+				scanner.line += 1;
+			}
+			scanner.index += 1;
+		}
+	}
+}